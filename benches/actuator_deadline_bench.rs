@@ -26,6 +26,7 @@ fn actuator_deadline_bench(c: &mut Criterion) {
         filtered: 0.1,
         raw: 0.1,
         timestamp: Instant::now(),
+        sensor_timestamp: Instant::now(),
         seq: 1,
     };
 
@@ -37,10 +37,11 @@ fn multi_actuator_dispatch_bench(c: &mut Criterion) {
                 let sync_clone = sync.clone();
                 b.iter(|| {
                     let pkt = ProcessedPacket {
-                        sensor_type: s.clone(),
+                        sensor_type: *s,
                         filtered: 10.0,
                         raw: 10.0,
                         timestamp: Instant::now(),
+                        sensor_timestamp: Instant::now(),
                         seq: 1,
                     };
                     actuators.dispatch(pkt, sync_clone.clone());
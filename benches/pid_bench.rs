@@ -27,6 +27,7 @@ fn pid_compute_bench(c: &mut Criterion) {
         filtered: 98.5,
         raw: 100.0,
         timestamp: Instant::now(),
+        sensor_timestamp: Instant::now(),
         seq: 1,
     };
 
@@ -138,6 +138,7 @@ fn receiver_latency_bench(c: &mut Criterion) {
                     filtered: 100.0,
                     raw: 100.0,
                     timestamp: Instant::now(),
+                    sensor_timestamp: Instant::now(),
                     seq: 1,
                 };
 
@@ -149,7 +150,7 @@ fn receiver_latency_bench(c: &mut Criterion) {
                 
                 // CRITICAL: receiver.run() must be non-blocking or 
                 // modified to process only the available packet.
-                black_box(receiver.run()); 
+                black_box(&mut receiver).run(); 
             },
             BatchSize::SmallInput
         )
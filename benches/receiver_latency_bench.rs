@@ -63,6 +63,7 @@ fn receiver_latency_bench(c: &mut Criterion) {
                     filtered: 100.0,
                     raw: 100.0,
                     timestamp: Instant::now(),
+                    sensor_timestamp: Instant::now(),
                     seq: 1,
                 };
 
@@ -77,7 +78,7 @@ fn receiver_latency_bench(c: &mut Criterion) {
                 // after the packet is processed, breaking the while loop.
                 drop(tx); 
 
-                black_box(receiver.run());
+                black_box(&mut receiver).run();
             },
             BatchSize::SmallInput
         )
@@ -35,6 +35,7 @@ fn bench_transmitter(c:&mut Criterion){
         filtered: 99.0,
         raw: 102.0,
         timestamp: Instant::now(),
+        sensor_timestamp: Instant::now(),
         seq: 1,
 
     };
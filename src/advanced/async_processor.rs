@@ -23,7 +23,7 @@ use crate::component_a::{
     sync_manager::SyncManager,
 };
 use crate::advanced::async_transmitter::async_transmit;
-use crate::utils::metrics::{SharedMetrics, push_capped, EventRecorder, Event, DeadlineComponent,push_capped_u64};
+use crate::utils::metrics::{SharedMetrics, EventRecorder, Event, DeadlineComponent};
 
 
 const PROCESS_DEADLINE_US: u64 = 200;
@@ -110,6 +110,7 @@ pub async fn async_processor_task(
             filtered: avg,
             raw: data.reading,
             timestamp: cycle_start,
+            sensor_timestamp: data.timestamp,
             seq: data.seq,
         };
 
@@ -119,16 +120,16 @@ pub async fn async_processor_task(
         let elapsed_us = cycle_start.elapsed().as_micros() as u64;
 
         {
-            let mut m = metrics.lock().unwrap_or_else(|e| e.into_inner());
+            let live = metrics.live();
 
-            push_capped_u64(&mut m.latency_us, elapsed_us);
-            m.total_cycles += 1;
+            live.latency_us.push(elapsed_us);
+            live.record_cycle();
 
             if elapsed_us > PROCESS_DEADLINE_US {
                 consecutive_overruns += 1;
 
                 if consecutive_overruns >= MISS_CONFIRM_THRESHOLD {
-                    m.record_deadline_miss(DeadlineComponent::Processor);
+                    live.record_deadline_miss(DeadlineComponent::Processor);
                     sync.record_proc_miss();
                     consecutive_overruns = 0;
                 }
@@ -137,11 +138,7 @@ pub async fn async_processor_task(
             }
 
             // Keep plots aligned with threaded processor
-            match data.sensor_type {
-                SensorType::Force => push_capped(&mut m.force, avg),
-                SensorType::Position => push_capped(&mut m.position, avg),
-                SensorType::Temperature => push_capped(&mut m.temperature, avg),
-            }
+            live.reading(data.sensor_type).push(avg);
         }
 
         async_transmit(&tx, pkt, sync.clone(), event_recorder.clone()).await;
@@ -21,7 +21,7 @@ use crate::component_a::{
     sync_manager::SyncManager,
 };
 
-use crate::utils::metrics::{SharedMetrics, push_capped_u64, EventRecorder, Event, DeadlineComponent, push_capped};
+use crate::utils::metrics::{SharedMetrics, EventRecorder, Event, DeadlineComponent};
 
 const PERIOD_MS: u64 = 5;
 
@@ -49,7 +49,7 @@ pub async fn async_sensor(
         event_recorder.record(Event::SensorRelease {
             seq,
             ts_ns: t0_ns,
-            sensor_type: sensor_type.name().to_string(),
+            sensor_type: sensor_type.name(),
         });
 
         // Measure actual period and jitter
@@ -64,17 +64,12 @@ pub async fn async_sensor(
             sync.record_proc_miss();
 
             // Mirror threaded sensor: record deadline miss in SharedMetrics for SENSOR
-            let mut m = metrics.lock().expect("metrics mutex poisoned");
-            m.record_deadline_miss(DeadlineComponent::Sensor);
-            // keep metrics lock short — we'll update other fields below as needed
+            metrics.live().record_deadline_miss(DeadlineComponent::Sensor);
         }
 
         // Record jitter and cycle count (shared with threaded sensor)
-        {
-            let mut m = metrics.lock().expect("metrics mutex poisoned");
-            m.total_cycles += 1;
-            push_capped_u64(&mut m.jitter_us, jitter_us);
-        }
+        metrics.live().record_cycle();
+        metrics.live().jitter_us.push(jitter_us);
 
         // Simulate reading
         let base = sensor_type.base_value();
@@ -121,12 +116,7 @@ pub async fn async_sensor(
         // If the sample was enqueued, update the per-sensor data buffers (for plots),
         // matching the threaded sensor which only pushes when send succeeds.
         if enqueued {
-            let mut m = metrics.lock().expect("metrics mutex poisoned");
-            match sensor_type {
-                SensorType::Force => push_capped(&mut m.force, filtered),
-                SensorType::Position => push_capped(&mut m.position, filtered),
-                SensorType::Temperature => push_capped(&mut m.temperature, filtered),
-            }
+            metrics.live().reading(sensor_type).push(filtered);
         }
 
         seq += 1;
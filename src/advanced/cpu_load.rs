@@ -77,7 +77,7 @@ pub fn spawn_cpu_load(
                     iter = iter.wrapping_add(1);
 
                     // Optional: Yield occasionally to simulate "background" priority
-                    if iter % 1_000_000 == 0 {
+                    if iter.is_multiple_of(1_000_000) {
                         thread::yield_now(); 
                     }
                 }
@@ -7,6 +7,9 @@
 //! - **Web server:** HTTP listener on port 8080 serving HTML dashboard + JSON metrics + live SVG.
 //!
 //! Per-component deadline tracking displayed: Sensor/Processor/Actuator miss counts enable bottleneck identification.
+//! `metrics.json` also carries the SyncManager running aggregates (`sync`), refreshed by the run loop.
+//! The fail-safe system mode (`mode`: current mode, time in it, last transition) is shown in both.
+//! `metrics.json` `routing`: sensor → actuator deliveries per route, fan-out and fan-in.
 
 use plotters::{
     coord::Shift,
@@ -21,13 +24,35 @@ use std::{
     thread,
     time::Duration,
     sync::atomic::{AtomicBool, Ordering},
-    collections::VecDeque,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
 };
 use log::{info, error};
 
 use socket2::{Socket, Domain, Type, SockAddr};
 
-use crate::utils::metrics::{SharedMetrics, MAX_POINTS};
+use crate::utils::{
+    bus::SubscriberStats,
+    control_quality::ControlQuality,
+    metrics::{ComputeTiming, SharedMetrics, SequenceStats, MAX_POINTS},
+};
+use crate::component_a::{
+    diagnostics::LatencyHistogram,
+    sync_manager::{Diagnostics, SyncMode},
+};
+use crate::component_b::{
+    failsafe::{ModeStatus, SystemMode},
+    routing::{RoutingStats, delivery_ratio},
+};
+
+/// Waveform snapshot: (force, position, temperature, gripper, motor, stabiliser).
+type WaveformSnapshot = (
+    VecDeque<f64>,
+    VecDeque<f64>,
+    VecDeque<f64>,
+    VecDeque<f64>,
+    VecDeque<f64>,
+    VecDeque<f64>,
+);
 
 /// Starts dashboard system: render thread + web server thread.
 /// Returns: (render_handle, web_handle, shutdown_flag).
@@ -56,31 +81,33 @@ pub fn start_dashboard_system(
         let mut first = true;
 
         while render_flag.load(Ordering::Relaxed) {
-            // Snapshot metrics (read-only, minimal lock time)
-            let (snapshot, miss_sensor, miss_processor, miss_actuator, total_cycles, last_jitter, last_latency) = {
-                let m = match render_metrics.lock() {
-                    Ok(g) => g,
-                    Err(p) => p.into_inner(),
-                };
+            // Snapshot metrics (copied from the lock-free rings; RT writers never wait on the renderer)
+            let (snapshot, miss_sensor, miss_processor, miss_actuator, total_cycles, last_jitter, last_latency, seq_stats, stale, limits, mode, laws, quality, bus_stats, routing, sync) = {
+                let m = render_metrics.snapshot();
 
                 let last_jitter = m.jitter_us.back().cloned().unwrap_or(0);
                 let last_latency = m.latency_us.back().cloned().unwrap_or(0);
+                let sync = sync_json(m.sync_mode, &m.sync_diagnostics);
+                let limits = actuator_limits_json(&m.saturations, &m.rate_limits);
+                let routing = routing_json(&m.routing);
 
                 (
-                    (
-                        m.force.clone(),
-                        m.position.clone(),
-                        m.temperature.clone(),
-                        m.gripper.clone(),
-                        m.motor.clone(),
-                        m.stabiliser.clone(),
-                    ),
+                    (m.force, m.position, m.temperature, m.gripper, m.motor, m.stabiliser),
                     m.miss_sensor,
                     m.miss_processor,
                     m.miss_actuator,
                     m.total_cycles,
                     last_jitter,
                     last_latency,
+                    m.seq_stats,
+                    m.stale_rejections,
+                    limits,
+                    m.mode,
+                    m.control_laws,
+                    m.control_quality,
+                    m.bus_stats,
+                    routing,
+                    sync,
                 )
             };
 
@@ -108,18 +135,30 @@ pub fn start_dashboard_system(
                     total_cycles,
                     last_jitter,
                     last_latency,
+                    &seq_stats,
+                    &stale,
+                    &mode,
                 );
 
                 // Cache JSON for web server (per-component metrics)
                 let json = format!(
-                    r#"{{"miss_sensor":{},"miss_processor":{},"miss_actuator":{},"total_misses":{},"cycles_observed":{},"last_jitter_us":{},"last_latency_us":{}}}"#,
+                    r#"{{"miss_sensor":{},"miss_processor":{},"miss_actuator":{},"total_misses":{},"cycles_observed":{},"last_jitter_us":{},"last_latency_us":{},"sequence":{},"stale_rejections":{},"actuator_limits":{},"mode":{},"control_laws":{},"control_quality":{},"bus":{},"routing":{},"sync":{}}}"#,
                     miss_sensor,
                     miss_processor,
                     miss_actuator,
                     miss_sensor + miss_processor + miss_actuator,
                     total_cycles,
                     last_jitter,
-                    last_latency
+                    last_latency,
+                    sequence_json(&seq_stats),
+                    stale_json(&stale),
+                    limits,
+                    mode_json(&mode),
+                    control_laws_json(&laws),
+                    control_quality_json(&quality),
+                    bus_json(&bus_stats),
+                    routing,
+                    sync
                 );
 
                 if let Ok(mut w) = cached_json_clone.write() {
@@ -151,23 +190,20 @@ pub fn start_dashboard_system(
 
 /// Renders SVG dashboard: 3x2 grid of waveforms + status bar with per-component metrics.
 /// Displays observational data (no verdict/color-coding); enables bottleneck identification.
+#[allow(clippy::too_many_arguments)]
 fn render_svg(
-    data: &(
-        VecDeque<f64>,
-        VecDeque<f64>,
-        VecDeque<f64>,
-        VecDeque<f64>,
-        VecDeque<f64>,
-        VecDeque<f64>,
-    ),
+    data: &WaveformSnapshot,
     miss_sensor: u64,
     miss_processor: u64,
     miss_actuator: u64,
     total_cycles: u64,
     last_jitter: u64,
     last_latency: u64,
+    seq_stats: &BTreeMap<&'static str, SequenceStats>,
+    stale: &BTreeMap<&'static str, u64>,
+    mode: &ModeStatus,
 ) {
-    let root = SVGBackend::new("data/LiveDashbaord/dashboard_temp.svg", (1280, 950))
+    let root = SVGBackend::new("data/LiveDashbaord/dashboard_temp.svg", (1280, 980))
         .into_drawing_area();
     root.fill(&WHITE).ok();
 
@@ -191,7 +227,43 @@ fn render_svg(
         total_misses, miss_sensor, miss_processor, miss_actuator, total_cycles, last_jitter, last_latency
     );
 
-    status_area.draw(&Text::new(status_text, (40, 30), status_font)).ok();
+    status_area.draw(&Text::new(status_text, (40, 30), status_font.clone())).ok();
+
+    // Sequence accounting: per-sensor loss rate and worst burst seen at the receiver
+    let seq_text = seq_stats
+        .iter()
+        .map(|(name, s)| {
+            format!(
+                "{}: loss {:.2}% (burst {}, dup {}, ooo {})",
+                name,
+                s.loss_rate() * 100.0,
+                s.max_burst_loss,
+                s.duplicates,
+                s.out_of_order
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+    status_area.draw(&Text::new(format!("Sequence: {}", seq_text), (40, 60), status_font.clone())).ok();
+
+    // Stale-command rejections (freshness limit), reported apart from deadline misses
+    let stale_text = stale
+        .iter()
+        .map(|(name, n)| format!("{}={}", name, n))
+        .collect::<Vec<_>>()
+        .join(", ");
+    status_area.draw(&Text::new(format!("Stale rejections: {}", stale_text), (40, 90), status_font.clone())).ok();
+
+    // Fail-safe mode and the transition that led to it
+    let mode_text = match (mode.enabled, mode.last) {
+        (false, _) => "supervisor disabled".to_string(),
+        (true, None) => format!("{} for {:.1} s", mode.mode.name(), mode.since_s),
+        (true, Some(t)) => format!(
+            "{} since {:.1} s (from {}: {} at {}) | transitions: {}",
+            mode.mode.name(), t.at_s, t.from.name(), t.reason, t.source, mode.transitions
+        ),
+    };
+    status_area.draw(&Text::new(format!("Mode: {}", mode_text), (40, 120), status_font)).ok();
 
     root.present().ok();
 
@@ -230,6 +302,259 @@ fn append_metrics_comment(
     }
 }
 
+/// Per-sensor sequence accounting as a JSON object keyed by sensor name.
+fn sequence_json(seq_stats: &BTreeMap<&'static str, SequenceStats>) -> String {
+    let entries = seq_stats
+        .iter()
+        .map(|(name, s)| {
+            format!(
+                r#""{}":{{"expected":{},"received":{},"lost":{},"loss_rate":{:.6},"max_burst_loss":{},"gaps":{},"duplicates":{},"out_of_order":{},"late_discarded":{}}}"#,
+                name,
+                s.expected,
+                s.received,
+                s.lost,
+                s.loss_rate(),
+                s.max_burst_loss,
+                s.gaps,
+                s.duplicates,
+                s.out_of_order,
+                s.late_discarded
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", entries)
+}
+
+/// Stale-command rejections as a JSON object keyed by actuator name.
+fn stale_json(stale: &BTreeMap<&'static str, u64>) -> String {
+    let entries = stale
+        .iter()
+        .map(|(name, n)| format!(r#""{}":{}"#, name, n))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", entries)
+}
+
+/// Saturation and rate-limit onsets as a JSON object keyed by actuator name.
+fn actuator_limits_json(saturations: &BTreeMap<&'static str, u64>, rate_limits: &BTreeMap<&'static str, u64>) -> String {
+    let entries = saturations
+        .keys()
+        .chain(rate_limits.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|name| {
+            format!(
+                r#""{}":{{"saturations":{},"rate_limits":{}}}"#,
+                name,
+                saturations.get(name).copied().unwrap_or(0),
+                rate_limits.get(name).copied().unwrap_or(0)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", entries)
+}
+
+/// Fail-safe mode, time per mode (s) and the last transition as a JSON object.
+fn mode_json(mode: &ModeStatus) -> String {
+    let time_in_mode = SystemMode::ALL
+        .iter()
+        .map(|m| format!(r#""{}":{:.3}"#, m.name(), mode.time_in_mode_s[*m as usize]))
+        .collect::<Vec<_>>()
+        .join(",");
+    let last = match mode.last {
+        Some(t) => format!(
+            r#"{{"from":"{}","to":"{}","reason":"{}","source":"{}","at_s":{:.3}}}"#,
+            t.from.name(), t.to.name(), t.reason, t.source, t.at_s
+        ),
+        None => "null".to_string(),
+    };
+    format!(
+        r#"{{"enabled":{},"mode":"{}","since_s":{:.3},"transitions":{},"time_in_mode_s":{{{}}},"last_transition":{}}}"#,
+        mode.enabled, mode.mode.name(), mode.since_s, mode.transitions, time_in_mode, last
+    )
+}
+
+/// Control-law compute time per law (ns; percentiles are log2 bucket bounds) as a JSON object.
+fn control_laws_json(laws: &BTreeMap<&'static str, ComputeTiming>) -> String {
+    let entries = laws
+        .iter()
+        .map(|(name, t)| {
+            format!(
+                r#""{}":{{"count":{},"mean_ns":{:.0},"p50_ns":{},"p99_ns":{},"max_ns":{},"over_deadline":{}}}"#,
+                name,
+                t.count,
+                t.mean_ns(),
+                t.percentile_ns(0.50),
+                t.percentile_ns(0.99),
+                t.max_ns,
+                t.over_deadline
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", entries)
+}
+
+/// Control quality per actuator (averages over its setpoint steps, error integrals, running segment).
+fn control_quality_json(quality: &BTreeMap<&'static str, ControlQuality>) -> String {
+    let num = |value: Option<f64>| value.map(|v| format!("{:.4}", v)).unwrap_or_else(|| "null".into());
+    let entries = quality
+        .iter()
+        .map(|(name, q)| {
+            format!(
+                r#""{}":{{"segments":{},"steps":{},"rise_time_s":{},"overshoot_pct":{},"settling_time_s":{},"ss_error":{},"iae":{:.4},"ise":{:.4},"itae":{:.4},"setpoint":{:.4}}}"#,
+                name,
+                q.segments,
+                q.steps,
+                num(q.mean_rise_time_s()),
+                num(q.mean_overshoot_pct()),
+                num(q.mean_settling_time_s()),
+                num(q.mean_abs_ss_error()),
+                q.iae,
+                q.ise,
+                q.itae,
+                q.last.to
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", entries)
+}
+
+/// Pub/sub bus delivery as a JSON array (one entry per subscriber).
+fn bus_json(bus_stats: &[SubscriberStats]) -> String {
+    let entries = bus_stats
+        .iter()
+        .map(|s| {
+            format!(
+                r#"{{"topic":"{}","subscriber":"{}","policy":"{}","capacity":{},"published":{},"delivered":{},"dropped":{},"depth":{},"max_depth":{},"lag_avg_us":{:.1},"lag_max_us":{}}}"#,
+                s.topic,
+                s.subscriber,
+                s.policy.name(),
+                s.capacity,
+                s.published,
+                s.delivered,
+                s.dropped,
+                s.depth,
+                s.max_depth,
+                s.lag_avg_us,
+                s.lag_max_us
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("[{}]", entries)
+}
+
+/// Sensor → actuator routing as a JSON object: deliveries per route, fan-out per sensor,
+/// fan-in per actuator (channel capacity, priority, deepest queue).
+fn routing_json(routing: &RoutingStats) -> String {
+    let routes = routing
+        .routes
+        .iter()
+        .map(|r| {
+            format!(
+                r#"{{"sensor":"{}","actuator":"{}","delivered":{},"dropped":{}}}"#,
+                r.sensor, r.actuator, r.delivered, r.dropped
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let fan_out = routing
+        .fan_out
+        .iter()
+        .map(|f| {
+            format!(
+                r#"{{"sensor":"{}","actuators":{},"packets":{},"delivered":{},"dropped":{}}}"#,
+                f.sensor, f.actuators, f.packets, f.delivered, f.dropped
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let fan_in = routing
+        .fan_in
+        .iter()
+        .map(|f| {
+            format!(
+                r#"{{"actuator":"{}","role":"{}","sensors":{},"capacity":{},"priority":"{}","received":{},"dropped":{},"delivery_ratio":{:.4},"max_depth":{}}}"#,
+                f.actuator,
+                f.role.name().to_lowercase(),
+                f.sensors,
+                f.capacity,
+                f.priority.label(),
+                f.received,
+                f.dropped,
+                delivery_ratio(f.received, f.dropped),
+                f.max_depth
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(r#"{{"routes":[{}],"fan_out":[{}],"fan_in":[{}]}}"#, routes, fan_out, fan_in)
+}
+
+/// Percentile summary of one histogram, plus its non-empty buckets as [upper_us, count] pairs.
+fn histogram_json(h: &LatencyHistogram) -> String {
+    let buckets = h
+        .buckets
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| **n > 0)
+        .map(|(i, n)| format!("[{},{}]", LatencyHistogram::bucket_upper_us(i), n))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        r#"{{"count":{},"mean_us":{:.1},"p50_us":{},"p95_us":{},"p99_us":{},"max_us":{},"buckets":[{}]}}"#,
+        h.count,
+        h.mean_us(),
+        h.percentile_us(0.50),
+        h.percentile_us(0.95),
+        h.percentile_us(0.99),
+        h.max_us,
+        buckets
+    )
+}
+
+/// Per-id counters as a JSON object, ids ascending.
+fn counts_json(counts: &HashMap<u16, u64>) -> String {
+    let ordered: BTreeMap<_, _> = counts.iter().collect();
+    let entries = ordered
+        .iter()
+        .map(|(id, n)| format!(r#""{}":{}"#, id, n))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", entries)
+}
+
+/// SyncManager aggregates (sensors keyed by id); `null` until the run loop first publishes.
+fn sync_json(mode: Option<SyncMode>, d: &Diagnostics) -> String {
+    let Some(mode) = mode else {
+        return "null".to_string();
+    };
+    let jitter = d
+        .jitter_hist
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .iter()
+        .map(|(id, h)| format!(r#""{}":{}"#, id, histogram_json(h)))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        r#"{{"mode":"{:?}","samples":{},"proc_misses":{},"tx_drops":{},"dropped_logs":{},"custom":{},"jitter_us":{{{}}},"rx_latency_us":{}}}"#,
+        mode,
+        counts_json(&d.sample_count),
+        d.proc_miss_count,
+        d.tx_drop_count,
+        d.dropped_logs,
+        counts_json(&d.custom_count),
+        jitter,
+        histogram_json(&d.rx_latency_hist)
+    )
+}
+
 /// Starts HTTP server on port 8080.
 /// Serves: dashboard.html (GET /), dashboard.svg (GET /dashboard.svg), metrics.json (GET /metrics.json).
 /// Each request spawned in separate thread; respects shutdown flag.
@@ -313,24 +638,30 @@ fn handle_http_request_with_cache(
     } else if line.contains("GET /metrics.json") {
         // Serve per-component metrics (live or cached)
         if renderer_active.load(Ordering::Relaxed) {
-            let m = match metrics.lock() {
-                Ok(g) => g,
-                Err(p) => p.into_inner(),
-            };
+            let m = metrics.snapshot();
 
             let last_jitter = m.jitter_us.back().cloned().unwrap_or(0);
             let last_latency = m.latency_us.back().cloned().unwrap_or(0);
 
             let total_misses = m.miss_sensor + m.miss_processor + m.miss_actuator;
             let json = format!(
-                r#"{{"miss_sensor":{},"miss_processor":{},"miss_actuator":{},"total_misses":{},"cycles_observed":{},"last_jitter_us":{},"last_latency_us":{}}}"#,
+                r#"{{"miss_sensor":{},"miss_processor":{},"miss_actuator":{},"total_misses":{},"cycles_observed":{},"last_jitter_us":{},"last_latency_us":{},"sequence":{},"stale_rejections":{},"actuator_limits":{},"mode":{},"control_laws":{},"control_quality":{},"bus":{},"routing":{},"sync":{}}}"#,
                 m.miss_sensor,
                 m.miss_processor,
                 m.miss_actuator,
                 total_misses,
                 m.total_cycles,
                 last_jitter,
-                last_latency
+                last_latency,
+                sequence_json(&m.seq_stats),
+                stale_json(&m.stale_rejections),
+                actuator_limits_json(&m.saturations, &m.rate_limits),
+                mode_json(&m.mode),
+                control_laws_json(&m.control_laws),
+                control_quality_json(&m.control_quality),
+                bus_json(&m.bus_stats),
+                routing_json(&m.routing),
+                sync_json(m.sync_mode, &m.sync_diagnostics)
             );
 
             format!(
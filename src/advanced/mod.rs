@@ -5,4 +5,6 @@ pub mod async_sensor;
 pub mod async_processor;
 pub mod async_transmitter;
 pub mod async_pipeline;
+pub mod mqtt;
+pub mod priority_inversion;
 
@@ -0,0 +1,742 @@
+//! mqtt.rs
+//! Optional MQTT bridge: live telemetry out, setpoint/tuning commands in.
+//!
+//! - Minimal MQTT 3.1.1 over plain TCP (QoS 0 only): CONNECT, PUBLISH, SUBSCRIBE, PINGREQ, DISCONNECT.
+//! - Bridge subscribes to the bus and publishes `ProcessedPacket`, `Feedback` and periodic
+//!   metric snapshots as JSON; inbound setpoint/tuning messages become bus `commands`.
+//! - Embedded minimal broker (loopback) so the bridge and external tools work without
+//!   installing one; any 3.1.1 broker (e.g. mosquitto on 127.0.0.1:1883) works too.
+//! - Enabled and addressed by `[mqtt]` in the run configuration (off by default).
+//!
+//! Topic scheme (`<prefix>` defaults to `rts`):
+//! - `<prefix>/processed/<Sensor>`        {"seq","filtered","raw","age_us"}
+//! - `<prefix>/feedback/<actuator>`       {"kind","value"}
+//! - `<prefix>/metrics`                   periodic snapshot
+//! - `<prefix>/cmd/setpoint/<Sensor>`     payload: number, or `resume` to return to the profile (inbound)
+//! - `<prefix>/cmd/tuning`                payload: `kp=1.2,ki=0.01,kd=0.2` (any subset, inbound)
+//! - `<prefix>/cmd/tuning/<Controller>`   same, for one controller (`Receiver` or a routed actuator name)
+//! - `<prefix>/cmd/autotune[/<Controller>]` payload: empty or a rule (`ziegler_nichols`, ...); starts
+//!   a relay auto-tune experiment (controllers that drive a plant only)
+
+use crossbeam::channel::{unbounded, Receiver, RecvTimeoutError};
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex, MutexGuard, atomic::{AtomicBool, Ordering}},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use log::{info, error, debug};
+use serde::Deserialize;
+
+use crate::component_a::{processor::ProcessedPacket, sensor::SensorType};
+use crate::component_b::{
+    autotune::TuningRule,
+    receiver::RECEIVER,
+    controller::ControlCommand,
+    feedback::{Feedback, FeedbackKind},
+};
+use crate::utils::{
+    bus::{Bus, OverflowPolicy},
+    metrics::SharedMetrics,
+};
+
+// MQTT 3.1.1 control packet types (upper nibble of the fixed header)
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;         // Reserved flags 0b0010
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+const DISCONNECT: u8 = 0xE0;
+
+const KEEP_ALIVE_SECS: u16 = 30;
+const SESSION_WRITE_TIMEOUT: Duration = Duration::from_millis(100);  // Broker: a stalled subscriber is dropped
+
+/// Inbound message: (topic, payload).
+pub type MqttMessage = (String, Vec<u8>);
+const MAX_PACKET_LEN: usize = 256 * 1024;  // Reject absurd remaining lengths
+
+// ============================================================================
+// Wire encoding
+// ============================================================================
+
+fn encode_remaining_len(mut len: usize, out: &mut Vec<u8>) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+fn push_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 5);
+    out.push(header);
+    encode_remaining_len(body.len(), &mut out);
+    out.extend_from_slice(body);
+    out
+}
+
+fn connect_packet(client_id: &str, keep_alive_secs: u16) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, "MQTT");
+    body.push(4);        // Protocol level 3.1.1
+    body.push(0x02);     // Clean session
+    body.extend_from_slice(&keep_alive_secs.to_be_bytes());
+    push_str(&mut body, client_id);
+    packet(CONNECT, &body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    push_str(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(PUBLISH, &body)
+}
+
+fn subscribe_packet(packet_id: u16, filters: &[String]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&packet_id.to_be_bytes());
+    for f in filters {
+        push_str(&mut body, f);
+        body.push(0);    // Requested QoS 0
+    }
+    packet(SUBSCRIBE, &body)
+}
+
+/// Reads one control packet: (fixed header byte, body).
+fn read_packet(stream: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 1];
+    stream.read_exact(&mut header)?;
+
+    let mut len = 0usize;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte)?;
+        len |= ((byte[0] & 0x7F) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 21 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "malformed remaining length"));
+        }
+    }
+    if len > MAX_PACKET_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "packet too large"));
+    }
+
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body)?;
+    Ok((header[0], body))
+}
+
+fn read_str(body: &[u8], pos: &mut usize) -> Option<String> {
+    let len = u16::from_be_bytes([*body.get(*pos)?, *body.get(*pos + 1)?]) as usize;
+    let s = body.get(*pos + 2..*pos + 2 + len)?;
+    *pos += 2 + len;
+    String::from_utf8(s.to_vec()).ok()
+}
+
+/// Topic and payload of a PUBLISH (QoS > 0 packet identifiers are skipped).
+fn parse_publish(header: u8, body: &[u8]) -> Option<MqttMessage> {
+    let mut pos = 0;
+    let topic = read_str(body, &mut pos)?;
+    if (header >> 1) & 0x03 > 0 {
+        pos += 2;
+    }
+    Some((topic, body.get(pos..)?.to_vec()))
+}
+
+/// MQTT topic filter matching (`+` one level, `#` remaining levels).
+fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut f = filter.split('/');
+    let mut t = topic.split('/');
+    loop {
+        match (f.next(), t.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(a), Some(b)) if a == b => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+// ============================================================================
+// Client
+// ============================================================================
+
+/// Blocking QoS-0 MQTT client. Inbound PUBLISH messages arrive on the channel
+/// returned by `connect` (read by a background thread).
+pub struct MqttClient {
+    stream: TcpStream,
+    next_packet_id: u16,
+    last_send: Instant,
+}
+
+impl MqttClient {
+    pub fn connect(addr: &str, client_id: &str) -> io::Result<(Self, Receiver<MqttMessage>)> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true).ok();
+        stream.write_all(&connect_packet(client_id, KEEP_ALIVE_SECS))?;
+
+        let (header, body) = read_packet(&mut stream)?;
+        if header != CONNACK || body.get(1) != Some(&0) {
+            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "broker refused CONNECT"));
+        }
+
+        let mut reader = stream.try_clone()?;
+        let (tx, rx) = unbounded();
+        thread::Builder::new()
+            .name("mqtt-client-rx".into())
+            .spawn(move || {
+                while let Ok((header, body)) = read_packet(&mut reader) {
+                    if header & 0xF0 == PUBLISH
+                        && let Some(msg) = parse_publish(header, &body)
+                        && tx.send(msg).is_err()
+                    {
+                        break;
+                    }
+                    // SUBACK / PINGRESP need no action for QoS 0
+                }
+            })?;
+
+        Ok((
+            Self {
+                stream,
+                next_packet_id: 1,
+                last_send: Instant::now(),
+            },
+            rx,
+        ))
+    }
+
+    pub fn publish(&mut self, topic: &str, payload: &[u8]) -> io::Result<()> {
+        self.send(&publish_packet(topic, payload))
+    }
+
+    pub fn subscribe(&mut self, filters: &[String]) -> io::Result<()> {
+        let id = self.next_packet_id;
+        self.next_packet_id = self.next_packet_id.wrapping_add(1).max(1);
+        self.send(&subscribe_packet(id, filters))
+    }
+
+    /// Sends PINGREQ if nothing was sent for half the keep-alive interval.
+    pub fn keep_alive(&mut self) -> io::Result<()> {
+        if self.last_send.elapsed() >= Duration::from_secs(KEEP_ALIVE_SECS as u64 / 2) {
+            self.send(&[PINGREQ, 0])?;
+        }
+        Ok(())
+    }
+
+    pub fn disconnect(mut self) {
+        let _ = self.stream.write_all(&[DISCONNECT, 0]);
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.stream.write_all(bytes)?;
+        self.last_send = Instant::now();
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Embedded broker
+// ============================================================================
+
+/// Writer half of a session: every frame to the client (fan-out, SUBACK, PINGRESP) goes through
+/// it, so frames written by different session threads never interleave on the wire.
+type SessionWriter = Arc<Mutex<TcpStream>>;
+
+struct BrokerSession {
+    id: u64,
+    writer: SessionWriter,          // Shared so fan-out writes happen outside the sessions lock
+    filters: Vec<String>,
+}
+
+/// Minimal in-process broker (QoS 0, no retained messages, no auth); loopback use only.
+pub struct MqttBroker {
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MqttBroker {
+    /// Binds `addr` (e.g. "127.0.0.1:1883", or port 0 for an ephemeral port) and starts accepting.
+    pub fn start(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let sessions: Arc<Mutex<Vec<BrokerSession>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let flag = running.clone();
+        let handle = thread::Builder::new()
+            .name("mqtt-broker".into())
+            .spawn(move || {
+                let mut next_id = 0u64;
+                while flag.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, peer)) => {
+                            next_id += 1;
+                            let id = next_id;
+                            let sessions = sessions.clone();
+                            debug!("[MQTT broker] client {} connected from {}", id, peer);
+                            thread::spawn(move || {
+                                if let Err(e) = serve_session(id, &stream, &sessions) {
+                                    debug!("[MQTT broker] client {} closed: {}", id, e);
+                                }
+                                let _ = stream.shutdown(Shutdown::Both);
+                                lock(&sessions).retain(|s| s.id != id);
+                            });
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(20));
+                        }
+                        Err(e) => error!("[MQTT broker] accept error: {}", e),
+                    }
+                }
+
+                // Close every session so their reader threads exit
+                let open: Vec<BrokerSession> = lock(&sessions).drain(..).collect();
+                for s in open {
+                    shutdown(&s.writer);
+                }
+            })?;
+
+        info!("[MQTT broker] listening on {}", addr);
+        Ok(Self {
+            addr,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn stop(mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(h) = self.handle.take() {
+            let _ = h.join();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Writes one whole frame to the session's client.
+fn write_frame(writer: &Mutex<TcpStream>, frame: &[u8]) -> io::Result<()> {
+    lock(writer).write_all(frame)
+}
+
+/// Closes the client's socket (its reader thread then exits and deregisters the session).
+fn shutdown(writer: &Mutex<TcpStream>) {
+    let _ = lock(writer).shutdown(Shutdown::Both);
+}
+
+/// Handles one client connection until it disconnects.
+fn serve_session(id: u64, mut stream: &TcpStream, sessions: &Mutex<Vec<BrokerSession>>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_nodelay(true).ok();
+
+    let (header, _) = read_packet(&mut stream)?;
+    if header != CONNECT {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "expected CONNECT"));
+    }
+    stream.write_all(&[CONNACK, 2, 0, 0])?;
+
+    // The send timeout is per socket, so it also bounds the SUBACK / PINGRESP replies: a failed
+    // reply ends the session like a failed fan-out write
+    stream.set_write_timeout(Some(SESSION_WRITE_TIMEOUT))?;
+    let writer: SessionWriter = Arc::new(Mutex::new(stream.try_clone()?));
+    lock(sessions).push(BrokerSession {
+        id,
+        writer: writer.clone(),
+        filters: Vec::new(),
+    });
+
+    loop {
+        let (header, body) = read_packet(&mut stream)?;
+        match header & 0xF0 {
+            PUBLISH => {
+                if let Some((topic, payload)) = parse_publish(header, &body) {
+                    let out = publish_packet(&topic, &payload);
+                    // QoS 0 fan-out after releasing the lock (a slow subscriber must not block the
+                    // others); sessions whose socket fails or times out are closed and dropped
+                    let targets: Vec<(u64, SessionWriter)> = lock(sessions)
+                        .iter()
+                        .filter(|s| s.filters.iter().any(|f| topic_matches(f, &topic)))
+                        .map(|s| (s.id, s.writer.clone()))
+                        .collect();
+                    let failed: Vec<u64> = targets
+                        .into_iter()
+                        .filter(|(_, writer)| write_frame(writer, &out).is_err())
+                        .map(|(id, writer)| {
+                            shutdown(&writer);
+                            id
+                        })
+                        .collect();
+                    if !failed.is_empty() {
+                        lock(sessions).retain(|s| !failed.contains(&s.id));
+                    }
+                }
+            }
+            0x80 => {
+                // SUBSCRIBE: packet id, then (filter, qos) pairs
+                let packet_id = [*body.first().unwrap_or(&0), *body.get(1).unwrap_or(&0)];
+                let mut pos = 2;
+                let mut filters = Vec::new();
+                while let Some(f) = read_str(&body, &mut pos) {
+                    filters.push(f);
+                    pos += 1;
+                }
+
+                let mut ack = vec![packet_id[0], packet_id[1]];
+                ack.extend(std::iter::repeat_n(0u8, filters.len()));   // Granted QoS 0
+                if let Some(s) = lock(sessions).iter_mut().find(|s| s.id == id) {
+                    s.filters.extend(filters);
+                }
+                write_frame(&writer, &packet(SUBACK, &ack))?;
+            }
+            PINGREQ => write_frame(&writer, &[PINGRESP, 0])?,
+            DISCONNECT => return Ok(()),
+            _ => {}     // Unsupported packets ignored
+        }
+    }
+}
+
+// ============================================================================
+// Bridge
+// ============================================================================
+
+/// `[mqtt]`: whether the bridge runs, and against which broker.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,              // Start the bridge (telemetry out, commands in)
+    pub embedded_broker: bool,      // Host the minimal broker on `address`; false = external broker there
+    pub address: String,            // host:port of the broker
+    pub client_id: String,
+    pub prefix: String,             // Topic prefix
+    pub metrics_interval_ms: u64,   // Period of the `<prefix>/metrics` snapshot
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        let bridge = MqttBridgeConfig::default();
+        Self {
+            enabled: false,
+            embedded_broker: true,
+            address: bridge.broker,
+            client_id: bridge.client_id,
+            prefix: bridge.prefix,
+            metrics_interval_ms: bridge.metrics_interval.as_millis() as u64,
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Bridge settings for a broker at `broker` (the embedded broker's bound address, or `address`)
+    /// accepting tuning/autotune commands for the controllers named in `targets`.
+    pub fn bridge(&self, broker: String, targets: Vec<&'static str>) -> MqttBridgeConfig {
+        MqttBridgeConfig {
+            broker,
+            client_id: self.client_id.clone(),
+            prefix: self.prefix.clone(),
+            metrics_interval: Duration::from_millis(self.metrics_interval_ms),
+            targets,
+        }
+    }
+}
+
+/// Bridge settings.
+#[derive(Debug, Clone)]
+pub struct MqttBridgeConfig {
+    pub broker: String,                 // host:port, loopback by default
+    pub client_id: String,
+    pub prefix: String,
+    pub metrics_interval: Duration,
+    pub targets: Vec<&'static str>,     // Controllers `cmd/tuning/<Controller>` may name
+}
+
+impl Default for MqttBridgeConfig {
+    fn default() -> Self {
+        Self {
+            broker: "127.0.0.1:1883".into(),
+            client_id: "rts_simulation".into(),
+            prefix: "rts".into(),
+            metrics_interval: Duration::from_secs(1),
+            targets: vec![RECEIVER],
+        }
+    }
+}
+
+/// Connects to the broker and starts the bridge thread.
+/// The thread exits (sending DISCONNECT) once the bus is closed.
+pub fn start_mqtt_bridge(config: MqttBridgeConfig, bus: &Bus, metrics: SharedMetrics) -> io::Result<JoinHandle<()>> {
+    let (mut client, inbound) = MqttClient::connect(&config.broker, &config.client_id)?;
+    client.subscribe(&[
+        format!("{}/cmd/setpoint/+", config.prefix),
+        format!("{}/cmd/tuning", config.prefix),
+        format!("{}/cmd/tuning/+", config.prefix),
+        format!("{}/cmd/autotune", config.prefix),
+        format!("{}/cmd/autotune/+", config.prefix),
+    ])?;
+    info!("[MQTT] bridge connected to {} (prefix '{}')", config.broker, config.prefix);
+
+    // Telemetry is best effort: evict the oldest when the broker link falls behind
+    let processed = bus.processed.subscribe("mqtt_bridge", 1024, OverflowPolicy::DropOldest);
+    let feedback = bus.feedback.subscribe("mqtt_bridge", 1024, OverflowPolicy::DropOldest);
+    let commands = bus.commands.clone();
+
+    thread::Builder::new()
+        .name("mqtt-bridge".into())
+        .spawn(move || {
+            let prefix = config.prefix.as_str();
+            let targets = config.targets.as_slice();
+            let mut last_snapshot = Instant::now();
+            let mut link_ok = true;
+
+            loop {
+                // Processed packets pace the loop (highest-rate topic)
+                let mut closed = false;
+                match processed.recv_timeout(Duration::from_millis(5)) {
+                    Ok(pkt) => link_ok &= publish_processed(&mut client, prefix, &pkt),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => closed = true,
+                }
+                while let Ok(fb) = feedback.try_recv() {
+                    link_ok &= publish_feedback(&mut client, prefix, &fb);
+                }
+
+                // Inbound setpoint / tuning commands → bus
+                while let Ok((topic, payload)) = inbound.try_recv() {
+                    match parse_command(prefix, targets, &topic, &payload) {
+                        Some(cmd) => {
+                            info!("[MQTT] command {:?}", cmd);
+                            commands.publish(cmd);
+                        }
+                        None => error!("[MQTT] ignored malformed command on '{}'", topic),
+                    }
+                }
+
+                if last_snapshot.elapsed() >= config.metrics_interval {
+                    last_snapshot = Instant::now();
+                    let payload = metrics_snapshot_json(&metrics);
+                    link_ok &= client.publish(&format!("{}/metrics", prefix), payload.as_bytes()).is_ok();
+                }
+                link_ok &= client.keep_alive().is_ok();
+
+                if !link_ok {
+                    error!("[MQTT] broker link lost; bridge stopping");
+                    break;
+                }
+                if closed {
+                    break;
+                }
+            }
+
+            client.disconnect();
+            info!("[MQTT] bridge stopped");
+        })
+}
+
+fn publish_processed(client: &mut MqttClient, prefix: &str, pkt: &ProcessedPacket) -> bool {
+    let payload = format!(
+        r#"{{"seq":{},"filtered":{:.4},"raw":{:.4},"age_us":{}}}"#,
+        pkt.seq,
+        pkt.filtered,
+        pkt.raw,
+        pkt.sensor_timestamp.elapsed().as_micros()
+    );
+    let topic = format!("{}/processed/{}", prefix, pkt.sensor_type.name());
+    client.publish(&topic, payload.as_bytes()).is_ok()
+}
+
+fn publish_feedback(client: &mut MqttClient, prefix: &str, fb: &Feedback) -> bool {
+    let payload = match fb.kind {
+        FeedbackKind::Ack => r#"{"kind":"Ack","value":null}"#.to_string(),
+        FeedbackKind::ActuatorState(v) => format!(r#"{{"kind":"ActuatorState","value":{:.4}}}"#, v),
+        FeedbackKind::Error(e) => format!(r#"{{"kind":"Error","value":"{}"}}"#, e),
+        FeedbackKind::Saturated(v) => format!(r#"{{"kind":"Saturated","value":{:.4}}}"#, v),
+        FeedbackKind::RateLimited(v) => format!(r#"{{"kind":"RateLimited","value":{:.4}}}"#, v),
+        FeedbackKind::CommandedState(v) => format!(r#"{{"kind":"CommandedState","value":{:.4}}}"#, v),
+        FeedbackKind::ActuatorFault(f) => format!(r#"{{"kind":"ActuatorFault","value":"{}"}}"#, f.name()),
+    };
+    let topic = format!("{}/feedback/{}", prefix, fb.actuator);
+    client.publish(&topic, payload.as_bytes()).is_ok()
+}
+
+fn metrics_snapshot_json(metrics: &SharedMetrics) -> String {
+    let m = metrics.snapshot();
+    format!(
+        r#"{{"cpu_load_threads":{},"cycles":{},"miss_sensor":{},"miss_processor":{},"miss_actuator":{},"deadline_miss":{},"last_latency_us":{},"last_jitter_us":{},"stale_total":{}}}"#,
+        m.cpu_load_threads,
+        m.total_cycles,
+        m.miss_sensor,
+        m.miss_processor,
+        m.miss_actuator,
+        m.deadline_miss,
+        m.latency_us.back().cloned().unwrap_or(0),
+        m.jitter_us.back().cloned().unwrap_or(0),
+        m.stale_total()
+    )
+}
+
+/// `<prefix>/cmd/setpoint/<Sensor>` → number or `resume`; `<prefix>/cmd/tuning[/<Controller>]` → `kp=..,ki=..,kd=..`;
+/// `<prefix>/cmd/autotune[/<Controller>]` → empty or a tuning rule name; `<Controller>` is one of `targets`.
+fn parse_command(prefix: &str, targets: &[&'static str], topic: &str, payload: &[u8]) -> Option<ControlCommand> {
+    let text = std::str::from_utf8(payload).ok()?.trim();
+    let rest = topic.strip_prefix(prefix)?.strip_prefix("/cmd/")?;
+
+    if let Some(sensor) = rest.strip_prefix("setpoint/") {
+        let sensor = match sensor {
+            "Force" => SensorType::Force,
+            "Position" => SensorType::Position,
+            "Temperature" => SensorType::Temperature,
+            _ => return None,
+        };
+        let value = match text {
+            "resume" => None,
+            text => Some(text.parse::<f64>().ok().filter(|v| v.is_finite())?),
+        };
+        return Some(ControlCommand::Setpoint { sensor, value });
+    }
+
+    if let Some(target) = rest.strip_prefix("autotune") {
+        let target = parse_target(targets, target)?;
+        let rule = match text {
+            "" => None,
+            name => Some(TuningRule::ALL.into_iter().find(|rule| rule.name() == name)?),
+        };
+        return Some(ControlCommand::Autotune { target, rule });
+    }
+
+    if let Some(target) = rest.strip_prefix("tuning") {
+        let target = parse_target(targets, target)?;
+        let (mut kp, mut ki, mut kd) = (None, None, None);
+        for pair in text.split(',') {
+            let (key, value) = pair.split_once('=')?;
+            let value = value.trim().parse::<f64>().ok().filter(|v| v.is_finite())?;
+            match key.trim() {
+                "kp" => kp = Some(value),
+                "ki" => ki = Some(value),
+                "kd" => kd = Some(value),
+                _ => return None,
+            }
+        }
+        return Some(ControlCommand::Tuning { target, kp, ki, kd });
+    }
+
+    None
+}
+
+/// Topic suffix after the command name: "" → every controller, "/<Controller>" → that one.
+/// None when the suffix is neither (unknown controller or trailing garbage).
+fn parse_target(targets: &[&'static str], suffix: &str) -> Option<Option<&'static str>> {
+    match suffix.strip_prefix('/') {
+        Some(name) => Some(Some(*targets.iter().find(|c| **c == name)?)),
+        None if suffix.is_empty() => Some(None),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_filters_match_single_and_multi_level_wildcards() {
+        assert!(topic_matches("rts/cmd/tuning", "rts/cmd/tuning"));
+        assert!(topic_matches("rts/cmd/setpoint/+", "rts/cmd/setpoint/Force"));
+        assert!(!topic_matches("rts/cmd/setpoint/+", "rts/cmd/setpoint"));
+        assert!(!topic_matches("rts/cmd/setpoint/+", "rts/cmd/setpoint/Force/x"));
+        assert!(topic_matches("rts/+/Gripper", "rts/feedback/Gripper"));
+        assert!(topic_matches("rts/#", "rts/processed/Force"));
+        assert!(topic_matches("#", "rts"));
+        assert!(!topic_matches("rts/cmd", "rts/cmd/tuning"));
+        assert!(!topic_matches("rts/cmd/tuning", "rts/cmd"));
+    }
+
+    #[test]
+    fn read_packet_decodes_multi_byte_remaining_length() {
+        let payload = vec![0xAB; 300];
+        let frame = publish_packet("rts/metrics", &payload);
+        // 2 + 11 topic bytes + 300 payload = 313 = 0xB9 0x02
+        assert_eq!(&frame[..3], &[PUBLISH, 0xB9, 0x02]);
+
+        let (header, body) = read_packet(&mut frame.as_slice()).unwrap();
+        assert_eq!(header, PUBLISH);
+        assert_eq!(body.len(), 313);
+        assert_eq!(parse_publish(header, &body), Some(("rts/metrics".to_string(), payload)));
+    }
+
+    #[test]
+    fn read_packet_rejects_malformed_and_oversized_lengths() {
+        let malformed = [PUBLISH, 0x80, 0x80, 0x80, 0x80, 0x01];
+        assert!(read_packet(&mut malformed.as_slice()).is_err());
+        // 0xFF 0xFF 0x7F encodes 2 MiB - 1, above MAX_PACKET_LEN
+        let oversized = [PUBLISH, 0xFF, 0xFF, 0x7F];
+        assert!(read_packet(&mut oversized.as_slice()).is_err());
+    }
+
+    #[test]
+    fn parse_publish_skips_the_qos1_packet_id() {
+        let mut body = Vec::new();
+        push_str(&mut body, "rts/cmd/tuning");
+        body.extend_from_slice(&[0x00, 0x07]);      // Packet identifier
+        body.extend_from_slice(b"kp=1.5");
+        let qos1 = PUBLISH | 0x02;
+        assert_eq!(parse_publish(qos1, &body), Some(("rts/cmd/tuning".to_string(), b"kp=1.5".to_vec())));
+        assert_eq!(parse_publish(PUBLISH, &[0x00, 0x09, b'r']), None);    // Truncated topic
+    }
+
+    #[test]
+    fn embedded_broker_delivers_publishes_to_matching_subscribers() {
+        let broker = MqttBroker::start("127.0.0.1:0").unwrap();
+        let addr = broker.addr().to_string();
+        let (mut subscriber, inbound) = MqttClient::connect(&addr, "sub").unwrap();
+        subscriber.subscribe(&["rts/feedback/+".to_string()]).unwrap();
+        let (mut publisher, _) = MqttClient::connect(&addr, "pub").unwrap();
+
+        // The SUBSCRIBE is handled on another session thread: publish until it is in place
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let received = loop {
+            publisher.publish("rts/processed/Force", b"skip").unwrap();
+            publisher.publish("rts/feedback/Gripper", b"{\"kind\":\"Ack\"}").unwrap();
+            if let Ok(msg) = inbound.recv_timeout(Duration::from_millis(50)) {
+                break msg;
+            }
+            assert!(Instant::now() < deadline, "no delivery from the embedded broker");
+        };
+        assert_eq!(received, ("rts/feedback/Gripper".to_string(), b"{\"kind\":\"Ack\"}".to_vec()));
+        assert!(inbound.try_iter().all(|(topic, _)| topic == "rts/feedback/Gripper"));
+
+        publisher.disconnect();
+        subscriber.disconnect();
+        broker.stop();
+    }
+}
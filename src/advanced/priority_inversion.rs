@@ -0,0 +1,233 @@
+//! priority_inversion.rs
+//! Built-in priority-inversion scenario: plain pthread mutex vs. `PTHREAD_PRIO_INHERIT`.
+//!
+//! Three threads pinned to one core under SCHED_FIFO:
+//! - **L (low, prio 10):** takes the lock, wakes H then M, busy-holds the lock for `hold`.
+//! - **M (medium, prio 20):** CPU hog; busy-spins for `hog` without touching the lock.
+//! - **H (high, prio 30):** blocks on the lock; its wait is the measured blocking time.
+//!
+//! Without inheritance M preempts L while H waits, so H blocks for roughly `hold + hog`
+//! (unbounded inversion). With inheritance L runs at H's priority, so H blocks for `hold` only.
+//!
+//! Without CAP_SYS_NICE / rtprio the threads stay SCHED_OTHER: the scenario still runs,
+//! but the report is flagged `realtime=false` and the two variants are not expected to differ.
+
+use std::{
+    fs::{create_dir_all, File},
+    io::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam::channel::bounded;
+use log::{info, warn};
+
+use crate::component_a::pi_mutex::PthreadMutex;
+
+const LOW_PRIO: i32 = 10;
+const MEDIUM_PRIO: i32 = 20;
+const HIGH_PRIO: i32 = 30;
+
+pub const INVERSION_CSV: &str = "data/priority_inversion.csv";
+
+/// Scenario parameters.
+#[derive(Debug, Clone)]
+pub struct InversionConfig {
+    pub iterations: usize,
+    pub hold: Duration,     // Critical section length of the low-priority holder
+    pub hog: Duration,      // CPU burst of the medium-priority thread
+    pub core: usize,        // Core all three threads are pinned to
+}
+
+impl Default for InversionConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 20,
+            hold: Duration::from_millis(2),
+            hog: Duration::from_millis(10),
+            core: 0,
+        }
+    }
+}
+
+/// Blocking time of the high-priority waiter for one mutex variant.
+#[derive(Debug, Clone)]
+pub struct InversionReport {
+    pub variant: &'static str,  // "plain" | "priority_inheritance"
+    pub realtime: bool,         // SCHED_FIFO was granted to every thread
+    pub iterations: usize,
+    pub mean_blocked_us: f64,
+    pub max_blocked_us: u64,
+}
+
+/// Runs the scenario with a plain mutex and with a priority-inheritance mutex,
+/// prints both results and writes them to `data/priority_inversion.csv`.
+pub fn run_priority_inversion(config: &InversionConfig) -> Vec<InversionReport> {
+    info!(
+        "[PI] Scenario: {} iterations, hold={:?}, hog={:?}, core={}",
+        config.iterations, config.hold, config.hog, config.core
+    );
+
+    let reports = vec![
+        run_variant(config, false),
+        run_variant(config, true),
+    ];
+
+    if reports.iter().any(|r| !r.realtime) {
+        warn!("[PI] SCHED_FIFO unavailable (needs CAP_SYS_NICE or rtprio); results are not real-time");
+    }
+
+    println!("\n Priority inversion (high-priority waiter blocking time):");
+    for r in &reports {
+        println!(
+            "   {:<22} mean={:>9.1} µs  max={:>7} µs  realtime={}",
+            r.variant, r.mean_blocked_us, r.max_blocked_us, r.realtime
+        );
+    }
+
+    if let Err(e) = write_reports(config, &reports) {
+        warn!("[PI] Failed to write {}: {}", INVERSION_CSV, e);
+    } else {
+        info!("[PI] Report written to {}", INVERSION_CSV);
+    }
+
+    reports
+}
+
+fn run_variant(config: &InversionConfig, inherit: bool) -> InversionReport {
+    let realtime = Arc::new(AtomicBool::new(true));
+    let mut blocked_us = Vec::with_capacity(config.iterations);
+
+    for _ in 0..config.iterations {
+        blocked_us.push(run_iteration(config, inherit, &realtime));
+    }
+
+    let total: u64 = blocked_us.iter().sum();
+    InversionReport {
+        variant: if inherit { "priority_inheritance" } else { "plain" },
+        realtime: realtime.load(Ordering::Relaxed),
+        iterations: blocked_us.len(),
+        mean_blocked_us: if blocked_us.is_empty() {
+            0.0
+        } else {
+            total as f64 / blocked_us.len() as f64
+        },
+        max_blocked_us: blocked_us.iter().copied().max().unwrap_or(0),
+    }
+}
+
+/// One L/M/H round; returns H's blocking time in µs.
+fn run_iteration(config: &InversionConfig, inherit: bool, realtime: &Arc<AtomicBool>) -> u64 {
+    let mutex = Arc::new(if inherit {
+        PthreadMutex::with_priority_inheritance(0u64)
+    } else {
+        PthreadMutex::plain(0u64)
+    });
+
+    let (ready_tx, ready_rx) = bounded::<()>(2);
+    let (go_high_tx, go_high_rx) = bounded::<()>(1);
+    let (go_medium_tx, go_medium_rx) = bounded::<()>(1);
+
+    // H and M park on their channels before L takes the lock
+    let high = {
+        let mutex = mutex.clone();
+        let ready = ready_tx.clone();
+        let rt = realtime.clone();
+        let core = config.core;
+        thread::spawn(move || {
+            enter_rt(core, HIGH_PRIO, &rt);
+            let _ = ready.send(());
+            let _ = go_high_rx.recv();
+
+            let start = Instant::now();
+            let mut guard = mutex.lock();
+            let blocked_us = start.elapsed().as_micros() as u64;
+            *guard += 1;
+            blocked_us
+        })
+    };
+
+    let medium = {
+        let rt = realtime.clone();
+        let core = config.core;
+        let hog = config.hog;
+        thread::spawn(move || {
+            enter_rt(core, MEDIUM_PRIO, &rt);
+            let _ = ready_tx.send(());
+            let _ = go_medium_rx.recv();
+            spin_for(hog);
+        })
+    };
+
+    for _ in 0..2 {
+        let _ = ready_rx.recv();
+    }
+
+    let low = {
+        let mutex = mutex.clone();
+        let rt = realtime.clone();
+        let core = config.core;
+        let hold = config.hold;
+        thread::spawn(move || {
+            enter_rt(core, LOW_PRIO, &rt);
+            let mut guard = mutex.lock();
+            let _ = go_high_tx.send(());     // H preempts, then blocks on the lock
+            let _ = go_medium_tx.send(());   // M preempts L unless L inherited H's priority
+            spin_for(hold);
+            *guard += 1;
+        })
+    };
+
+    let _ = low.join();
+    let _ = medium.join();
+    high.join().unwrap_or(0)
+}
+
+/// Pins the current thread to `core` and switches it to SCHED_FIFO at `priority`.
+/// Clears `realtime` if either step is refused.
+fn enter_rt(core: usize, priority: i32, realtime: &AtomicBool) {
+    let pinned = core_affinity::get_core_ids()
+        .unwrap_or_default()
+        .get(core)
+        .is_some_and(|id| core_affinity::set_for_current(*id));
+
+    let param = libc::sched_param { sched_priority: priority };
+    // SAFETY: pthread_self() is always a valid handle for the calling thread.
+    let rc = unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+
+    if !pinned || rc != 0 {
+        realtime.store(false, Ordering::Relaxed);
+    }
+}
+
+#[inline]
+fn spin_for(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        std::hint::spin_loop();
+    }
+}
+
+fn write_reports(config: &InversionConfig, reports: &[InversionReport]) -> std::io::Result<()> {
+    create_dir_all("data")?;
+    let mut file = File::create(INVERSION_CSV)?;
+    writeln!(file, "variant,realtime,iterations,hold_us,hog_us,mean_blocked_us,max_blocked_us")?;
+    for r in reports {
+        writeln!(
+            file,
+            "{},{},{},{},{},{:.1},{}",
+            r.variant,
+            r.realtime,
+            r.iterations,
+            config.hold.as_micros(),
+            config.hog.as_micros(),
+            r.mean_blocked_us,
+            r.max_blocked_us
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Control-quality analysis across CPU load levels: replays every `data/logs/events_load_X.csv`
+//! through the step-response analyzer → per-actuator table next to the deadline-miss rate
+//! → `data/control_quality_by_load.csv` (one row per load level and actuator).
+//!
+//! Ties timing degradation (misses, from `cpu_load_results.csv` when present) to loss of
+//! control quality: rise time, overshoot, settling time, steady-state error, IAE / ISE / ITAE.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, write},
+};
+
+use rts_simulation::utils::control_quality::{ControlQuality, analyze_events_csv};
+
+const LOG_DIR: &str = "data/logs";
+const OUTPUT_CSV: &str = "data/control_quality_by_load.csv";
+
+fn main() {
+    println!(" RTS Control Quality Analysis");
+    println!("================================\n");
+
+    let levels = find_event_logs();
+    if levels.is_empty() {
+        eprintln!(" No events_load_X.csv in {}. Run experiments first!", LOG_DIR);
+        return;
+    }
+    let miss_rates = read_miss_rates(&format!("{}/cpu_load_results.csv", LOG_DIR));
+
+    // load level → actuator → totals
+    let mut results: BTreeMap<usize, BTreeMap<String, ControlQuality>> = BTreeMap::new();
+    for (level, path) in &levels {
+        match analyze_events_csv(path) {
+            Ok(analysed) => {
+                let per_actuator = analysed.into_iter().map(|(name, r)| (name, r.totals)).collect();
+                results.insert(*level, per_actuator);
+            }
+            Err(e) => eprintln!(" Failed to analyse {}: {}", path, e),
+        }
+    }
+
+    let actuators: Vec<String> = results
+        .values()
+        .flat_map(|per_actuator| per_actuator.keys().cloned())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if actuators.is_empty() {
+        eprintln!(" No ControlSample rows found (logs from before control-quality tracking?)");
+        return;
+    }
+
+    for actuator in &actuators {
+        print_table(actuator, &results, &miss_rates);
+    }
+
+    export_csv(&results, &miss_rates);
+}
+
+/// `(load level, path)` of every events log, sorted by level.
+fn find_event_logs() -> Vec<(usize, String)> {
+    let Ok(entries) = fs::read_dir(LOG_DIR) else { return Vec::new() };
+    let mut levels: Vec<(usize, String)> = entries
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            let level = name.strip_prefix("events_load_")?.strip_suffix(".csv")?.parse().ok()?;
+            Some((level, format!("{}/{}", LOG_DIR, name)))
+        })
+        .collect();
+    levels.sort();
+    levels
+}
+
+/// Deadline-miss rate (%) per load level from the sweep summary (last row per level wins).
+fn read_miss_rates(path: &str) -> BTreeMap<usize, f64> {
+    let Ok(text) = fs::read_to_string(path) else { return BTreeMap::new() };
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split(',').collect();
+            Some((parts.first()?.parse().ok()?, parts.get(3)?.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Prints one actuator's figures per load level.
+fn print_table(
+    actuator: &str,
+    results: &BTreeMap<usize, BTreeMap<String, ControlQuality>>,
+    miss_rates: &BTreeMap<usize, f64>,
+) {
+    println!("{}", actuator.to_uppercase());
+    println!(
+        "{:<6} {:<10} {:<7} {:<11} {:<13} {:<13} {:<11} {:<12} {:<12}",
+        "Load", "Miss (%)", "Steps", "Rise (s)", "Overshoot %", "Settling (s)", "|SS err|", "IAE", "ITAE"
+    );
+    println!("{}", "=".repeat(104));
+
+    for (level, per_actuator) in results {
+        let Some(q) = per_actuator.get(actuator) else { continue };
+        println!(
+            "{:<6} {:<10} {:<7} {:<11} {:<13} {:<13} {:<11} {:<12.4} {:<12.4}",
+            level,
+            fmt(miss_rates.get(level).copied(), 2),
+            q.steps,
+            fmt(q.mean_rise_time_s(), 4),
+            fmt(q.mean_overshoot_pct(), 2),
+            fmt(q.mean_settling_time_s(), 4),
+            fmt(q.mean_abs_ss_error(), 4),
+            q.iae,
+            q.itae
+        );
+    }
+    println!();
+}
+
+fn fmt(value: Option<f64>, decimals: usize) -> String {
+    value.map(|v| format!("{:.*}", decimals, v)).unwrap_or_else(|| "-".into())
+}
+
+fn export_csv(results: &BTreeMap<usize, BTreeMap<String, ControlQuality>>, miss_rates: &BTreeMap<usize, f64>) {
+    let field = |value: Option<f64>| value.map(|v| format!("{:.6}", v)).unwrap_or_default();
+    let mut csv = String::from(
+        "load_level,deadline_miss_rate,actuator,segments,steps,settled,mean_rise_time_s,mean_overshoot_pct,max_overshoot_pct,mean_settling_time_s,mean_abs_ss_error,iae,ise,itae\n",
+    );
+    for (level, per_actuator) in results {
+        for (actuator, q) in per_actuator {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.3},{},{},{:.6},{:.6},{:.6}\n",
+                level,
+                field(miss_rates.get(level).copied()),
+                actuator,
+                q.segments,
+                q.steps,
+                q.settled,
+                field(q.mean_rise_time_s()),
+                field(q.mean_overshoot_pct()),
+                q.overshoot_max_pct,
+                field(q.mean_settling_time_s()),
+                field(q.mean_abs_ss_error()),
+                q.iae,
+                q.ise,
+                q.itae
+            ));
+        }
+    }
+
+    match write(OUTPUT_CSV, csv) {
+        Ok(_) => println!("Control quality per load level written to {}", OUTPUT_CSV),
+        Err(e) => eprintln!(" Failed to write {}: {}", OUTPUT_CSV, e),
+    }
+}
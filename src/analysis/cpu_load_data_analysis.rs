@@ -71,26 +71,24 @@ fn read_csv(path: &str) -> Vec<ExperimentResult> {
     // Skip header row
     let _ = lines.next();
     
-    for line in lines {
-        if let Ok(line) = line {
-            let parts: Vec<&str> = line.split(',').collect();
-            if parts.len() >= 6 {
-                // CSV columns: cpu_load_threads, deadline_miss, total_cycles, miss_rate (%), jitter (us), latency (us)
-                if let (Ok(cpu), Ok(miss), Ok(cycles), Ok(jitter), Ok(latency)) = (
-                    parts[0].parse::<usize>(),
-                    parts[1].parse::<u64>(),
-                    parts[2].parse::<u64>(),
-                    parts[4].parse::<u64>(),  // Index 4: max_jitter_us
-                    parts[5].parse::<u64>(),  // Index 5: avg_latency_us
-                ) {
-                    results.push(ExperimentResult {
-                        cpu_load_threads: cpu,
-                        deadline_miss: miss,
-                        total_cycles: cycles,
-                        max_jitter_us: jitter,
-                        avg_latency_us: latency,
-                    });
-                }
+    for line in lines.map_while(Result::ok) {
+        let parts: Vec<&str> = line.split(',').collect();
+        if parts.len() >= 6 {
+            // CSV columns: cpu_load_threads, deadline_miss, total_cycles, miss_rate (%), jitter (us), latency (us)
+            if let (Ok(cpu), Ok(miss), Ok(cycles), Ok(jitter), Ok(latency)) = (
+                parts[0].parse::<usize>(),
+                parts[1].parse::<u64>(),
+                parts[2].parse::<u64>(),
+                parts[4].parse::<u64>(),  // Index 4: max_jitter_us
+                parts[5].parse::<u64>(),  // Index 5: avg_latency_us
+            ) {
+                results.push(ExperimentResult {
+                    cpu_load_threads: cpu,
+                    deadline_miss: miss,
+                    total_cycles: cycles,
+                    max_jitter_us: jitter,
+                    avg_latency_us: latency,
+                });
             }
         }
     }
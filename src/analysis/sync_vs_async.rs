@@ -1,10 +1,11 @@
 
 //! Performance analysis: threaded vs async comparison with combined dashboard.
 //!
-//! Reads CSV logs (sync_events_load_0.csv, async_events.csv) → extracts latency/jitter metrics
+//! Reads CSV logs (sync_events_load_0.csv, async_events.csv; either CSV schema) → extracts latency/jitter metrics
 //! → generates comparison report (percentiles, throughput, drops) → outputs 2x2 dashboard HTML.
+//! A binary log (sync_events_load_0.bin, `[sync] log_format = "binary"`) is preferred when present:
+//! records carry their kind, so no event-string parsing is needed.
 
-use polars::prelude::*;
 use plotly::{
     common::Mode,
     layout::{Axis, Layout},
@@ -12,6 +13,8 @@ use plotly::{
 };
 use std::{error::Error, fs, path::Path};
 
+use rts_simulation::component_a::synclog::{read_csv, RecordKind, SyncLogReader};
+
 /// Aggregated metrics summary for one execution mode (threaded or async).
 #[derive(Debug, Clone)]
 struct Summary {
@@ -39,7 +42,11 @@ fn main() -> Result<(), Box<dyn Error>> {
     fs::create_dir_all("data/results")?;
 
     // Analyze both implementations from CSV logs
-    let (sync, sync_ts) = analyze_csv("Threaded", "data/logs/sync_events_load_0.csv")?;
+    let (sync, sync_ts) = if Path::new("data/logs/sync_events_load_0.bin").exists() {
+        analyze_binary("Threaded", "data/logs/sync_events_load_0.bin")?
+    } else {
+        analyze_csv("Threaded", "data/logs/sync_events_load_0.csv")?
+    };
     let (async_, async_ts) = analyze_csv("Async", "data/logs/async_events.csv")?;
 
     // Print individual summaries
@@ -82,42 +89,69 @@ fn analyze_csv(label: &str, path: &str) -> Result<(Summary, TimeSeries), Box<dyn
         ));
     }
 
-    // Read CSV with polars; select relevant columns
-    let df = LazyCsvReader::new(path)
-        .with_has_header(true)
-        .finish()?
-        .select([
-            col("ts_epoch_us").cast(DataType::Float64),
-            col("event"),
-            col("value").cast(DataType::Float64),
-        ])
-        .collect()?;
-
-    let timestamps = df.column("ts_epoch_us")?.f64()?;
-    let events = df.column("event")?.str()?;
-    let values = df.column("value")?.f64()?;
+    // Typed rows; schema 1 files (string-encoded events) are converted by the reader
+    let log = read_csv(path)?;
+    println!("  {} log: CSV schema {}, {} rows", label, log.schema, log.rows.len());
+    if log.skipped > 0 {
+        println!("  Warning: {} malformed rows skipped in {}", log.skipped, path);
+    }
+
+    let mut jitter_vals = Vec::new();
+    let mut latency_vals = Vec::new();
+    let mut time_points = Vec::new();
+    let mut tx_drops = 0u32;
+
+    // Categorize by event kind
+    for row in &log.rows {
+        match row.event_kind {
+            RecordKind::Jitter => jitter_vals.push(row.value_us.unwrap_or(0) as f64),
+            RecordKind::RxLatency => {
+                latency_vals.push(row.value_us.unwrap_or(0) as f64);
+                time_points.push(row.record_epoch_us as f64);
+            }
+            RecordKind::TxDrop => tx_drops += 1,
+            _ => {}
+        }
+    }
+
+    Ok(summarize(label, jitter_vals, latency_vals, time_points, tx_drops))
+}
+
+/// Same metrics as `analyze_csv`, decoded from a binary SyncManager log.
+/// Timestamps are record times (the CSV `record_epoch_us` column) so both inputs compare alike.
+fn analyze_binary(label: &str, path: &str) -> Result<(Summary, TimeSeries), Box<dyn Error>> {
+    let reader = SyncLogReader::open(path)?;
+    let header = reader.header.clone();
 
     let mut jitter_vals = Vec::new();
     let mut latency_vals = Vec::new();
     let mut time_points = Vec::new();
     let mut tx_drops = 0u32;
 
-    // Iterate rows; categorize by event type
-    for i in 0..df.height() {
-        let ts = timestamps.get(i).unwrap_or(0.0);
-        let event = events.get(i).unwrap_or("");
-        let value = values.get(i).unwrap_or(0.0);
-
-        if event.starts_with("jitter:") {
-            jitter_vals.push(value);
-        } else if event.starts_with("rx_latency:") {
-            latency_vals.push(value);
-            time_points.push(ts);
-        } else if event == "tx_drop" || event == "tx_drop\n" {
-            tx_drops += 1;
+    for rec in reader {
+        let rec = rec?;
+        match rec.kind {
+            RecordKind::Jitter => jitter_vals.push(rec.value()),
+            RecordKind::RxLatency => {
+                latency_vals.push(rec.value());
+                time_points.push(rec.record_epoch_us(&header) as f64);
+            }
+            RecordKind::TxDrop => tx_drops += 1,
+            _ => {}
         }
     }
 
+    Ok(summarize(label, jitter_vals, latency_vals, time_points, tx_drops))
+}
+
+/// Aggregates extracted samples into a Summary + TimeSeries.
+fn summarize(
+    label: &str,
+    jitter_vals: Vec<f64>,
+    latency_vals: Vec<f64>,
+    time_points: Vec<f64>,
+    tx_drops: u32,
+) -> (Summary, TimeSeries) {
     // Compute duration (min-max timestamps) for throughput calculation
     let duration_sec = if time_points.is_empty() {
         1.0
@@ -146,7 +180,7 @@ fn analyze_csv(label: &str, path: &str) -> Result<(Summary, TimeSeries), Box<dyn
         jitters: jitter_vals.clone(),
     };
 
-    Ok((
+    (
         Summary {
             label: label.to_string(),
             jitter_mean: mean(&jitter_vals),
@@ -160,7 +194,7 @@ fn analyze_csv(label: &str, path: &str) -> Result<(Summary, TimeSeries), Box<dyn
             latency_samples_sec,
         },
         ts,
-    ))
+    )
 }
 
 /// Compares threaded vs async: prints mean, percentiles, throughput with % difference.
@@ -170,7 +204,7 @@ fn compare_implementations(threaded: &Summary, async_: &Summary) {
     println!("Latency (µs):");
     println!("  Threaded Mean:  {:.2}", threaded.latency_mean);
     println!("  Async Mean:     {:.2}", async_.latency_mean);
-    if threaded.latency_mean.abs() < std::f64::EPSILON {
+    if threaded.latency_mean.abs() < f64::EPSILON {
         println!("  Difference:     N/A (threaded mean is zero)");
     } else {
         let latency_diff = ((async_.latency_mean - threaded.latency_mean) / threaded.latency_mean) * 100.0;
@@ -180,7 +214,7 @@ fn compare_implementations(threaded: &Summary, async_: &Summary) {
     println!("\nJitter (µs):");
     println!("  Threaded P95:   {:.2}", threaded.jitter_p95);
     println!("  Async P95:      {:.2}", async_.jitter_p95);
-    if threaded.jitter_p95.abs() < std::f64::EPSILON {
+    if threaded.jitter_p95.abs() < f64::EPSILON {
         println!("  Difference:     N/A (threaded jitter P95 is zero)");
     } else {
         let jitter_diff = ((async_.jitter_p95 - threaded.jitter_p95) / threaded.jitter_p95) * 100.0;
@@ -190,7 +224,7 @@ fn compare_implementations(threaded: &Summary, async_: &Summary) {
     println!("\nP99 Latency (µs):");
     println!("  Threaded:       {:.2}", threaded.latency_p99);
     println!("  Async:          {:.2}", async_.latency_p99);
-    if threaded.latency_p99.abs() < std::f64::EPSILON {
+    if threaded.latency_p99.abs() < f64::EPSILON {
         println!("  Difference:     N/A (threaded P99 is zero)");
     } else {
         let p99_diff = ((async_.latency_p99 - threaded.latency_p99) / threaded.latency_p99) * 100.0;
@@ -200,7 +234,7 @@ fn compare_implementations(threaded: &Summary, async_: &Summary) {
     println!("\nThroughput (events/sec):");
     println!("  Threaded:       {:.2}", threaded.throughput_events_sec);
     println!("  Async:          {:.2}", async_.throughput_events_sec);
-    if threaded.throughput_events_sec.abs() < std::f64::EPSILON {
+    if threaded.throughput_events_sec.abs() < f64::EPSILON {
         println!("  Difference:     N/A (threaded throughput is zero)");
     } else {
         let throughput_diff = ((async_.throughput_events_sec - threaded.throughput_events_sec) / threaded.throughput_events_sec) * 100.0;
@@ -270,7 +304,7 @@ fn generate_combined_dashboard(
     if !ts1.timestamps.is_empty() {
         plot.add_trace(
             Scatter::new(ts1.timestamps.clone(), ts1.latencies.clone())
-                .name(&format!("{} Latency", s1.label))
+                .name(format!("{} Latency", s1.label))
                 .mode(Mode::Markers)
                 .x_axis("x3")
                 .y_axis("y3"),
@@ -280,7 +314,7 @@ fn generate_combined_dashboard(
     if !ts2.timestamps.is_empty() {
         plot.add_trace(
             Scatter::new(ts2.timestamps.clone(), ts2.latencies.clone())
-                .name(&format!("{} Latency", s2.label))
+                .name(format!("{} Latency", s2.label))
                 .mode(Mode::Markers)
                 .x_axis("x3")
                 .y_axis("y3"),
@@ -294,7 +328,7 @@ fn generate_combined_dashboard(
                 (0..ts1.jitters.len()).map(|i| i as f64).collect(),
                 ts1.jitters.clone(),
             )
-            .name(&format!("{} Jitter", s1.label))
+            .name(format!("{} Jitter", s1.label))
             .mode(Mode::Markers)
             .x_axis("x4")
             .y_axis("y4"),
@@ -307,7 +341,7 @@ fn generate_combined_dashboard(
                 (0..ts2.jitters.len()).map(|i| i as f64).collect(),
                 ts2.jitters.clone(),
             )
-            .name(&format!("{} Jitter", s2.label))
+            .name(format!("{} Jitter", s2.label))
             .mode(Mode::Markers)
             .x_axis("x4")
             .y_axis("y4"),
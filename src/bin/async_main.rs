@@ -7,7 +7,6 @@
 use std::{
     sync::{
         Arc,
-        Mutex,
         atomic::{AtomicBool, Ordering},
     },
     thread,
@@ -21,7 +20,8 @@ use tokio::{
 
 use rts_simulation::advanced::async_pipeline::run_async_pipeline;
 use rts_simulation::component_a::sync_manager::{SyncManager, SyncMode};
-use rts_simulation::utils::metrics::{Metrics, EventRecorder};
+use rts_simulation::utils::metrics::{SharedMetrics, EventRecorder};
+use rts_simulation::utils::logwriter::install_crash_flush;
 
 const SIMULATION_DURATION_SECS: u64 = 30;
 
@@ -48,16 +48,17 @@ const SIMULATION_DURATION_SECS: u64 = 30;
 #[tokio::main(flavor = "multi_thread", worker_threads = 4)]
 async fn main() {
     env_logger::init();
+    install_crash_flush();
     println!("=== ASYNC PIPELINE START ===");
 
     // Shared state: metrics, sync manager, event recorder
     let running = Arc::new(AtomicBool::new(true));
-    let metrics = Arc::new(Mutex::new(Metrics::default()));
+    let metrics = SharedMetrics::default();
     let sync = Arc::new(SyncManager::new(SyncMode::LockFree));
 
     // Event recording system: non-blocking queue → background CSV export
     let event_recorder = Arc::new(EventRecorder::new());
-    let _exporter_handle = event_recorder.start_exporter(
+    let exporter_handle = event_recorder.start_exporter(
         "data/logs/events_async_load_0.csv".to_string(),
         0,  // CPU load: 0 (baseline, no contention)
     );
@@ -120,5 +121,11 @@ async fn main() {
         eprintln!("Warning: failed to stop consumer: {}", e);
     }
 
+    // Exporter drains every producer buffer before exiting
+    event_recorder.shutdown();
+    let _ = exporter_handle.join();
+    let dropped: u64 = event_recorder.producer_stats().iter().map(|p| p.dropped).sum();
+    println!("Events exported: {} ({} dropped)", event_recorder.exported_count(), dropped);
+
     println!("=== ASYNC PIPELINE FINISHED ===");
 }
\ No newline at end of file
@@ -0,0 +1,261 @@
+//! Decoder for binary SyncManager logs (`[sync] log_format = "binary"`).
+//!
+//! Usage:
+//!   synclog csv     <file.bin> [--kind k1,k2] [--out FILE]   CSV in the live-log schema
+//!   synclog json    <file.bin> [--kind k1,k2] [--out FILE]   {"header":{...},"records":[...]}
+//!   synclog summary <file.bin> [--kind k1,k2]                 Counts, jitter/latency stats, queue residency
+//!
+//! Kinds: sample, jitter, proc_miss, tx_drop, rx_latency, custom.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    process::ExitCode,
+};
+
+use rts_simulation::component_a::synclog::{
+    csv_schema_line, RecordKind, SyncCsvRow, SyncLogHeader, SyncLogReader, SyncRecord,
+};
+
+const USAGE: &str = "usage: synclog <csv|json|summary> <file.bin> [--kind k1,k2] [--out FILE]";
+
+/// Parsed command line.
+struct Args {
+    command: String,
+    input: String,
+    kinds: Option<HashSet<RecordKind>>,     // None = every kind
+    out: Option<String>,                    // None = stdout
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1).collect()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("synclog: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(argv: Vec<String>) -> Result<Args, String> {
+    let mut it = argv.into_iter();
+    let command = it.next().ok_or("missing command")?;
+    if !matches!(command.as_str(), "csv" | "json" | "summary") {
+        return Err(format!("unknown command '{}'", command));
+    }
+    let input = it.next().ok_or("missing input file")?;
+
+    let mut kinds = None;
+    let mut out = None;
+    while let Some(flag) = it.next() {
+        match flag.as_str() {
+            "--kind" => {
+                let list = it.next().ok_or("--kind needs a value")?;
+                let mut set = HashSet::new();
+                for name in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    set.insert(RecordKind::parse(name).ok_or_else(|| format!("unknown kind '{}'", name))?);
+                }
+                kinds = Some(set);
+            }
+            "--out" => out = Some(it.next().ok_or("--out needs a value")?),
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    Ok(Args { command, input, kinds, out })
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let reader = SyncLogReader::open(&args.input)?;
+    let header = reader.header.clone();
+    let records = reader.filter(|r| match (r, &args.kinds) {
+        (Ok(rec), Some(kinds)) => kinds.contains(&rec.kind),
+        _ => true,
+    });
+
+    let mut out: Box<dyn Write> = match &args.out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    match args.command.as_str() {
+        "csv" => write_csv(&mut out, &header, records)?,
+        "json" => write_json(&mut out, &header, records)?,
+        _ => write_summary(&mut out, &header, records)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// ============================================================================
+// CSV / JSON
+// ============================================================================
+
+/// Same schema as the live CSV log (schema line + typed columns), so analysis reads either.
+fn write_csv(
+    out: &mut dyn Write,
+    header: &SyncLogHeader,
+    records: impl Iterator<Item = io::Result<SyncRecord>>,
+) -> Result<(), Box<dyn Error>> {
+    writeln!(out, "{}", csv_schema_line(&header.sensor_map))?;
+    let mut wtr = csv::Writer::from_writer(out);
+    for rec in records {
+        let rec = rec?;
+        wtr.serialize(SyncCsvRow::from_record(&rec, header.anchor_epoch_us))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn write_json(
+    out: &mut dyn Write,
+    header: &SyncLogHeader,
+    records: impl Iterator<Item = io::Result<SyncRecord>>,
+) -> Result<(), Box<dyn Error>> {
+    let sensors: Vec<String> = header
+        .sensor_map
+        .iter()
+        .map(|(id, name)| format!("\"{}\":\"{}\"", id, escape(name)))
+        .collect();
+    write!(
+        out,
+        "{{\"header\":{{\"version\":{},\"label\":\"{}\",\"anchor_epoch_us\":{},\"sensors\":{{{}}}}},\"records\":[",
+        header.version,
+        escape(&header.label),
+        header.anchor_epoch_us,
+        sensors.join(",")
+    )?;
+
+    for (i, rec) in records.enumerate() {
+        let rec = rec?;
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(
+            out,
+            "\n{{\"seq\":{},\"t_ns\":{},\"epoch_us\":{},\"kind\":\"{}\",\"id\":{},\"value\":{},\"residency_us\":{}}}",
+            rec.seq,
+            rec.t_ns,
+            rec.record_epoch_us(header),
+            rec.kind.name(),
+            rec.id,
+            rec.arg,
+            rec.residency_us
+        )?;
+    }
+    writeln!(out, "\n]}}")?;
+    Ok(())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ============================================================================
+// SUMMARY
+// ============================================================================
+
+fn write_summary(
+    out: &mut dyn Write,
+    header: &SyncLogHeader,
+    records: impl Iterator<Item = io::Result<SyncRecord>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut counts: BTreeMap<RecordKind, u64> = BTreeMap::new();
+    let mut samples: BTreeMap<u16, u64> = BTreeMap::new();
+    let mut jitter: BTreeMap<u16, Vec<f64>> = BTreeMap::new();
+    let mut customs: BTreeMap<u16, u64> = BTreeMap::new();
+    let mut latency = Vec::new();
+    let mut residency = Vec::new();
+    let (mut first_ns, mut last_ns) = (i64::MAX, i64::MIN);
+    let (mut seq_min, mut seq_max) = (u64::MAX, 0u64);
+
+    for rec in records {
+        let rec = rec?;
+        *counts.entry(rec.kind).or_insert(0) += 1;
+        match rec.kind {
+            RecordKind::Sample => *samples.entry(rec.id).or_insert(0) += 1,
+            RecordKind::Jitter => jitter.entry(rec.id).or_default().push(rec.arg as f64),
+            RecordKind::RxLatency => latency.push(rec.arg as f64),
+            RecordKind::Custom => *customs.entry(rec.id).or_insert(0) += 1,
+            RecordKind::ProcMiss | RecordKind::TxDrop => {}
+        }
+        residency.push(rec.residency_us as f64);
+        first_ns = first_ns.min(rec.t_ns);
+        last_ns = last_ns.max(rec.t_ns);
+        seq_min = seq_min.min(rec.seq);
+        seq_max = seq_max.max(rec.seq);
+    }
+
+    let total: u64 = counts.values().sum();
+    let name = |id: &u16| header.sensor_map.get(id).cloned().unwrap_or_else(|| format!("sensor:{}", id));
+
+    writeln!(out, "=== SYNC LOG SUMMARY ===")?;
+    writeln!(out, "Run:            {} (format v{})", header.label, header.version)?;
+    writeln!(out, "Anchor:         {} µs since epoch", header.anchor_epoch_us)?;
+    writeln!(out, "Records:        {}", total)?;
+    if total == 0 {
+        return Ok(());
+    }
+    let span_s = (last_ns - first_ns) as f64 / 1e9;
+    writeln!(out, "Span:           {:.3} s", span_s)?;
+    // Sequence numbers are assigned before the queue push; holes are events dropped on a full queue
+    writeln!(out, "Seq range:      {}..={} ({} missing)", seq_min, seq_max, (seq_max - seq_min + 1).saturating_sub(total))?;
+
+    writeln!(out, "\nEvents by kind:")?;
+    for (kind, n) in &counts {
+        writeln!(out, "  {:<12} {:>10}", kind.name(), n)?;
+    }
+
+    if !samples.is_empty() {
+        writeln!(out, "\nSamples per sensor:")?;
+        for (id, n) in &samples {
+            let rate = if span_s > 0.0 { *n as f64 / span_s } else { 0.0 };
+            writeln!(out, "  {:<12} {:>10}  ({:.1}/s)", name(id), n, rate)?;
+        }
+    }
+
+    if !jitter.is_empty() {
+        writeln!(out, "\nJitter per sensor (µs):")?;
+        for (id, v) in &mut jitter {
+            writeln!(out, "  {:<12} {}", name(id), stats(v))?;
+        }
+    }
+
+    if !latency.is_empty() {
+        writeln!(out, "\nRx latency (µs):  {}", stats(&mut latency))?;
+    }
+
+    if !customs.is_empty() {
+        writeln!(out, "\nCustom codes:")?;
+        for (code, n) in &customs {
+            writeln!(out, "  {:<12} {:>10}", code, n)?;
+        }
+    }
+
+    writeln!(out, "\nResidency (µs):   {}", stats(&mut residency))?;
+    Ok(())
+}
+
+/// "mean / p95 / p99 / max (n)" for a sample set.
+fn stats(v: &mut [f64]) -> String {
+    v.sort_by(|a, b| a.total_cmp(b));
+    let pct = |p: f64| v[((v.len() - 1) as f64 * p).round() as usize];
+    format!(
+        "mean {:.1}  p95 {:.1}  p99 {:.1}  max {:.1}  (n={})",
+        v.iter().sum::<f64>() / v.len() as f64,
+        pct(0.95),
+        pct(0.99),
+        v[v.len() - 1],
+        v.len()
+    )
+}
@@ -0,0 +1,239 @@
+//! contention.rs
+//! Optional lock instrumentation for the diagnostics sinks (`[sync] instrument = true`).
+//!
+//! - Wait: time spent acquiring a lock (mutex, pthread mutex, RwLock, DashMap shard).
+//! - Hold: time spent inside the critical section, measured from acquisition to release.
+//! - CAS retries (Atomics mode): failed `compare_exchange_weak` attempts per increment.
+//! - Push fails (LockFree mode): events rejected by the full log queue.
+//! - Every figure is attributed to the calling thread (thread name, else thread id).
+//!
+//! A disabled `Probe` costs one branch per record; counters are per-thread atomics,
+//! so enabling it adds no shared cache line between producers. RT threads register
+//! (`Probe::register`, via `SyncManager::attach_thread`) before their RT section.
+
+use std::{
+    cell::RefCell,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::Instant,
+};
+
+use parking_lot::Mutex;
+
+static NEXT_PROBE_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // (probe id, this thread's counters for that probe)
+    static LOCAL_COUNTERS: RefCell<Vec<(u64, Arc<ThreadCounters>)>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Default)]
+struct ThreadCounters {
+    thread: String,
+    ops: AtomicU64,
+    wait_total_ns: AtomicU64,
+    wait_max_ns: AtomicU64,
+    hold_total_ns: AtomicU64,
+    hold_max_ns: AtomicU64,
+    cas_retries: AtomicU64,
+    push_fails: AtomicU64,
+}
+
+/// Per-thread contention figures (snapshot).
+#[derive(Debug, Clone, Default)]
+pub struct ThreadContention {
+    pub thread: String,
+    pub ops: u64,               // Instrumented record_* calls
+    pub wait_total_ns: u64,     // Lock acquisition
+    pub wait_max_ns: u64,
+    pub hold_total_ns: u64,     // Critical section
+    pub hold_max_ns: u64,
+    pub cas_retries: u64,       // Atomics mode
+    pub push_fails: u64,        // LockFree mode (queue full)
+}
+
+impl ThreadContention {
+    pub fn wait_avg_ns(&self) -> f64 {
+        if self.ops == 0 { 0.0 } else { self.wait_total_ns as f64 / self.ops as f64 }
+    }
+
+    pub fn hold_avg_ns(&self) -> f64 {
+        if self.ops == 0 { 0.0 } else { self.hold_total_ns as f64 / self.ops as f64 }
+    }
+}
+
+/// Contention snapshot for one run: one entry per producer thread.
+#[derive(Debug, Clone, Default)]
+pub struct ContentionStats {
+    pub threads: Vec<ThreadContention>,
+}
+
+impl ContentionStats {
+    /// All threads folded into one entry (thread = "total").
+    pub fn total(&self) -> ThreadContention {
+        self.threads.iter().fold(
+            ThreadContention { thread: "total".into(), ..ThreadContention::default() },
+            |mut t, c| {
+                t.ops += c.ops;
+                t.wait_total_ns += c.wait_total_ns;
+                t.wait_max_ns = t.wait_max_ns.max(c.wait_max_ns);
+                t.hold_total_ns += c.hold_total_ns;
+                t.hold_max_ns = t.hold_max_ns.max(c.hold_max_ns);
+                t.cas_retries += c.cas_retries;
+                t.push_fails += c.push_fails;
+                t
+            },
+        )
+    }
+}
+
+/// Registry of per-thread counters for one sink.
+struct LockProbe {
+    id: u64,
+    threads: Mutex<Vec<Arc<ThreadCounters>>>,
+}
+
+impl LockProbe {
+    /// This thread's counters; registered on first use (or by `Probe::register`), then served
+    /// from a thread-local cache.
+    fn local(&self) -> Arc<ThreadCounters> {
+        LOCAL_COUNTERS.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some((_, c)) = cache.iter().find(|(id, _)| *id == self.id) {
+                return c.clone();
+            }
+
+            let current = thread::current();
+            let counters = Arc::new(ThreadCounters {
+                thread: current
+                    .name()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("{:?}", current.id())),
+                ..ThreadCounters::default()
+            });
+            self.threads.lock().push(counters.clone());
+            cache.push((self.id, counters.clone()));
+            counters
+        })
+    }
+}
+
+/// Instrumentation handle held by every sink; `Probe::default()` is disabled.
+#[derive(Clone, Default)]
+pub struct Probe {
+    inner: Option<Arc<LockProbe>>,
+}
+
+impl Probe {
+    pub fn enabled() -> Self {
+        Self {
+            inner: Some(Arc::new(LockProbe {
+                id: NEXT_PROBE_ID.fetch_add(1, Ordering::Relaxed),
+                threads: Mutex::new(Vec::new()),
+            })),
+        }
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Registers the calling thread's counters now (name, registry entry, thread-local cache),
+    /// so its first instrumented record does not allocate; call before `rt_alloc::enter`.
+    pub fn register(&self) {
+        if let Some(probe) = &self.inner {
+            probe.local();
+        }
+    }
+
+    /// Runs `body` on the guard returned by `acquire`, timing acquisition (wait) and
+    /// the critical section (hold). The guard is released when `body` returns.
+    #[inline]
+    pub fn locked<G, R>(&self, acquire: impl FnOnce() -> G, body: impl FnOnce(G) -> R) -> R {
+        let Some(probe) = &self.inner else {
+            return body(acquire());
+        };
+
+        let start = Instant::now();
+        let guard = acquire();
+        let acquired = Instant::now();
+        let result = body(guard);
+        let released = Instant::now();
+
+        let wait_ns = acquired.duration_since(start).as_nanos() as u64;
+        let hold_ns = released.duration_since(acquired).as_nanos() as u64;
+        let c = probe.local();
+        c.ops.fetch_add(1, Ordering::Relaxed);
+        c.wait_total_ns.fetch_add(wait_ns, Ordering::Relaxed);
+        c.wait_max_ns.fetch_max(wait_ns, Ordering::Relaxed);
+        c.hold_total_ns.fetch_add(hold_ns, Ordering::Relaxed);
+        c.hold_max_ns.fetch_max(hold_ns, Ordering::Relaxed);
+        result
+    }
+
+    /// Counts a lock-free operation (no wait/hold to measure).
+    #[inline]
+    pub fn op(&self) {
+        if let Some(probe) = &self.inner {
+            probe.local().ops.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds `delta` to `counter`. Disabled: a plain `fetch_add`. Enabled: a CAS loop
+    /// so failed attempts (concurrent writers on the same counter) can be counted.
+    #[inline]
+    pub fn add(&self, counter: &AtomicU64, delta: u64) {
+        let Some(probe) = &self.inner else {
+            counter.fetch_add(delta, Ordering::Relaxed);
+            return;
+        };
+
+        let mut retries = 0u64;
+        let mut current = counter.load(Ordering::Relaxed);
+        while let Err(actual) = counter.compare_exchange_weak(
+            current,
+            current.wrapping_add(delta),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            current = actual;
+            retries += 1;
+        }
+        if retries > 0 {
+            probe.local().cas_retries.fetch_add(retries, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts an event rejected by a full queue.
+    #[inline]
+    pub fn push_failed(&self) {
+        if let Some(probe) = &self.inner {
+            probe.local().push_fails.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Per-thread figures; `None` when instrumentation is disabled.
+    pub fn snapshot(&self) -> Option<ContentionStats> {
+        let probe = self.inner.as_ref()?;
+        let threads = probe
+            .threads
+            .lock()
+            .iter()
+            .map(|c| ThreadContention {
+                thread: c.thread.clone(),
+                ops: c.ops.load(Ordering::Relaxed),
+                wait_total_ns: c.wait_total_ns.load(Ordering::Relaxed),
+                wait_max_ns: c.wait_max_ns.load(Ordering::Relaxed),
+                hold_total_ns: c.hold_total_ns.load(Ordering::Relaxed),
+                hold_max_ns: c.hold_max_ns.load(Ordering::Relaxed),
+                cas_retries: c.cas_retries.load(Ordering::Relaxed),
+                push_fails: c.push_fails.load(Ordering::Relaxed),
+            })
+            .collect();
+        Some(ContentionStats { threads })
+    }
+}
@@ -0,0 +1,873 @@
+//! diagnostics.rs
+//! Diagnostic sinks: one implementation per synchronisation strategy behind `DiagnosticsSink`.
+//!
+//! - Every sink exposes the same producer API and a uniform `snapshot()` → `Diagnostics`.
+//! - `SyncManager` owns one sink chosen by `SyncMode`; adding a strategy means adding a sink,
+//!   not editing every `record_*` method.
+//! - Sinks: Mutex, PriorityInheritance (pthread PI mutex), Atomics (DashMap),
+//!   LockFree (queue + consumer), Sharded (per-thread shards), RwLock (read-locked map of atomics).
+//! - Each sink carries a `Probe` (contention.rs): disabled by default, or timing its lock
+//!   wait/hold, CAS retries and push failures per thread when instrumentation is on.
+//! - The LockFree consumer writes CSV rows (rotated by logwriter.rs) or binary records (synclog.rs).
+//! - RT producers never allocate: per-sensor entries are created up front (`prepare`) and each
+//!   producer thread takes the sink's locks and sets up its parking data before its RT section
+//!   (`attach_thread`).
+
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use parking_lot::{Mutex, RwLock};
+use crossbeam_queue::ArrayQueue;
+use dashmap::DashMap;
+use log::{error, debug};
+
+use crate::component_a::{
+    contention::Probe,
+    pi_mutex::PthreadMutex,
+    synclog::{self, ClockAnchor, SyncCsvRow, SyncLogFormat, SyncLogHeader, SyncLogWriter, SyncRecord},
+};
+use crate::utils::logwriter::{LogPolicy, LogWriter};
+
+const LOG_CAPACITY: usize = 8192;        // Bounded queue size (prevents unbounded memory growth)
+const CONSUMER_POLL_MS: u64 = 5;         // Consumer sleep interval (reduces busy-loop CPU)
+const FLUSH_BATCHES: usize = 8;          // Batch writes before flushing to disk (reduces syscall jitter)
+const SHARDS: usize = 16;                // Sharded sink: shard count (threads map round-robin)
+const SHARD_SENSOR_SLOTS: usize = 8;     // Sharded sink: sensor ids below this use fixed slots
+const HIST_BUCKETS: usize = 32;          // Log2 µs buckets: 0, 1, 2-3, 4-7, ... (last one open-ended)
+
+static PARK_WARMUP_KEY: u8 = 0;          // Park address no lock ever waits on
+
+#[derive(Debug, Clone, Copy)]
+pub enum LogEventKind {
+    Sample { sensor_id: u16 },
+    Jitter { sensor_id: u16, jitter_us: u64 },
+    ProcMiss,
+    TxDrop,
+    RxLatency { latency_us: u64 },
+    Custom { code: u16 },
+}
+
+#[derive(Debug, Clone)]
+pub struct RawLog {
+    pub seq: u64,
+    pub ts: Instant,           // Record time (producer side)
+    pub kind: LogEventKind,    // Carries its own arguments (jitter / latency µs, code)
+}
+
+/// Log2-bucketed µs histogram: fixed memory, percentiles within a factor of two.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    pub buckets: [u64; HIST_BUCKETS],   // buckets[i]: values in [2^(i-1), 2^i) µs; buckets[0]: 0 µs
+    pub count: u64,
+    pub sum_us: u64,
+    pub max_us: u64,
+}
+
+impl LatencyHistogram {
+    #[inline]
+    pub fn record(&mut self, us: u64) {
+        let bucket = ((u64::BITS - us.leading_zeros()) as usize).min(HIST_BUCKETS - 1);
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum_us += us;
+        self.max_us = self.max_us.max(us);
+    }
+
+    /// Largest value that falls in `bucket` (inclusive).
+    pub fn bucket_upper_us(bucket: usize) -> u64 {
+        if bucket == 0 { 0 } else { (1u64 << bucket) - 1 }
+    }
+
+    pub fn mean_us(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum_us as f64 / self.count as f64 }
+    }
+
+    /// Upper bound of the bucket holding the `p` quantile (0..=1), capped at the exact max.
+    pub fn percentile_us(&self, p: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((p.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Self::bucket_upper_us(i).min(self.max_us);
+            }
+        }
+        self.max_us
+    }
+}
+
+/// Uniform diagnostics snapshot.
+/// `rx_latency_*`, `custom_count`, `dropped_logs` and the histograms are only populated by the
+/// LockFree sink (the counter sinks do not track per-event data); LockFree counts what its
+/// consumer has drained, so a snapshot taken mid-run is live to within one consumer poll.
+#[derive(Debug, Default, Clone)]
+pub struct Diagnostics {
+    pub sample_count: HashMap<u16, u64>,
+    pub jitter_sum: HashMap<u16, u64>,
+    pub proc_miss_count: u64,
+    pub tx_drop_count: u64,
+    pub rx_latency_count: u64,
+    pub rx_latency_sum_us: u64,
+    pub custom_count: HashMap<u16, u64>,
+    pub dropped_logs: u64,
+    pub jitter_hist: HashMap<u16, LatencyHistogram>,  // Per sensor
+    pub rx_latency_hist: LatencyHistogram,
+}
+
+impl Diagnostics {
+    /// Zeroed counters for `sensor_ids`, so `apply` never inserts into the maps.
+    fn prepare(&mut self, sensor_ids: &[u16]) {
+        for &id in sensor_ids {
+            self.sample_count.entry(id).or_insert(0);
+            self.jitter_sum.entry(id).or_insert(0);
+        }
+    }
+
+    fn apply(&mut self, kind: LogEventKind) {
+        match kind {
+            LogEventKind::Sample { sensor_id } => *self.sample_count.entry(sensor_id).or_insert(0) += 1,
+            LogEventKind::Jitter { sensor_id, jitter_us } => {
+                *self.jitter_sum.entry(sensor_id).or_insert(0) += jitter_us
+            }
+            LogEventKind::ProcMiss => self.proc_miss_count += 1,
+            LogEventKind::TxDrop => self.tx_drop_count += 1,
+            LogEventKind::RxLatency { latency_us } => {
+                self.rx_latency_count += 1;
+                self.rx_latency_sum_us += latency_us;
+            }
+            LogEventKind::Custom { code } => *self.custom_count.entry(code).or_insert(0) += 1,
+        }
+    }
+
+    /// Counters plus distributions; used by the LockFree consumer (off the producer path).
+    fn apply_with_histograms(&mut self, kind: LogEventKind) {
+        self.apply(kind);
+        match kind {
+            LogEventKind::Jitter { sensor_id, jitter_us } => {
+                self.jitter_hist.entry(sensor_id).or_default().record(jitter_us)
+            }
+            LogEventKind::RxLatency { latency_us } => self.rx_latency_hist.record(latency_us),
+            _ => {}
+        }
+    }
+}
+
+/// Producer API shared by all synchronisation strategies.
+pub trait DiagnosticsSink: Send + Sync {
+    fn record_sample(&self, sensor_id: u16);
+    fn record_jitter(&self, sensor_id: u16, jitter_us: u64);
+    fn record_proc_miss(&self);
+    fn record_tx_drop(&self);
+
+    /// Per-event latency; ignored by counter-only sinks.
+    fn record_rx_latency(&self, _latency_us: u64) {}
+
+    /// Custom event codes; ignored by counter-only sinks.
+    fn record_custom(&self, _code: u16) {}
+
+    /// Creates the per-sensor entries for `sensor_ids` before the producers start, so a
+    /// sensor's first record does not insert (allocate) on an RT thread.
+    fn prepare(&self, _sensor_ids: &[u16]) {}
+
+    /// Called on each producer thread before its RT section: takes the sink's locks once and
+    /// creates the thread's parking data (otherwise allocated on its first contended lock).
+    fn attach_thread(&self) {}
+
+    fn snapshot(&self) -> Diagnostics;
+}
+
+/// Creates parking_lot's per-thread data (and grows its global table for this thread) now,
+/// instead of the first time the thread blocks on a contended parking_lot / DashMap lock.
+fn init_park_data() {
+    // SAFETY: `validate` fails, so the thread never parks; the callbacks do not panic and do not
+    // call into parking_lot, and no lock uses `PARK_WARMUP_KEY` as its address
+    unsafe {
+        parking_lot_core::park(
+            &PARK_WARMUP_KEY as *const u8 as usize,
+            || false,
+            || {},
+            |_, _| {},
+            parking_lot_core::DEFAULT_PARK_TOKEN,
+            None,
+        );
+    }
+}
+
+// ============================================================================
+// MUTEX SINK (High contention, simple mutual exclusion)
+// ============================================================================
+// Single mutex protecting all diagnostics. Sensor/Processor lock on every record.
+// Risk: Priority inversion if high-priority thread blocks on low-priority holder.
+#[derive(Default)]
+pub struct MutexSink {
+    diag: Mutex<Diagnostics>,
+    probe: Probe,
+}
+
+impl MutexSink {
+    pub fn with_probe(mut self, probe: Probe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    #[inline]
+    fn apply(&self, kind: LogEventKind) {
+        self.probe.locked(|| self.diag.lock(), |mut d| d.apply(kind));
+    }
+}
+
+impl DiagnosticsSink for MutexSink {
+    fn record_sample(&self, sensor_id: u16) {
+        self.apply(LogEventKind::Sample { sensor_id });
+    }
+
+    fn record_jitter(&self, sensor_id: u16, jitter_us: u64) {
+        self.apply(LogEventKind::Jitter { sensor_id, jitter_us });
+    }
+
+    fn record_proc_miss(&self) {
+        self.apply(LogEventKind::ProcMiss);
+    }
+
+    fn record_tx_drop(&self) {
+        self.apply(LogEventKind::TxDrop);
+    }
+
+    fn prepare(&self, sensor_ids: &[u16]) {
+        self.diag.lock().prepare(sensor_ids);
+    }
+
+    fn attach_thread(&self) {
+        init_park_data();
+        drop(self.diag.lock());
+    }
+
+    fn snapshot(&self) -> Diagnostics {
+        self.diag.lock().clone()
+    }
+}
+
+// ============================================================================
+// PRIORITY-INHERITANCE SINK (pthread mutex, PTHREAD_PRIO_INHERIT)
+// ============================================================================
+// Same single-lock design as MutexSink, but a low-priority holder is boosted to
+// the priority of the highest waiter, bounding priority inversion under SCHED_FIFO.
+#[derive(Default)]
+pub struct PriorityInheritanceSink {
+    diag: PthreadMutex<Diagnostics>,
+    probe: Probe,
+}
+
+impl PriorityInheritanceSink {
+    pub fn with_probe(mut self, probe: Probe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    #[inline]
+    fn apply(&self, kind: LogEventKind) {
+        self.probe.locked(|| self.diag.lock(), |mut d| d.apply(kind));
+    }
+}
+
+impl DiagnosticsSink for PriorityInheritanceSink {
+    fn record_sample(&self, sensor_id: u16) {
+        self.apply(LogEventKind::Sample { sensor_id });
+    }
+
+    fn record_jitter(&self, sensor_id: u16, jitter_us: u64) {
+        self.apply(LogEventKind::Jitter { sensor_id, jitter_us });
+    }
+
+    fn record_proc_miss(&self) {
+        self.apply(LogEventKind::ProcMiss);
+    }
+
+    fn record_tx_drop(&self) {
+        self.apply(LogEventKind::TxDrop);
+    }
+
+    fn prepare(&self, sensor_ids: &[u16]) {
+        self.diag.lock().prepare(sensor_ids);
+    }
+
+    fn attach_thread(&self) {
+        drop(self.diag.lock());
+    }
+
+    fn snapshot(&self) -> Diagnostics {
+        self.diag.lock().clone()
+    }
+}
+
+// ============================================================================
+// ATOMICS SINK (Contention-free per-counter)
+// ============================================================================
+// Per-sensor counters (DashMap): CAS-based increment, no mutex locks.
+// Each sensor_id gets its own AtomicU64 for sample counts and jitter sums.
+// Instrumented: wait = DashMap shard lock, hold = increment, plus CAS retries.
+#[derive(Default)]
+pub struct AtomicSink {
+    samples: DashMap<u16, AtomicU64>,
+    jitter: DashMap<u16, AtomicU64>,
+    proc_miss: AtomicU64,
+    tx_drops: AtomicU64,
+    probe: Probe,
+}
+
+impl AtomicSink {
+    pub fn with_probe(mut self, probe: Probe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    #[inline]
+    fn add_per_sensor(&self, map: &DashMap<u16, AtomicU64>, sensor_id: u16, delta: u64) {
+        // `get` first: `entry` reserves room for an insert (may grow the shard) even for a known id
+        let known = self.probe.locked(
+            || map.get(&sensor_id),
+            |c| c.map(|c| self.probe.add(&c, delta)).is_some(),
+        );
+        if known {
+            return;
+        }
+        self.probe.locked(
+            || map.entry(sensor_id).or_insert_with(|| AtomicU64::new(0)),
+            |c| self.probe.add(&c, delta),
+        );
+    }
+}
+
+impl DiagnosticsSink for AtomicSink {
+    fn record_sample(&self, sensor_id: u16) {
+        self.add_per_sensor(&self.samples, sensor_id, 1);
+    }
+
+    fn record_jitter(&self, sensor_id: u16, jitter_us: u64) {
+        self.add_per_sensor(&self.jitter, sensor_id, jitter_us);
+    }
+
+    fn record_proc_miss(&self) {
+        self.probe.op();
+        self.probe.add(&self.proc_miss, 1);
+    }
+
+    fn record_tx_drop(&self) {
+        self.probe.op();
+        self.probe.add(&self.tx_drops, 1);
+    }
+
+    fn prepare(&self, sensor_ids: &[u16]) {
+        for &id in sensor_ids {
+            self.samples.entry(id).or_insert_with(|| AtomicU64::new(0));
+            self.jitter.entry(id).or_insert_with(|| AtomicU64::new(0));
+        }
+    }
+
+    fn attach_thread(&self) {
+        init_park_data();
+        self.samples.iter().for_each(drop);     // Read-locks every shard once
+        self.jitter.iter().for_each(drop);
+    }
+
+    fn snapshot(&self) -> Diagnostics {
+        Diagnostics {
+            sample_count: self.samples.iter().map(|r| (*r.key(), r.value().load(Ordering::Relaxed))).collect(),
+            jitter_sum: self.jitter.iter().map(|r| (*r.key(), r.value().load(Ordering::Relaxed))).collect(),
+            proc_miss_count: self.proc_miss.load(Ordering::Relaxed),
+            tx_drop_count: self.tx_drops.load(Ordering::Relaxed),
+            ..Diagnostics::default()
+        }
+    }
+}
+
+// ============================================================================
+// SHARDED SINK (Per-thread counter shards)
+// ============================================================================
+// Each thread is assigned a cache-line aligned shard on first use, so concurrent
+// producers increment different cache lines; snapshot sums all shards.
+#[repr(align(64))]
+#[derive(Default)]
+struct Shard {
+    samples: [AtomicU64; SHARD_SENSOR_SLOTS],
+    jitter: [AtomicU64; SHARD_SENSOR_SLOTS],
+    proc_miss: AtomicU64,
+    tx_drops: AtomicU64,
+}
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SHARD_INDEX: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn shard_index() -> usize {
+    SHARD_INDEX.with(|idx| match idx.get() {
+        Some(i) => i,
+        None => {
+            let i = NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % SHARDS;
+            idx.set(Some(i));
+            i
+        }
+    })
+}
+
+pub struct ShardedSink {
+    shards: Box<[Shard]>,
+    overflow: Mutex<Diagnostics>,       // Sensor ids beyond the fixed slots (rare)
+    probe: Probe,
+}
+
+impl Default for ShardedSink {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Shard::default()).collect(),
+            overflow: Mutex::new(Diagnostics::default()),
+            probe: Probe::default(),
+        }
+    }
+}
+
+impl ShardedSink {
+    pub fn with_probe(mut self, probe: Probe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    #[inline]
+    fn shard(&self) -> &Shard {
+        &self.shards[shard_index()]
+    }
+
+    /// Slow path for sensor ids without a fixed slot; the only lock in this sink.
+    fn apply_overflow(&self, kind: LogEventKind) {
+        self.probe.locked(|| self.overflow.lock(), |mut d| d.apply(kind));
+    }
+
+    #[inline]
+    fn add(&self, counter: &AtomicU64, delta: u64) {
+        self.probe.op();
+        self.probe.add(counter, delta);
+    }
+}
+
+impl DiagnosticsSink for ShardedSink {
+    fn record_sample(&self, sensor_id: u16) {
+        match self.shard().samples.get(sensor_id as usize) {
+            Some(c) => self.add(c, 1),
+            None => self.apply_overflow(LogEventKind::Sample { sensor_id }),
+        }
+    }
+
+    fn record_jitter(&self, sensor_id: u16, jitter_us: u64) {
+        match self.shard().jitter.get(sensor_id as usize) {
+            Some(c) => self.add(c, jitter_us),
+            None => self.apply_overflow(LogEventKind::Jitter { sensor_id, jitter_us }),
+        }
+    }
+
+    fn record_proc_miss(&self) {
+        self.add(&self.shard().proc_miss, 1);
+    }
+
+    fn record_tx_drop(&self) {
+        self.add(&self.shard().tx_drops, 1);
+    }
+
+    fn attach_thread(&self) {
+        init_park_data();
+        shard_index();
+        drop(self.overflow.lock());
+    }
+
+    fn snapshot(&self) -> Diagnostics {
+        let mut d = self.overflow.lock().clone();
+        for shard in self.shards.iter() {
+            for id in 0..SHARD_SENSOR_SLOTS {
+                let samples = shard.samples[id].load(Ordering::Relaxed);
+                let jitter = shard.jitter[id].load(Ordering::Relaxed);
+                if samples > 0 {
+                    *d.sample_count.entry(id as u16).or_insert(0) += samples;
+                }
+                if jitter > 0 {
+                    *d.jitter_sum.entry(id as u16).or_insert(0) += jitter;
+                }
+            }
+            d.proc_miss_count += shard.proc_miss.load(Ordering::Relaxed);
+            d.tx_drop_count += shard.tx_drops.load(Ordering::Relaxed);
+        }
+        d
+    }
+}
+
+// ============================================================================
+// RWLOCK SINK (Read-locked map of atomics)
+// ============================================================================
+// Producers take a shared read lock and increment atomics; the write lock is only
+// taken the first time an unprepared sensor id is seen. Readers never block each other.
+#[derive(Default)]
+pub struct RwLockSink {
+    per_sensor: RwLock<HashMap<u16, (AtomicU64, AtomicU64)>>,     // (samples, jitter_sum)
+    proc_miss: AtomicU64,
+    tx_drops: AtomicU64,
+    probe: Probe,
+}
+
+impl RwLockSink {
+    pub fn with_probe(mut self, probe: Probe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    fn with_sensor(&self, sensor_id: u16, f: impl Fn(&(AtomicU64, AtomicU64))) {
+        let known = self.probe.locked(
+            || self.per_sensor.read(),
+            |map| match map.get(&sensor_id) {
+                Some(counters) => {
+                    f(counters);
+                    true
+                }
+                None => false,
+            },
+        );
+        if known {
+            return;
+        }
+        self.probe.locked(
+            || self.per_sensor.write(),
+            |mut map| f(map.entry(sensor_id).or_insert_with(|| (AtomicU64::new(0), AtomicU64::new(0)))),
+        );
+    }
+}
+
+impl DiagnosticsSink for RwLockSink {
+    fn record_sample(&self, sensor_id: u16) {
+        self.with_sensor(sensor_id, |(samples, _)| {
+            samples.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    fn record_jitter(&self, sensor_id: u16, jitter_us: u64) {
+        self.with_sensor(sensor_id, |(_, jitter)| {
+            jitter.fetch_add(jitter_us, Ordering::Relaxed);
+        });
+    }
+
+    fn record_proc_miss(&self) {
+        self.probe.op();
+        self.proc_miss.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_tx_drop(&self) {
+        self.probe.op();
+        self.tx_drops.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn prepare(&self, sensor_ids: &[u16]) {
+        let mut map = self.per_sensor.write();
+        for &id in sensor_ids {
+            map.entry(id).or_insert_with(|| (AtomicU64::new(0), AtomicU64::new(0)));
+        }
+    }
+
+    fn attach_thread(&self) {
+        init_park_data();
+        drop(self.per_sensor.read());
+    }
+
+    fn snapshot(&self) -> Diagnostics {
+        let map = self.per_sensor.read();
+        Diagnostics {
+            sample_count: map.iter().map(|(id, (s, _))| (*id, s.load(Ordering::Relaxed))).collect(),
+            jitter_sum: map.iter().map(|(id, (_, j))| (*id, j.load(Ordering::Relaxed))).collect(),
+            proc_miss_count: self.proc_miss.load(Ordering::Relaxed),
+            tx_drop_count: self.tx_drops.load(Ordering::Relaxed),
+            ..Diagnostics::default()
+        }
+    }
+}
+
+// ============================================================================
+// LOCK-FREE SINK (Non-blocking queue + background consumer)
+// ============================================================================
+// Bounded queue (ArrayQueue): Sensor/Processor push events without blocking.
+// Consumer thread drains queue in background, batches writes, flushes periodically.
+// Risk mitigation: Non-blocking push avoids priority inversion.
+//                   Bounded queue prevents unbounded memory growth.
+//                   Batch flushing reduces syscall jitter.
+pub struct LockFreeSink {
+    log_queue: Arc<ArrayQueue<RawLog>>,
+    dropped_logs: Arc<AtomicU64>,               // Count events dropped due to queue full
+    seq_counter: AtomicU64,                     // Sequence number for ordering events
+    drained: Arc<Mutex<Diagnostics>>,           // Aggregates of events the consumer has written
+    consumer_handle: Mutex<Option<JoinHandle<()>>>,
+    consumer_running: Arc<AtomicBool>,
+    anchor: ClockAnchor,                        // Maps RawLog.ts to wall-clock in the consumer
+    probe: Probe,
+}
+
+impl Default for LockFreeSink {
+    fn default() -> Self {
+        Self {
+            log_queue: Arc::new(ArrayQueue::new(LOG_CAPACITY)),
+            dropped_logs: Arc::new(AtomicU64::new(0)),
+            seq_counter: AtomicU64::new(1),
+            drained: Arc::new(Mutex::new(Diagnostics::default())),
+            consumer_handle: Mutex::new(None),
+            consumer_running: Arc::new(AtomicBool::new(false)),
+            anchor: ClockAnchor::now(),
+            probe: Probe::default(),
+        }
+    }
+}
+
+impl LockFreeSink {
+    pub fn with_probe(mut self, probe: Probe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: ClockAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Non-blocking push: if queue full, drop event and count it.
+    #[inline]
+    fn push(&self, kind: LogEventKind) {
+        let seq = self.seq_counter.fetch_add(1, Ordering::Relaxed);
+        let raw = RawLog {
+            seq,
+            ts: Instant::now(),
+            kind,
+        };
+        self.probe.op();
+        if self.log_queue.push(raw).is_err() {
+            self.dropped_logs.fetch_add(1, Ordering::Relaxed);
+            self.probe.push_failed();
+        }
+    }
+
+    pub fn dropped_log_count(&self) -> u64 {
+        self.dropped_logs.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "bench")]
+    pub fn queue_len(&self) -> usize {
+        self.log_queue.len()
+    }
+
+    #[cfg(feature = "bench")]
+    pub fn queue_capacity(&self) -> usize {
+        self.log_queue.capacity()
+    }
+
+    // ========================================================================
+    // CONSUMER THREAD: Background thread drains queue, batches writes
+    // Reduces contention & syscall jitter vs. inline logging
+    // ========================================================================
+
+    /// `format` selects CSV rows or fixed-width binary records (synclog.rs); `label`
+    /// identifies the run in the binary header; `policy` rotates and fsyncs the CSV log.
+    pub fn start_consumer(
+        &self,
+        output: PathBuf,
+        sensor_map: HashMap<u16, String>,
+        format: SyncLogFormat,
+        label: String,
+        policy: LogPolicy,
+    ) -> Result<(), String> {
+        let mut guard = self.consumer_handle.lock();
+        if guard.is_some() {
+            return Err("consumer already running".into());
+        }
+
+        let q = self.log_queue.clone();
+        let dropped_logs = self.dropped_logs.clone();
+        let drained = self.drained.clone();
+        let running = self.consumer_running.clone();
+        let anchor = self.anchor;
+        running.store(true, Ordering::SeqCst);
+
+        let handle = thread::spawn(move || {
+            let mut out = match LogOutput::create(&output, format, &sensor_map, label, anchor, policy) {
+                Ok(out) => out,
+                Err(e) => {
+                    error!("failed to create sync log {:?}: {:?}", output, e);
+                    return;
+                }
+            };
+            let mut flush_counter = 0usize;
+
+            while running.load(Ordering::SeqCst) {
+                let mut any = false;
+                // Batch dequeue: drain up to 256 events per poll
+                for _ in 0..256 {
+                    match q.pop() {
+                        Some(raw) => {
+                            any = true;
+                            out.write(&raw);
+                            drained.lock().apply_with_histograms(raw.kind);
+                        }
+                        None => break,
+                    }
+                }
+                if any {
+                    flush_counter += 1;
+                    // Batch flushing: only flush to disk after FLUSH_BATCHES batches
+                    // Reduces syscall overhead and jitter
+                    if flush_counter >= FLUSH_BATCHES {
+                        out.flush();
+                        flush_counter = 0;
+                    }
+                } else {
+                    // Queue empty: sleep to avoid busy-loop
+                    thread::sleep(Duration::from_millis(CONSUMER_POLL_MS));
+                }
+            }
+
+            // Final drain: flush all remaining events
+            while let Some(raw) = q.pop() {
+                out.write(&raw);
+                drained.lock().apply_with_histograms(raw.kind);
+            }
+            out.flush();
+            let final_drops = dropped_logs.load(Ordering::Relaxed);
+            debug!(
+                "[SyncManager::consumer] exiting. dropped_logs={}",
+                final_drops
+            );
+        });
+
+        *guard = Some(handle);
+        Ok(())
+    }
+
+    pub fn stop_consumer(&self) {
+        self.consumer_running.store(false, Ordering::SeqCst);
+        let handle = self.consumer_handle.lock().take();
+        if let Some(h) = handle {
+            let _ = h.join();
+        }
+    }
+}
+
+/// Consumer output: CSV rows or binary records, both timed against the sink's `ClockAnchor`.
+/// Record time is `RawLog.ts` (taken by the producer); consume time is when the row is written.
+struct LogOutput {
+    anchor: ClockAnchor,
+    target: LogTarget,
+}
+
+enum LogTarget {
+    Csv(LogWriter),             // Rotated per `LogPolicy`; schema line + columns atop each segment
+    Binary(SyncLogWriter<File>),
+}
+
+impl LogOutput {
+    fn create(
+        path: &PathBuf,
+        format: SyncLogFormat,
+        sensor_map: &HashMap<u16, String>,
+        label: String,
+        anchor: ClockAnchor,
+        policy: LogPolicy,
+    ) -> std::io::Result<Self> {
+        let sensor_map = sensor_map.iter().map(|(id, name)| (*id, name.clone())).collect();
+        let target = match format {
+            SyncLogFormat::Csv => {
+                let header = format!("{}\n{}\n", synclog::csv_schema_line(&sensor_map), synclog::CSV_COLUMNS);
+                LogTarget::Csv(LogWriter::create(path, header, policy)?)
+            }
+            SyncLogFormat::Binary => {
+                let file = File::create(path)?;
+                let header = SyncLogHeader {
+                    version: synclog::VERSION,
+                    anchor_epoch_us: anchor.epoch_us(),
+                    label,
+                    sensor_map,
+                };
+                LogTarget::Binary(SyncLogWriter::new(file, &header)?)
+            }
+        };
+        Ok(Self { anchor, target })
+    }
+
+    /// One monotonic read per event; no wall-clock call on the consumer path.
+    fn write(&mut self, raw: &RawLog) {
+        let consumed = Instant::now();
+        let residency_us = consumed.saturating_duration_since(raw.ts).as_micros().min(u32::MAX as u128) as u32;
+        let rec = SyncRecord::new(raw.seq, self.anchor.offset_ns(raw.ts), raw.kind, residency_us);
+        match &mut self.target {
+            LogTarget::Csv(wtr) => {
+                SyncCsvRow::from_record(&rec, self.anchor.epoch_us()).write_csv(wtr).ok();
+            }
+            LogTarget::Binary(writer) => {
+                writer.write(&rec).ok();
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        match &mut self.target {
+            LogTarget::Csv(wtr) => {
+                wtr.flush().ok();
+            }
+            LogTarget::Binary(writer) => {
+                writer.flush().ok();
+            }
+        }
+    }
+}
+
+impl DiagnosticsSink for LockFreeSink {
+    fn record_sample(&self, sensor_id: u16) {
+        self.push(LogEventKind::Sample { sensor_id });
+    }
+
+    fn record_jitter(&self, sensor_id: u16, jitter_us: u64) {
+        self.push(LogEventKind::Jitter { sensor_id, jitter_us });
+    }
+
+    fn record_proc_miss(&self) {
+        self.push(LogEventKind::ProcMiss);
+    }
+
+    fn record_tx_drop(&self) {
+        self.push(LogEventKind::TxDrop);
+    }
+
+    fn record_rx_latency(&self, latency_us: u64) {
+        self.push(LogEventKind::RxLatency { latency_us });
+    }
+
+    fn record_custom(&self, code: u16) {
+        self.push(LogEventKind::Custom { code });
+    }
+
+    fn snapshot(&self) -> Diagnostics {
+        let mut d = self.drained.lock().clone();
+        d.dropped_logs = self.dropped_log_count();
+        d
+    }
+}
+
+impl Drop for LockFreeSink {
+    fn drop(&mut self) {
+        self.stop_consumer();
+    }
+}
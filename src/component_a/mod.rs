@@ -8,5 +8,9 @@
 pub mod sensor;
 pub mod processor;
 pub mod sync_manager;
+pub mod contention;
+pub mod diagnostics;
+pub mod synclog;
+pub mod pi_mutex;
 pub mod transmitter;
 
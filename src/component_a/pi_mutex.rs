@@ -0,0 +1,122 @@
+//! pi_mutex.rs
+//! POSIX mutex with optional priority inheritance (`PTHREAD_PRIO_INHERIT`).
+//!
+//! - With inheritance, a thread holding the lock runs at the priority of the highest-priority
+//!   waiter, so a medium-priority thread cannot preempt it (bounded priority inversion).
+//! - Without inheritance it is a plain pthread mutex: the baseline for the inversion scenario.
+//! - The pthread object lives in a `Box` so it never moves after initialisation.
+
+use std::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+/// Mutex backed by `pthread_mutex_t`.
+pub struct PthreadMutex<T> {
+    raw: Box<UnsafeCell<libc::pthread_mutex_t>>,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: access to `data` is serialised by the pthread mutex.
+unsafe impl<T: Send> Send for PthreadMutex<T> {}
+unsafe impl<T: Send> Sync for PthreadMutex<T> {}
+
+impl<T> PthreadMutex<T> {
+    /// Mutex using the `PTHREAD_PRIO_INHERIT` protocol.
+    pub fn with_priority_inheritance(value: T) -> Self {
+        Self::new(value, true)
+    }
+
+    /// Plain pthread mutex (`PTHREAD_PRIO_NONE`).
+    pub fn plain(value: T) -> Self {
+        Self::new(value, false)
+    }
+
+    fn new(value: T, inherit: bool) -> Self {
+        let raw = Box::new(UnsafeCell::new(libc::PTHREAD_MUTEX_INITIALIZER));
+
+        // SAFETY: attr is initialised before use and destroyed after; the mutex is
+        // initialised in place at its final (boxed) address.
+        unsafe {
+            let mut attr = MaybeUninit::<libc::pthread_mutexattr_t>::uninit();
+            let rc = libc::pthread_mutexattr_init(attr.as_mut_ptr());
+            assert_eq!(rc, 0, "pthread_mutexattr_init failed");
+
+            let protocol = if inherit {
+                libc::PTHREAD_PRIO_INHERIT
+            } else {
+                libc::PTHREAD_PRIO_NONE
+            };
+            let rc = libc::pthread_mutexattr_setprotocol(attr.as_mut_ptr(), protocol);
+            assert_eq!(rc, 0, "pthread_mutexattr_setprotocol failed");
+
+            let rc = libc::pthread_mutex_init(raw.get(), attr.as_ptr());
+            assert_eq!(rc, 0, "pthread_mutex_init failed");
+            libc::pthread_mutexattr_destroy(attr.as_mut_ptr());
+        }
+
+        Self {
+            raw,
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Blocks until the lock is acquired.
+    pub fn lock(&self) -> PthreadMutexGuard<'_, T> {
+        // SAFETY: the mutex was initialised in `new` and is destroyed only in `drop`.
+        let rc = unsafe { libc::pthread_mutex_lock(self.raw.get()) };
+        assert_eq!(rc, 0, "pthread_mutex_lock failed");
+        PthreadMutexGuard {
+            mutex: self,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T: Default> Default for PthreadMutex<T> {
+    fn default() -> Self {
+        Self::with_priority_inheritance(T::default())
+    }
+}
+
+impl<T> Drop for PthreadMutex<T> {
+    fn drop(&mut self) {
+        // SAFETY: no guard can outlive the mutex, so it is unlocked here.
+        unsafe {
+            libc::pthread_mutex_destroy(self.raw.get());
+        }
+    }
+}
+
+/// RAII guard; unlocks on drop. Not `Send`: pthread mutexes must be unlocked by their owner.
+pub struct PthreadMutexGuard<'a, T> {
+    mutex: &'a PthreadMutex<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T> Deref for PthreadMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T> DerefMut for PthreadMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard holds the lock exclusively.
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T> Drop for PthreadMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: this thread locked the mutex in `lock`.
+        unsafe {
+            libc::pthread_mutex_unlock(self.mutex.raw.get());
+        }
+    }
+}
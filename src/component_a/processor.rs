@@ -1,55 +1,77 @@
 //! Processor: filter sensor data, detect anomalies, enforce deadlines, adjust thresholds via feedback.
 //!
 //! Pipeline: raw sensor data → moving average filter → anomaly detection → deadline check → transmit.
-//! Feedback loop: dynamically adjusts anomaly_threshold based on actuator state (error, ack, unstable).
+//! Feedback loop: dynamically adjusts anomaly_threshold based on actuator state (error, ack, unstable, actuator limits).
 //! Deadline: 200µs per cycle; consecutive misses (3x) recorded for per-component tracking.
+//! Bus: feedback arrives through a bus subscription; detected anomalies are published on the bus.
+//! Watchdogs (`with_watchdogs`): the consecutive-overrun streak feeds the fail-safe supervisor.
+//! Allocation-free cycle: filter windows and jitter timestamps live in per-sensor arrays
+//! (indexed by `SensorType::index`) allocated before the loop.
 //! 
 //! 
 use crossbeam::channel::{Receiver, TryRecvError};
 use std::{
     time::{Duration, Instant},
     sync::Arc,
-    collections::{HashMap, VecDeque},
+    collections::VecDeque,
     hint::black_box,
     thread::sleep,
 };
-use crate::utils::metrics::{SharedMetrics, EventRecorder,DeadlineComponent,push_capped,push_capped_u64};
+use crate::utils::metrics::{SharedMetrics, EventRecorder,DeadlineComponent};
+use crate::utils::bus::{Bus, Subscription, Topic};
+use crate::utils::rt_alloc;
 
 use crate::component_a::{
-    sensor::{SensorData, SensorType},
+    sensor::{SensorData, SensorType, SENSOR_COUNT},
     transmitter::Transmitter,
     sync_manager::SyncManager,
 };
-use crate::component_b::feedback::{Feedback, FeedbackKind};
+use crate::component_b::{failsafe::Watchdogs, feedback::{Feedback, FeedbackKind}};
 
 #[derive(Clone, Debug)]
 pub struct ProcessedPacket {
     pub sensor_type: SensorType,
     pub filtered: f64,
     pub raw: f64,
-    pub timestamp: Instant,
+    pub timestamp: Instant,           // Processor cycle start
+    pub sensor_timestamp: Instant,    // Sensor release (end-to-end age reference)
     pub seq: u64,
 }
 
+/// Anomaly detected by the processor (published on the bus `anomalies` topic).
+#[derive(Clone, Debug)]
+pub struct Anomaly {
+    pub sensor_type: SensorType,
+    pub seq: u64,
+    pub reading: f64,
+    pub filtered: f64,
+    pub threshold: f64,               // anomaly_threshold in force at detection
+    pub timestamp: Instant,           // Sensor release
+}
+
 /// Processor: Filter, detect anomalies, transmit to actuators.
 /// REQUIREMENT 2: Closes feedback loop via dynamic threshold adjustment.
 pub struct Processor {
     rx: Receiver<SensorData>,
-    feedback_rx: Receiver<Feedback>,      // Feedback channel (Component B → A)
+    feedback_rx: Subscription<Feedback>,  // Feedback subscription (Component B → A)
     window_size: usize,
     anomaly_threshold: f64,               // Dynamically adjusted via feedback
+    threshold_limits: (f64, f64),         // Bounds of every adjustment (min, max)
     deadline_us: u64,
     expected_interval_us: u64,
     sync: Arc<SyncManager>,
     transmitter: Arc<Transmitter>,
     metrics: SharedMetrics,
     event_recorder: Arc<EventRecorder>,
+    anomalies: Option<Topic<Anomaly>>,    // Bus topic for detected anomalies
+    watchdogs: Option<Arc<Watchdogs>>,    // Fail-safe supervisor (overrun streak)
 }
 
 impl Processor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         rx: Receiver<SensorData>,
-        feedback_rx: Receiver<Feedback>,  // NEW: Feedback channel
+        feedback_rx: Subscription<Feedback>,  // Feedback subscription
         window_size: usize,
         anomaly_threshold: f64,
         deadline_us: u64,
@@ -64,15 +86,38 @@ impl Processor {
             feedback_rx,
             window_size,
             anomaly_threshold,
+            threshold_limits: (0.0, f64::INFINITY),
             deadline_us,
             expected_interval_us,
             sync,
             transmitter,
             metrics,
             event_recorder,
+            anomalies: None,
+            watchdogs: None,
         }
     }
 
+    /// Publishes detected anomalies on the bus.
+    pub fn with_bus(mut self, bus: &Bus) -> Self {
+        self.anomalies = Some(bus.anomalies.clone());
+        self
+    }
+
+    /// Keeps the feedback-adjusted threshold within `[min, max]` (standard deviations); without
+    /// limits a steady stream of one feedback kind drives it to 0 or to infinity.
+    pub fn with_threshold_limits(mut self, min: f64, max: f64) -> Self {
+        self.threshold_limits = (min.min(max), max.max(min));
+        self.anomaly_threshold = self.anomaly_threshold.clamp(self.threshold_limits.0, self.threshold_limits.1);
+        self
+    }
+
+    /// Reports the consecutive-overrun streak to the fail-safe supervisor.
+    pub fn with_watchdogs(mut self, watchdogs: Arc<Watchdogs>) -> Self {
+        self.watchdogs = Some(watchdogs);
+        self
+    }
+
     /// Main processing loop.
     /// - Receives raw sensor data from channel
     /// - Processes (filter, anomaly detection, deadline check)
@@ -81,10 +126,14 @@ impl Processor {
     pub fn run(&mut self) {
         println!("[Processor] started window={} deadline={}us", self.window_size, self.deadline_us);
         
-        let mut buffers: HashMap<SensorType, VecDeque<f64>> = HashMap::new();
-        let mut last_ts: HashMap<SensorType, Instant> = HashMap::new();
+        let mut buffers: [VecDeque<f64>; SENSOR_COUNT] =
+            std::array::from_fn(|_| VecDeque::with_capacity(self.window_size + 1));
+        let mut last_ts: [Option<Instant>; SENSOR_COUNT] = [None; SENSOR_COUNT];
         let mut consecutive_overruns: u32 = 0;
-        const MISS_CONFIRM_THRESHOLD: u32 = 3;
+
+        self.event_recorder.attach_thread();
+        self.sync.attach_thread();
+        let _rt = rt_alloc::enter("processor");
 
         loop {
             // ====================================================================
@@ -95,7 +144,7 @@ impl Processor {
                 match fb.kind {
                     FeedbackKind::Error("unstable_sensor") => {
                         // Actuator detected unstable sensor: relax threshold to reduce false anomalies
-                        self.anomaly_threshold *= 1.1;
+                        self.adjust_threshold(1.1);
                         println!(
                             "[Processor] Feedback: Unstable sensor. Relaxed threshold to {:.2}",
                             self.anomaly_threshold
@@ -103,17 +152,21 @@ impl Processor {
                     }
                     FeedbackKind::Error("deadline_miss") => {
                         // Actuator missed deadline: tighten threshold to reduce noise
-                        self.anomaly_threshold *= 0.95;
+                        self.adjust_threshold(0.95);
                         println!(
                             "[Processor] Feedback: Deadline miss. Tightened threshold to {:.2}",
                             self.anomaly_threshold
                         );
                     }
-                    FeedbackKind::Ack => {
+                    FeedbackKind::Saturated(_) | FeedbackKind::RateLimited(_) => {
+                        // Actuator at a limit: the loop is about to deviate from its setpoint
+                        // without any sensor fault; relax slightly against false anomalies
+                        // (no log line: a chattering loop hits its limits many times a second)
+                        self.adjust_threshold(1.02);
+                    }
+                    FeedbackKind::Ack if self.anomaly_threshold > 1.5 => {
                         // Successful actuation: slowly fine-tune threshold toward optimum
-                        if self.anomaly_threshold > 1.5 {
-                            self.anomaly_threshold *= 0.999;
-                        }
+                        self.adjust_threshold(0.999);
                     }
                     _ => {}
                 }
@@ -125,11 +178,11 @@ impl Processor {
                     let sid = sensor_to_id(&data.sensor_type);
 
                     // Track sensor jitter (scheduling precision)
-                    let jitter_abs = last_ts
-                        .insert(data.sensor_type, data.timestamp)
+                    let jitter_abs = last_ts[data.sensor_type.index()]
+                        .replace(data.timestamp)
                         .map(|prev| {
                             let actual = data.timestamp.duration_since(prev).as_micros() as i64;
-                            (actual - self.expected_interval_us as i64).abs() as u64
+                            (actual - self.expected_interval_us as i64).unsigned_abs()
                         })
                         .unwrap_or(0);
                     self.sync.record_jitter(sid, jitter_abs);
@@ -139,6 +192,16 @@ impl Processor {
 
                     if is_anomaly {
                         self.sync.record_custom(100 + sid);
+                        if let Some(topic) = &self.anomalies {
+                            topic.publish(Anomaly {
+                                sensor_type: data.sensor_type,
+                                seq: data.seq,
+                                reading: data.reading,
+                                filtered: avg,
+                                threshold: self.anomaly_threshold,
+                                timestamp: data.timestamp,
+                            });
+                        }
                     }
 
                     // Record filtered result event
@@ -156,6 +219,7 @@ impl Processor {
                         filtered: avg,
                         raw: data.reading,
                         timestamp: cycle_start,
+                        sensor_timestamp: data.timestamp,
                         seq: data.seq,
                     };
                     self.transmitter.transmit(pkt);
@@ -163,7 +227,7 @@ impl Processor {
 
                     // SECTION 3: Deadline enforcement (200 µs)
                     let elapsed_us = cycle_start.elapsed().as_micros() as u64;
-                    self.update_metrics(elapsed_us, &mut consecutive_overruns, MISS_CONFIRM_THRESHOLD);
+                    self.update_metrics(elapsed_us, &mut consecutive_overruns);
                      //self.update_metrics(elapsed_us);  
                     
                 }
@@ -180,6 +244,13 @@ impl Processor {
         }
     }
 
+    /// Scales the anomaly threshold by `factor`, clamped to the configured limits.
+    #[inline]
+    fn adjust_threshold(&mut self, factor: f64) {
+        let (min, max) = self.threshold_limits;
+        self.anomaly_threshold = (self.anomaly_threshold * factor).clamp(min, max);
+    }
+
     /// Process sensor data: moving average filter + anomaly detection.
     /// SECTION 1: Noise-reduction filter (moving average)
     /// SECTION 2: Anomaly detection (statistical threshold - uses dynamic self.anomaly_threshold)
@@ -187,10 +258,10 @@ impl Processor {
     pub fn process_data(
         &self,
         data: &SensorData,
-        buffers: &mut HashMap<SensorType, VecDeque<f64>>,
+        buffers: &mut [VecDeque<f64>; SENSOR_COUNT],
     ) -> (f64, bool) {
         // SECTION 1: Moving average filter
-        let buf = buffers.entry(data.sensor_type).or_default();
+        let buf = &mut buffers[data.sensor_type.index()];
         buf.push_back(data.reading);
 
         if buf.len() > self.window_size {
@@ -203,18 +274,8 @@ impl Processor {
 
         let avg = buf.iter().sum::<f64>() / buf.len() as f64;
 
-        // Store filtered values in metrics
-        {
-            let mut m = match self.metrics.lock() {
-                Ok(g) => g,
-                Err(poisoned) => poisoned.into_inner(),
-            };
-            match data.sensor_type {
-                SensorType::Force => push_capped(&mut m.force, avg),
-                SensorType::Position => push_capped(&mut m.position, avg),
-                SensorType::Temperature => push_capped(&mut m.temperature, avg),
-            }
-        }
+        // Store filtered values in metrics (lock-free ring)
+        self.metrics.live().reading(data.sensor_type).push(avg);
 
         // SECTION 3b: Simulated CPU work (creates realistic deadline pressure)
         const BUSY_US: u128 = 110;
@@ -236,40 +297,37 @@ impl Processor {
 ///
 /// **Dual approach:**
 /// - **Immediate recording:** Every deadline miss logged for accuracy (real-time requirement).
-/// - **Consecutive tracking:** Monitors for systemic problems (3+ misses = critical pattern).
+/// - **Consecutive tracking:** Reports the streak to the fail-safe supervisor, which degrades or
+///   stops the system on a sustained overload (`overrun_degraded` / `overrun_critical`) and logs
+///   the transition off the RT path.
 ///
 /// This captures all failures while identifying sustained overload vs transient spikes.
 ///
 /// # Arguments
 /// * `elapsed_us` — Actual cycle execution time (microseconds).
 /// * `consecutive_overruns` — Mutable counter; increments on overrun, resets on success.
-/// * `threshold` — Consecutive threshold (typically 3); triggers critical alert when reached.
-fn update_metrics(&self, elapsed_us: u64, consecutive_overruns: &mut u32, threshold: u32) {
-    let mut m = match self.metrics.lock() {
-        Ok(g) => g,
-        Err(poisoned) => poisoned.into_inner(),
-    };
+fn update_metrics(&self, elapsed_us: u64, consecutive_overruns: &mut u32) {
+    let live = self.metrics.live();
 
     // Record latency and increment cycle counter
-    push_capped_u64(&mut m.latency_us, elapsed_us);
-    m.total_cycles += 1;
+    live.latency_us.push(elapsed_us);
+    live.record_cycle();
 
     // Deadline enforcement: 200µs per cycle
     if elapsed_us > self.deadline_us {
         // Record every miss immediately (accuracy for real-time monitoring)
-        m.record_deadline_miss(DeadlineComponent::Processor);
+        live.record_deadline_miss(DeadlineComponent::Processor);
         self.sync.record_proc_miss();  // Log to lock-free sync CSV
         
         // Also track consecutive misses for pattern detection
         *consecutive_overruns += 1;
-        if *consecutive_overruns >= threshold {
-            // Critical: sustained overload detected (3+ consecutive misses)
-            eprintln!("[Processor] CRITICAL: {} consecutive deadline misses - systemic overload", *consecutive_overruns);
-        }
     } else {
         // Cycle succeeded: reset consecutive counter
         *consecutive_overruns = 0;
     }
+    if let Some(watchdogs) = &self.watchdogs {
+        watchdogs.set_overrun_streak(*consecutive_overruns);
+    }
 }
 }
 
@@ -3,19 +3,32 @@
 //! Simulates physical sensors (force, position, temperature) with periodic releases.
 //! - Real-time scheduling: SpinSleeper maintains consistent sampling rates (5 ms)
 //! - Deadline tracking: Reports scheduling misses to both SyncManager (CSV) and SharedMetrics (Dashboard)
+//! - Optional bus: every sample is also published on the `sensor_data` topic for observers
+//! - Allocation-free loop: interned sensor names, RNG warmed up before the RT section
+//! - Optional plant (`with_plant`): readings sample the plant output driven by the paired
+//!   actuator instead of the constant `base_value` (see `component_b/plant.rs`)
+//! - Optional watchdogs (`with_watchdogs`): every release is a heartbeat for the fail-safe supervisor
 
 use crossbeam::channel::Sender;
-use rand::random_range;
+use rand::Rng;
+use serde::Deserialize;
 use spin_sleep::{SpinSleeper, SpinStrategy};
 use std::{
     sync::{Arc, atomic::{AtomicBool, Ordering}},
     time::{Duration, Instant},
 };
 use crate::component_a::sync_manager::SyncManager;
-use crate::utils::metrics::{SharedMetrics, push_capped, push_capped_u64, EventRecorder, Event,DeadlineComponent};
+use crate::component_b::{failsafe::Watchdogs, plant::Plant};
+use crate::utils::bus::{Bus, Topic};
+use crate::utils::rt_alloc;
+use crate::utils::metrics::{SharedMetrics, EventRecorder, Event,DeadlineComponent};
 use log::debug;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Number of `SensorType` variants (size of per-sensor arrays on the RT path).
+pub const SENSOR_COUNT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SensorType {
     Force,
     Position,
@@ -23,6 +36,19 @@ pub enum SensorType {
 }
 
 impl SensorType {
+    /// Every variant, in `index` order.
+    pub const ALL: [SensorType; SENSOR_COUNT] = [SensorType::Force, SensorType::Position, SensorType::Temperature];
+
+    /// Dense index in `0..SENSOR_COUNT`; replaces per-sample `HashMap` lookups.
+    #[inline]
+    pub fn index(&self) -> usize {
+        match self {
+            SensorType::Force => 0,
+            SensorType::Position => 1,
+            SensorType::Temperature => 2,
+        }
+    }
+
     pub fn base_value(&self) -> f64 {
         match self {
             SensorType::Force => 100.0,
@@ -65,9 +91,13 @@ pub struct Sensor {
     pub sync: Arc<SyncManager>,
     pub metrics: SharedMetrics,
     pub event_recorder: Arc<EventRecorder>,
+    pub sensor_data: Option<Topic<SensorData>>,
+    pub plant: Option<Plant>,
+    pub watchdogs: Option<Arc<Watchdogs>>,
 }
 
 impl Sensor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: &str,
         sampling_rate_ms: u64,
@@ -87,12 +117,33 @@ impl Sensor {
             sync,
             metrics,
             event_recorder,
+            sensor_data: None,
+            plant: None,
+            watchdogs: None,
         }
     }
 
+    /// Publishes every sample on the bus `sensor_data` topic.
+    pub fn with_bus(mut self, bus: &Bus) -> Self {
+        self.sensor_data = Some(bus.sensor_data.clone());
+        self
+    }
+
+    /// Samples `plant` (plus noise) instead of the constant base value.
+    pub fn with_plant(mut self, plant: Plant) -> Self {
+        self.plant = Some(plant);
+        self
+    }
+
+    /// Reports every release to the fail-safe supervisor (sensor heartbeat).
+    pub fn with_watchdogs(mut self, watchdogs: Arc<Watchdogs>) -> Self {
+        self.watchdogs = Some(watchdogs);
+        self
+    }
+
     /// Main sensor loop: periodic release with real-time scheduling.
     /// Reports deadline misses to both SyncManager (CSV) and SharedMetrics (Dashboard).
-    pub fn run(&self) {
+    pub fn run(&mut self) {
         // ====================================================================
         // Real-Time Scheduling: Initialize periodic release schedule
         // ====================================================================
@@ -104,6 +155,12 @@ impl Sensor {
         let mut last_tick = Instant::now();
         let mut seq: u64 = 1;
 
+        // Thread-local RNG and event buffer are created lazily: do it before the RT section
+        let mut rng = rand::rng();
+        self.event_recorder.attach_thread();
+        self.sync.attach_thread();
+        let _rt = rt_alloc::enter(self.sensor_type.name());
+
         while self.running.load(Ordering::Acquire) {
             // ====================================================================
             // Real-Time Scheduling: Wait until next scheduled release
@@ -116,16 +173,9 @@ impl Sensor {
                 // Report to SyncManager (CSV logs)
                 self.sync.record_proc_miss();
                 
-                // Report to SharedMetrics (Dashboard visibility)
+                // Report to SharedMetrics (Dashboard visibility, lock-free)
                 // This tracks SENSOR scheduling misses separately from Processor/Actuator
-                {
-                    let mut m = match self.metrics.lock() {
-                        Ok(guard) => guard,
-                        Err(poisoned) => poisoned.into_inner(),
-                    };
-                    //m.miss_sensor += 1;  // Specific counter for sensor scheduling
-                    m.record_deadline_miss(DeadlineComponent::Sensor);
-                }
+                self.metrics.live().record_deadline_miss(DeadlineComponent::Sensor);
             }
 
             let actual_tick = Instant::now();
@@ -137,8 +187,11 @@ impl Sensor {
             self.event_recorder.record(Event::SensorRelease {
                 seq,
                 ts_ns: t0_ns,
-                sensor_type: self.sensor_type.name().to_string(),
+                sensor_type: self.sensor_type.name(),
             });
+            if let Some(watchdogs) = &self.watchdogs {
+                watchdogs.sensor_beat(self.sensor_type);
+            }
 
             // ====================================================================
             // Sampling Rate Consistency: Measure jitter
@@ -148,11 +201,14 @@ impl Sensor {
             last_tick = actual_tick;
 
             // ====================================================================
-            // Sensor Simulation: Generate reading with noise
+            // Sensor Simulation: Sample plant (or constant base value) with noise
             // ====================================================================
-            let base = self.sensor_type.base_value();
+            let base = match self.plant.as_mut() {
+                Some(plant) => plant.sample(actual_tick),
+                None => self.sensor_type.base_value(),
+            };
             let (lo, hi) = self.sensor_type.noise_range();
-            let reading = base + random_range(lo..hi);
+            let reading = base + rng.random_range(lo..hi);
 
             // NOTE: Filtering and anomaly detection happen in Processor (Component A)
             // This sensor only generates raw readings at fixed intervals
@@ -165,6 +221,10 @@ impl Sensor {
                 seq,
             };
 
+            if let Some(topic) = &self.sensor_data {
+                topic.publish(data.clone());
+            }
+
             // Try to send without blocking; update sync counters on success/failure
             let mut sent = false;
             match self.tx.try_send(data) {
@@ -193,25 +253,15 @@ impl Sensor {
                 queue_len,
             });
 
-            // Update sensor-local metrics
-            {
-                let mut m = match self.metrics.lock() {
-                    Ok(guard) => guard,
-                    Err(poisoned) => poisoned.into_inner(),
-                };
-
-                if sent {
-                    match self.sensor_type {
-                        SensorType::Force => push_capped(&mut m.force, reading),
-                        SensorType::Position => push_capped(&mut m.position, reading),
-                        SensorType::Temperature => push_capped(&mut m.temperature, reading),
-                    }
-                }
-
-                // Keep jitter history for diagnostics
-                push_capped_u64(&mut m.jitter_us, jitter_us);
+            // Update sensor-local metrics (lock-free rings)
+            let live = self.metrics.live();
+            if sent {
+                live.reading(self.sensor_type).push(reading);
             }
 
+            // Keep jitter history for diagnostics
+            live.jitter_us.push(jitter_us);
+
             // ====================================================================
             // Real-Time Scheduling: Schedule next release
             // ====================================================================
@@ -0,0 +1,689 @@
+//! synclog.rs
+//! Compact binary format for the LockFree SyncManager log (`[sync] log_format = "binary"`).
+//!
+//! - File = header + fixed-width 32-byte records (little endian); no per-event string formatting
+//!   on the consumer thread, which shares core 0 with the pipeline.
+//! - Header: magic, version, record size, run anchor (epoch µs), run label, sensor map.
+//! - Record: seq, record time relative to the anchor, kind, id (sensor id / custom code),
+//!   argument (jitter / latency in µs), queue residency (record → consume).
+//! - Times come from one `ClockAnchor` taken at `SyncManager` creation: record time is the
+//!   producer's `Instant`, mapped to wall-clock once, not the consumer's `SystemTime::now()`.
+//! - `SyncLogReader` decodes a file; the `synclog` binary converts it to CSV/JSON and summarises.
+//! - The CSV log uses typed columns (`SyncCsvRow`, schema 3) behind a `# rts_sync_log schema=N`
+//!   line; `read_csv` also accepts schema 1 (string-encoded `event` + `value`) and schema 2
+//!   (consume-time `ts_epoch_us` + `age_us`) files.
+//!
+//! Header layout:
+//! ```text
+//! magic[8] "RTSSYNC\0" | version u16 | record_size u16 | anchor_epoch_us u64 |
+//! label_len u16 | label | sensor_count u16 | { id u16 | name_len u16 | name }*
+//! ```
+//! Record layout:
+//! ```text
+//! seq u64 | t_ns i64 | arg u64 | id u16 | kind u8 | reserved u8 | residency_us u32
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::component_a::diagnostics::LogEventKind;
+
+pub const MAGIC: [u8; 8] = *b"RTSSYNC\0";
+pub const VERSION: u16 = 1;
+pub const RECORD_SIZE: usize = 32;
+
+/// Monotonic ↔ wall-clock correspondence, taken once so every event time derives from
+/// the producer's `Instant` rather than from when the consumer got to it.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockAnchor {
+    instant: Instant,
+    epoch_us: u64,
+}
+
+impl ClockAnchor {
+    pub fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            epoch_us: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_micros() as u64,
+        }
+    }
+
+    /// Wall-clock time of the anchor.
+    #[inline]
+    pub fn epoch_us(&self) -> u64 {
+        self.epoch_us
+    }
+
+    /// Signed offset of `t` from the anchor (events may predate it by a few ns).
+    #[inline]
+    pub fn offset_ns(&self, t: Instant) -> i64 {
+        match t.checked_duration_since(self.instant) {
+            Some(d) => d.as_nanos() as i64,
+            None => -(self.instant.duration_since(t).as_nanos() as i64),
+        }
+    }
+}
+
+#[inline]
+fn epoch_plus_ns(epoch_us: u64, offset_ns: i64) -> u64 {
+    (epoch_us as i64 + offset_ns / 1_000).max(0) as u64
+}
+
+/// Output format of the LockFree consumer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncLogFormat {
+    #[default]
+    Csv,        // Human-readable; one formatted string per event
+    Binary,     // Fixed-width records; decode with `synclog`
+}
+
+/// Event kind tag stored in each record (and the CSV `event_kind` column).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum RecordKind {
+    Sample = 1,
+    Jitter = 2,
+    ProcMiss = 3,
+    TxDrop = 4,
+    RxLatency = 5,
+    Custom = 6,
+}
+
+#[allow(dead_code)] // Names/parsing used by the decoder side (`synclog`, sync_vs_async)
+impl RecordKind {
+    pub const ALL: [RecordKind; 6] = [
+        RecordKind::Sample,
+        RecordKind::Jitter,
+        RecordKind::ProcMiss,
+        RecordKind::TxDrop,
+        RecordKind::RxLatency,
+        RecordKind::Custom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecordKind::Sample => "sample",
+            RecordKind::Jitter => "jitter",
+            RecordKind::ProcMiss => "proc_miss",
+            RecordKind::TxDrop => "tx_drop",
+            RecordKind::RxLatency => "rx_latency",
+            RecordKind::Custom => "custom",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+
+    fn from_u8(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| *k as u8 == tag)
+    }
+}
+
+/// File header: run metadata written once before the records.
+#[derive(Debug, Clone, Default)]
+pub struct SyncLogHeader {
+    #[allow(dead_code)]
+    pub version: u16,
+    pub anchor_epoch_us: u64,                   // Wall-clock time of the SyncManager's ClockAnchor (t_ns = 0)
+    pub label: String,                          // Run label, e.g. "load_4"
+    pub sensor_map: BTreeMap<u16, String>,
+}
+
+/// One decoded event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncRecord {
+    pub seq: u64,
+    pub t_ns: i64,          // Record time relative to the header anchor
+    pub kind: RecordKind,
+    pub id: u16,            // Sensor id (sample/jitter) or custom code; 0 otherwise
+    pub arg: u64,           // jitter_us / latency_us; 0 otherwise
+    pub residency_us: u32,  // Queue residency: record → consumed
+}
+
+impl SyncRecord {
+    pub fn new(seq: u64, t_ns: i64, kind: LogEventKind, residency_us: u32) -> Self {
+        let (kind, id, arg) = match kind {
+            LogEventKind::Sample { sensor_id } => (RecordKind::Sample, sensor_id, 0),
+            LogEventKind::Jitter { sensor_id, jitter_us } => (RecordKind::Jitter, sensor_id, jitter_us),
+            LogEventKind::ProcMiss => (RecordKind::ProcMiss, 0, 0),
+            LogEventKind::TxDrop => (RecordKind::TxDrop, 0, 0),
+            LogEventKind::RxLatency { latency_us } => (RecordKind::RxLatency, 0, latency_us),
+            LogEventKind::Custom { code } => (RecordKind::Custom, code, 0),
+        };
+        Self { seq, t_ns, kind, id, arg, residency_us }
+    }
+
+    /// Wall-clock record time.
+    #[allow(dead_code)]
+    pub fn record_epoch_us(&self, header: &SyncLogHeader) -> u64 {
+        epoch_plus_ns(header.anchor_epoch_us, self.t_ns)
+    }
+
+    /// Microsecond argument as a float (jitter / latency; 0 for other kinds).
+    #[allow(dead_code)]
+    pub fn value(&self) -> f64 {
+        self.arg as f64
+    }
+
+    fn encode(&self) -> [u8; RECORD_SIZE] {
+        let mut buf = [0u8; RECORD_SIZE];
+        buf[0..8].copy_from_slice(&self.seq.to_le_bytes());
+        buf[8..16].copy_from_slice(&self.t_ns.to_le_bytes());
+        buf[16..24].copy_from_slice(&self.arg.to_le_bytes());
+        buf[24..26].copy_from_slice(&self.id.to_le_bytes());
+        buf[26] = self.kind as u8;
+        buf[28..32].copy_from_slice(&self.residency_us.to_le_bytes());
+        buf
+    }
+
+    #[allow(dead_code)]
+    fn decode(buf: &[u8; RECORD_SIZE]) -> io::Result<Self> {
+        let kind = RecordKind::from_u8(buf[26])
+            .ok_or_else(|| invalid(format!("unknown record kind {}", buf[26])))?;
+        Ok(Self {
+            seq: u64::from_le_bytes(buf[0..8].try_into().unwrap_or_default()),
+            t_ns: i64::from_le_bytes(buf[8..16].try_into().unwrap_or_default()),
+            arg: u64::from_le_bytes(buf[16..24].try_into().unwrap_or_default()),
+            id: u16::from_le_bytes([buf[24], buf[25]]),
+            kind,
+            residency_us: u32::from_le_bytes(buf[28..32].try_into().unwrap_or_default()),
+        })
+    }
+}
+
+#[allow(dead_code)]
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+// ============================================================================
+// WRITER
+// ============================================================================
+
+pub struct SyncLogWriter<W: Write> {
+    out: BufWriter<W>,
+}
+
+impl<W: Write> SyncLogWriter<W> {
+    /// Writes the header; records follow with `write`.
+    pub fn new(inner: W, header: &SyncLogHeader) -> io::Result<Self> {
+        let mut out = BufWriter::new(inner);
+        out.write_all(&MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&(RECORD_SIZE as u16).to_le_bytes())?;
+        out.write_all(&header.anchor_epoch_us.to_le_bytes())?;
+        write_str(&mut out, &header.label)?;
+        out.write_all(&(header.sensor_map.len() as u16).to_le_bytes())?;
+        for (id, name) in &header.sensor_map {
+            out.write_all(&id.to_le_bytes())?;
+            write_str(&mut out, name)?;
+        }
+        Ok(Self { out })
+    }
+
+    #[inline]
+    pub fn write(&mut self, record: &SyncRecord) -> io::Result<()> {
+        self.out.write_all(&record.encode())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn write_str<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    let bytes = &s.as_bytes()[..s.len().min(u16::MAX as usize)];
+    out.write_all(&(bytes.len() as u16).to_le_bytes())?;
+    out.write_all(bytes)
+}
+
+// ============================================================================
+// READER
+// ============================================================================
+
+/// Streaming decoder; iterate to get records in file order.
+#[allow(dead_code)]
+pub struct SyncLogReader<R: Read> {
+    pub header: SyncLogHeader,
+    input: R,
+    record_size: usize,
+}
+
+#[allow(dead_code)]
+impl SyncLogReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+#[allow(dead_code)]
+impl<R: Read> SyncLogReader<R> {
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid("not a SyncManager binary log (bad magic)"));
+        }
+
+        let version = read_u16(&mut input)?;
+        if version > VERSION {
+            return Err(invalid(format!("unsupported log version {}", version)));
+        }
+        let record_size = read_u16(&mut input)? as usize;
+        if record_size < RECORD_SIZE {
+            return Err(invalid(format!("record size {} too small", record_size)));
+        }
+
+        let anchor_epoch_us = read_u64(&mut input)?;
+        let label = read_str(&mut input)?;
+        let mut sensor_map = BTreeMap::new();
+        for _ in 0..read_u16(&mut input)? {
+            let id = read_u16(&mut input)?;
+            sensor_map.insert(id, read_str(&mut input)?);
+        }
+
+        Ok(Self {
+            header: SyncLogHeader { version, anchor_epoch_us, label, sensor_map },
+            input,
+            record_size,
+        })
+    }
+}
+
+impl<R: Read> Iterator for SyncLogReader<R> {
+    type Item = io::Result<SyncRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = [0u8; RECORD_SIZE];
+        match self.input.read_exact(&mut buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return None,   // End (or torn last record)
+            Err(e) => return Some(Err(e)),
+        }
+        // Newer versions may append fields: skip what this decoder does not know
+        if self.record_size > RECORD_SIZE {
+            let mut rest = vec![0u8; self.record_size - RECORD_SIZE];
+            if let Err(e) = self.input.read_exact(&mut rest) {
+                return (e.kind() != io::ErrorKind::UnexpectedEof).then_some(Err(e));
+            }
+        }
+        Some(SyncRecord::decode(&buf))
+    }
+}
+
+#[allow(dead_code)]
+fn read_u16<R: Read>(r: &mut R) -> io::Result<u16> {
+    let mut b = [0u8; 2];
+    r.read_exact(&mut b)?;
+    Ok(u16::from_le_bytes(b))
+}
+
+#[allow(dead_code)]
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut b = [0u8; 8];
+    r.read_exact(&mut b)?;
+    Ok(u64::from_le_bytes(b))
+}
+
+#[allow(dead_code)]
+fn read_str<R: Read>(r: &mut R) -> io::Result<String> {
+    let mut bytes = vec![0u8; read_u16(r)? as usize];
+    r.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|e| invalid(e.to_string()))
+}
+
+// ============================================================================
+// CSV SCHEMA
+// ============================================================================
+
+pub const CSV_SCHEMA_VERSION: u32 = 3;
+
+/// First line of every CSV log since schema 2 (schema 1 files start with the column header),
+/// e.g. `# rts_sync_log schema=3 sensors=1:Force,2:Position`.
+pub fn csv_schema_line(sensor_map: &BTreeMap<u16, String>) -> String {
+    let sensors: Vec<String> = sensor_map.iter().map(|(id, name)| format!("{}:{}", id, name)).collect();
+    format!("# rts_sync_log schema={} sensors={}", CSV_SCHEMA_VERSION, sensors.join(","))
+}
+
+/// Column header of schema 3 (the field order of `SyncCsvRow`).
+pub const CSV_COLUMNS: &str = "seq,record_epoch_us,consume_epoch_us,residency_us,event_kind,sensor_id,value_us,code";
+
+/// One CSV log row (schema 3). Columns that do not apply to the kind are left empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncCsvRow {
+    pub seq: u64,
+    pub record_epoch_us: u64,       // When the producer recorded the event (anchored monotonic clock)
+    pub consume_epoch_us: u64,      // When the consumer wrote it
+    pub residency_us: u64,          // Queue residency (consume - record)
+    pub event_kind: RecordKind,
+    pub sensor_id: Option<u16>,     // sample, jitter
+    pub value_us: Option<u64>,      // jitter, rx_latency
+    pub code: Option<u16>,          // custom
+}
+
+impl SyncCsvRow {
+    pub fn from_record(rec: &SyncRecord, anchor_epoch_us: u64) -> Self {
+        let (sensor_id, value_us, code) = match rec.kind {
+            RecordKind::Sample => (Some(rec.id), None, None),
+            RecordKind::Jitter => (Some(rec.id), Some(rec.arg), None),
+            RecordKind::RxLatency => (None, Some(rec.arg), None),
+            RecordKind::Custom => (None, None, Some(rec.id)),
+            RecordKind::ProcMiss | RecordKind::TxDrop => (None, None, None),
+        };
+        let record_epoch_us = epoch_plus_ns(anchor_epoch_us, rec.t_ns);
+        Self {
+            seq: rec.seq,
+            record_epoch_us,
+            consume_epoch_us: record_epoch_us + rec.residency_us as u64,
+            residency_us: rec.residency_us as u64,
+            event_kind: rec.kind,
+            sensor_id,
+            value_us,
+            code,
+        }
+    }
+
+    /// Writes the row as one CSV line (same output as serde, without a csv buffer in between,
+    /// so every row reaches the rotating log writer whole).
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        fn opt<T: std::fmt::Display>(v: Option<T>) -> String {
+            v.map(|v| v.to_string()).unwrap_or_default()
+        }
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            self.seq,
+            self.record_epoch_us,
+            self.consume_epoch_us,
+            self.residency_us,
+            self.event_kind.name(),
+            opt(self.sensor_id),
+            opt(self.value_us),
+            opt(self.code)
+        )
+    }
+
+    /// Schema 1 row: kind and arguments are encoded in `event`
+    /// (`Force`, `sensor:4`, `jitter:12us@sensor:1`, `rx_latency:42us`, `custom:900`, ...).
+    /// Samples logged under a sensor name carry no `sensor_id`.
+    #[allow(dead_code)]
+    fn from_v1(seq: u64, ts_epoch_us: u64, age_us: u64, event: &str) -> Self {
+        let event = event.trim();
+        let number = |s: &str| s.trim_end_matches("us").parse::<u64>().ok();
+
+        let (event_kind, sensor_id, value_us, code) = if let Some(rest) = event.strip_prefix("jitter:") {
+            let (us, sensor) = rest.split_once("@sensor:").unwrap_or((rest, ""));
+            (RecordKind::Jitter, sensor.parse().ok(), number(us), None)
+        } else if let Some(us) = event.strip_prefix("rx_latency:") {
+            (RecordKind::RxLatency, None, number(us), None)
+        } else if let Some(c) = event.strip_prefix("custom:") {
+            (RecordKind::Custom, None, None, c.parse().ok())
+        } else if event == "proc_miss" {
+            (RecordKind::ProcMiss, None, None, None)
+        } else if event == "tx_drop" {
+            (RecordKind::TxDrop, None, None, None)
+        } else {
+            (RecordKind::Sample, event.strip_prefix("sensor:").and_then(|id| id.parse().ok()), None, None)
+        };
+
+        CsvRowV2 { seq, ts_epoch_us, age_us, event_kind, sensor_id, value_us, code }.into()
+    }
+}
+
+/// Schema 2 row. `ts_epoch_us` was the consume time; the record time is reconstructed from `age_us`.
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct CsvRowV2 {
+    seq: u64,
+    ts_epoch_us: u64,
+    age_us: u64,
+    event_kind: RecordKind,
+    sensor_id: Option<u16>,
+    value_us: Option<u64>,
+    code: Option<u16>,
+}
+
+impl From<CsvRowV2> for SyncCsvRow {
+    fn from(r: CsvRowV2) -> Self {
+        Self {
+            seq: r.seq,
+            record_epoch_us: r.ts_epoch_us.saturating_sub(r.age_us),
+            consume_epoch_us: r.ts_epoch_us,
+            residency_us: r.age_us,
+            event_kind: r.event_kind,
+            sensor_id: r.sensor_id,
+            value_us: r.value_us,
+            code: r.code,
+        }
+    }
+}
+
+/// A CSV log read by `read_csv`, normalised to schema 3 rows.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default)]
+pub struct SyncCsvLog {
+    pub schema: u32,
+    pub sensor_map: BTreeMap<u16, String>,  // From the schema line (empty for schema 1)
+    pub rows: Vec<SyncCsvRow>,
+    pub skipped: usize,         // Malformed rows
+}
+
+/// Reads a CSV log of any schema (1: string events, 2: typed, 3: record/consume/residency).
+#[allow(dead_code)]
+pub fn read_csv(path: impl AsRef<Path>) -> io::Result<SyncCsvLog> {
+    let mut input = BufReader::new(File::open(path)?);
+    let mut first = String::new();
+    input.read_line(&mut first)?;
+
+    let mut sensor_map = BTreeMap::new();
+    let schema = match first.strip_prefix('#') {
+        Some(meta) => {
+            for entry in meta.split_whitespace().filter_map(|kv| kv.strip_prefix("sensors=")) {
+                for (id, name) in entry.split(',').filter_map(|e| e.split_once(':')) {
+                    if let Ok(id) = id.parse() {
+                        sensor_map.insert(id, name.to_string());
+                    }
+                }
+            }
+            meta.split_whitespace()
+                .find_map(|kv| kv.strip_prefix("schema="))
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| invalid(format!("unrecognised schema line '{}'", first.trim())))?
+        }
+        None => 1,
+    };
+    if schema > CSV_SCHEMA_VERSION {
+        return Err(invalid(format!("unsupported CSV schema {}", schema)));
+    }
+
+    // Schema 1: the line already read is the column header
+    let header_line = if schema == 1 { first } else { String::new() };
+    let mut rdr = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(io::Cursor::new(header_line).chain(input));
+
+    let mut log = SyncCsvLog { schema, sensor_map, ..SyncCsvLog::default() };
+    match schema {
+        1 => {
+            for record in rdr.records() {
+                let record = record.map_err(|e| invalid(e.to_string()))?;
+                let field = |i: usize| record.get(i).unwrap_or("").trim();
+                match (field(0).parse(), field(1).parse(), field(2).parse()) {
+                    (Ok(seq), Ok(ts), Ok(age)) => log.rows.push(SyncCsvRow::from_v1(seq, ts, age, field(3))),
+                    _ => log.skipped += 1,
+                }
+            }
+        }
+        2 => {
+            for row in rdr.deserialize::<CsvRowV2>() {
+                match row {
+                    Ok(row) => log.rows.push(row.into()),
+                    Err(_) => log.skipped += 1,
+                }
+            }
+        }
+        _ => {
+            for row in rdr.deserialize::<SyncCsvRow>() {
+                match row {
+                    Ok(row) => log.rows.push(row),
+                    Err(_) => log.skipped += 1,
+                }
+            }
+        }
+    }
+    Ok(log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `contents` to a per-test file in the temp directory and returns its path.
+    fn temp_csv(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rts_synclog_{}_{}.csv", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn binary_header_and_records_round_trip() {
+        let header = SyncLogHeader {
+            version: VERSION,
+            anchor_epoch_us: 1_700_000_000_000_000,
+            label: "load_4".to_string(),
+            sensor_map: BTreeMap::from([(1, "Force".to_string()), (2, "Position".to_string())]),
+        };
+        let records = [
+            SyncRecord::new(0, 1_500, LogEventKind::Sample { sensor_id: 2 }, 7),
+            SyncRecord::new(1, -800, LogEventKind::Jitter { sensor_id: 1, jitter_us: 42 }, 0),
+            SyncRecord::new(2, 9_000_000, LogEventKind::ProcMiss, 3),
+            SyncRecord::new(3, 9_000_001, LogEventKind::TxDrop, 0),
+            SyncRecord::new(4, i64::MAX, LogEventKind::RxLatency { latency_us: u64::MAX }, u32::MAX),
+            SyncRecord::new(u64::MAX, 0, LogEventKind::Custom { code: 900 }, 1),
+        ];
+
+        let mut bytes = Vec::new();
+        let mut writer = SyncLogWriter::new(&mut bytes, &header).unwrap();
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        writer.flush().unwrap();
+        drop(writer);
+
+        // magic 8 | version 2 | record_size 2 | anchor 8 | label 2+6 | count 2 | 2 × (id 2 | name 2+n)
+        let header_len = 8 + 2 + 2 + 8 + 2 + 6 + 2 + (2 + 2 + 5) + (2 + 2 + 8);
+        assert_eq!(bytes.len(), header_len + records.len() * RECORD_SIZE);
+
+        let mut reader = SyncLogReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.header.version, VERSION);
+        assert_eq!(reader.header.anchor_epoch_us, header.anchor_epoch_us);
+        assert_eq!(reader.header.label, header.label);
+        assert_eq!(reader.header.sensor_map, header.sensor_map);
+        let decoded: Vec<SyncRecord> = reader.by_ref().collect::<io::Result<_>>().unwrap();
+        assert_eq!(decoded, records);
+    }
+
+    #[test]
+    fn binary_reader_rejects_bad_magic_and_unknown_kinds() {
+        assert!(SyncLogReader::new(&b"NOTASYNCLOG....."[..]).is_err());
+
+        let mut buf = SyncRecord::new(0, 0, LogEventKind::TxDrop, 0).encode();
+        buf[26] = 0xEE;
+        assert!(SyncRecord::decode(&buf).is_err());
+    }
+
+    #[test]
+    fn read_csv_accepts_schema_1() {
+        let path = temp_csv(
+            "schema1",
+            "seq,ts_epoch_us,age_us,event\n\
+             0,1000,10,Force\n\
+             1,1100,5,sensor:4\n\
+             2,1200,20,jitter:12us@sensor:1\n\
+             3,1300,0,rx_latency:42us\n\
+             4,1400,1,custom:900\n\
+             5,1500,2,proc_miss\n\
+             not,a,row,tx_drop\n",
+        );
+        let log = read_csv(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((log.schema, log.skipped), (1, 1));
+        assert!(log.sensor_map.is_empty());
+        let kinds: Vec<RecordKind> = log.rows.iter().map(|r| r.event_kind).collect();
+        assert_eq!(
+            kinds,
+            [RecordKind::Sample, RecordKind::Sample, RecordKind::Jitter, RecordKind::RxLatency, RecordKind::Custom, RecordKind::ProcMiss]
+        );
+        // Named samples carry no sensor id; the record time is consume time minus age
+        assert_eq!((log.rows[0].sensor_id, log.rows[0].record_epoch_us), (None, 990));
+        assert_eq!(log.rows[1].sensor_id, Some(4));
+        assert_eq!((log.rows[2].sensor_id, log.rows[2].value_us), (Some(1), Some(12)));
+        assert_eq!(log.rows[3].value_us, Some(42));
+        assert_eq!(log.rows[4].code, Some(900));
+    }
+
+    #[test]
+    fn read_csv_accepts_schema_2() {
+        let path = temp_csv(
+            "schema2",
+            "# rts_sync_log schema=2 sensors=1:Force,2:Position\n\
+             seq,ts_epoch_us,age_us,event_kind,sensor_id,value_us,code\n\
+             0,5000,30,jitter,2,17,\n\
+             1,5100,0,custom,,,7\n\
+             2,5200,4,bogus,,,\n",
+        );
+        let log = read_csv(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((log.schema, log.skipped, log.rows.len()), (2, 1, 2));
+        assert_eq!(log.sensor_map.get(&2).map(String::as_str), Some("Position"));
+        let jitter = &log.rows[0];
+        assert_eq!((jitter.record_epoch_us, jitter.consume_epoch_us, jitter.residency_us), (4970, 5000, 30));
+        assert_eq!((jitter.event_kind, jitter.sensor_id, jitter.value_us, jitter.code), (RecordKind::Jitter, Some(2), Some(17), None));
+        assert_eq!((log.rows[1].event_kind, log.rows[1].code), (RecordKind::Custom, Some(7)));
+    }
+
+    #[test]
+    fn read_csv_reads_back_schema_3_rows() {
+        let sensor_map = BTreeMap::from([(1, "Force".to_string())]);
+        let rows = [
+            SyncCsvRow::from_record(&SyncRecord::new(0, 2_000, LogEventKind::Sample { sensor_id: 1 }, 3), 10_000),
+            SyncCsvRow::from_record(&SyncRecord::new(1, 4_000, LogEventKind::RxLatency { latency_us: 55 }, 0), 10_000),
+            SyncCsvRow::from_record(&SyncRecord::new(2, 6_000, LogEventKind::TxDrop, 1), 10_000),
+        ];
+        let mut contents = format!("{}\n{}\n", csv_schema_line(&sensor_map), CSV_COLUMNS).into_bytes();
+        for row in &rows {
+            row.write_csv(&mut contents).unwrap();
+        }
+        let path = temp_csv("schema3", std::str::from_utf8(&contents).unwrap());
+        let log = read_csv(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((log.schema, log.skipped), (CSV_SCHEMA_VERSION, 0));
+        assert_eq!(log.sensor_map, sensor_map);
+        assert_eq!(log.rows, rows);
+        assert_eq!((log.rows[0].record_epoch_us, log.rows[0].consume_epoch_us), (10_002, 10_005));
+    }
+
+    #[test]
+    fn read_csv_rejects_newer_schemas() {
+        let path = temp_csv("schema9", "# rts_sync_log schema=9\nseq\n");
+        let result = read_csv(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,575 @@
+//! actuator_fault.rs
+//! Actuator fault injection and feedback-based fault detection.
+//!
+//! - Injection (`[[faults.scenario]]`): an actuator (by its routing name) gets stuck, loses gain,
+//!   lags, intermittently ignores its command or inverts it for a time window of the run. The
+//!   injector sits after the actuator model (`actuator_model.rs`): the controller's plant and
+//!   `ActuatorState` feedback see the faulty state, while `CommandedState` feedback carries what
+//!   a healthy actuator would have reached. Scenarios are allocated at startup; `apply` is
+//!   allocation-free (RT thread).
+//! - Detection: a thread pairs each actuator's `CommandedState` with the `ActuatorState` that
+//!   follows it on the bus `feedback` topic, and classifies the mismatches over a sliding window
+//!   (inverted, proportional, held, trailing); a new verdict is published as typed
+//!   `ActuatorFault` feedback.
+//! - Scoring per scenario: detection latency from the injection, the fault it was classified as,
+//!   and false positives (verdicts on an actuator with no fault) per healthy check, apart for
+//!   the time outside every scenario (`baseline`). Exported to the run summary and
+//!   `fault_detection_all.csv`.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::component_b::feedback::{Feedback, FeedbackKind, FeedbackLoop};
+use crate::utils::bus::Subscription;
+use crate::utils::metrics::{EventRecorder, SharedMetrics};
+
+const DETECTOR_TICK_MS: u64 = 10;
+const PUBLISH_TICKS: u32 = 50;      // Scores to the run metrics every 500 ms (and at the end)
+const MAX_WINDOW: usize = 256;
+
+/// Injected fault and its parameters as written in a scenario (tagged by `kind`).
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FaultSpec {
+    Stuck,                          // Output frozen at its value when the fault starts
+    ReducedGain { gain: f64 },      // Output = gain × healthy output
+    Lag { lag_s: f64 },             // Extra first-order lag (s) on top of the actuator model
+    Intermittent { drop_rate: f64 },// Fraction of commands ignored (output holds)
+    Inversion,                      // Output = −healthy output
+}
+
+impl FaultSpec {
+    pub fn fault(&self) -> ActuatorFault {
+        match self {
+            FaultSpec::Stuck => ActuatorFault::Stuck,
+            FaultSpec::ReducedGain { .. } => ActuatorFault::ReducedGain,
+            FaultSpec::Lag { .. } => ActuatorFault::Lag,
+            FaultSpec::Intermittent { .. } => ActuatorFault::Intermittent,
+            FaultSpec::Inversion => ActuatorFault::Inversion,
+        }
+    }
+}
+
+/// Fault kind, injected or detected (typed `ActuatorFault` feedback).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActuatorFault {
+    Stuck,
+    ReducedGain,
+    Lag,
+    Intermittent,
+    Inversion,
+    Deviation,                      // Detected only: mismatch without a recognised pattern
+}
+
+impl ActuatorFault {
+    pub fn name(self) -> &'static str {
+        match self {
+            ActuatorFault::Stuck => "stuck",
+            ActuatorFault::ReducedGain => "reduced_gain",
+            ActuatorFault::Lag => "lag",
+            ActuatorFault::Intermittent => "intermittent",
+            ActuatorFault::Inversion => "inversion",
+            ActuatorFault::Deviation => "deviation",
+        }
+    }
+}
+
+/// `[[faults.scenario]]`: one fault on one actuator for a time window of the run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FaultScenario {
+    pub name: String,
+    pub actuator: String,           // Routed actuator name (case-insensitive)
+    #[serde(flatten)]
+    pub fault: FaultSpec,
+    pub start_s: f64,               // Since the pipeline started (every sweep level restarts at 0)
+    pub duration_s: f64,
+}
+
+impl FaultScenario {
+    fn end_s(&self) -> f64 {
+        self.start_s + self.duration_s
+    }
+
+    /// Index of the targeted actuator in `actuators` (routing table order).
+    fn target(&self, actuators: &[&'static str]) -> Option<usize> {
+        actuators.iter().position(|name| name.eq_ignore_ascii_case(&self.actuator))
+    }
+}
+
+/// `[faults]`: injected scenarios and the detector's thresholds.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FaultsConfig {
+    pub detector: bool,             // Run the detector (actuators then report `CommandedState`)
+    pub window: usize,              // Command / response pairs per verdict
+    pub tolerance: f64,             // A response within tolerance + relative_tolerance × |command| matches
+    pub relative_tolerance: f64,
+    pub min_mismatch: f64,          // Fraction of mismatched pairs in the window that raises a verdict
+    pub grace_s: f64,               // After a scenario ends, verdicts still count as its detection
+    pub scenario: Vec<FaultScenario>,
+}
+
+impl Default for FaultsConfig {
+    fn default() -> Self {
+        Self {
+            detector: true,
+            window: 20,
+            tolerance: 0.01,
+            relative_tolerance: 0.02,
+            min_mismatch: 0.2,
+            grace_s: 0.5,
+            scenario: Vec::new(),
+        }
+    }
+}
+
+impl FaultsConfig {
+    /// Injector per actuator (routing table order); None where no scenario targets it.
+    /// Scenarios naming no routed actuator are reported and ignored.
+    pub fn build(&self, origin: Instant, actuators: &[&'static str]) -> Vec<Option<FaultInjector>> {
+        for s in self.scenario.iter().filter(|s| s.target(actuators).is_none()) {
+            warn!("[Faults] Scenario {} targets unknown actuator {:?}; ignored", s.name, s.actuator);
+        }
+        (0..actuators.len()).map(|target| {
+            let faults: Vec<_> = self
+                .scenario
+                .iter()
+                .filter(|s| s.target(actuators) == Some(target))
+                .map(|s| (s.start_s, s.end_s(), s.fault))
+                .collect();
+            (!faults.is_empty()).then_some(FaultInjector {
+                origin,
+                faults,
+                active: None,
+                held: 0.0,
+                output: 0.0,
+                last: None,
+                rng: 0x9E37_79B9_7F4A_7C15 ^ target as u64,
+            })
+        })
+        .collect()
+    }
+}
+
+/// Applies the scenarios of one actuator to its healthy output; owned by its controller.
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    origin: Instant,
+    faults: Vec<(f64, f64, FaultSpec)>, // `(start_s, end_s, fault)`
+    active: Option<usize>,
+    held: f64,                      // Output when the active fault started
+    output: f64,                    // Last faulty output
+    last: Option<Instant>,          // Previous `apply`
+    rng: u64,                       // xorshift state (intermittent drops, reproducible)
+}
+
+impl FaultInjector {
+    /// Output of the actuator at `now` when a healthy one would reach `healthy`.
+    pub fn apply(&mut self, healthy: f64, now: Instant) -> f64 {
+        let dt = self.last.map_or(0.0, |last| now.saturating_duration_since(last).as_secs_f64());
+        self.last = Some(now);
+        let t_s = now.saturating_duration_since(self.origin).as_secs_f64();
+
+        let current = self.faults.iter().position(|&(start, end, _)| t_s >= start && t_s < end);
+        if current != self.active {
+            self.active = current;
+            self.held = self.output;
+        }
+
+        self.output = match current.map(|i| self.faults[i].2) {
+            None => healthy,
+            Some(FaultSpec::Stuck) => self.held,
+            Some(FaultSpec::ReducedGain { gain }) => healthy * gain,
+            Some(FaultSpec::Lag { lag_s }) => self.output + (healthy - self.output) * dt / (lag_s.max(0.0) + dt).max(1e-9),
+            Some(FaultSpec::Intermittent { drop_rate }) => {
+                if self.next_unit() < drop_rate { self.output } else { healthy }
+            }
+            Some(FaultSpec::Inversion) => -healthy,
+        };
+        self.output
+    }
+
+    /// Uniform in [0, 1) (xorshift64).
+    fn next_unit(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Detection outcome of one scenario (or of the fault-free `baseline`).
+#[derive(Debug, Clone, Default)]
+pub struct FaultScore {
+    pub scenario: String,
+    pub actuator: &'static str,     // `-` for the baseline
+    pub injected: Option<ActuatorFault>,
+    pub start_s: f64,
+    pub end_s: f64,
+    pub detected_at_s: Option<f64>, // First verdict on the faulty actuator
+    pub detected_as: Option<ActuatorFault>,
+    pub false_positives: u64,       // Verdicts on healthy actuators while this scenario ran
+    pub healthy_checks: u64,        // Command / response pairs checked on healthy actuators
+}
+
+impl FaultScore {
+    /// Injection → first verdict.
+    pub fn latency_s(&self) -> Option<f64> {
+        self.detected_at_s.map(|t| t - self.start_s)
+    }
+
+    /// False verdicts per healthy check.
+    pub fn false_positive_rate(&self) -> f64 {
+        if self.healthy_checks == 0 { 0.0 } else { self.false_positives as f64 / self.healthy_checks as f64 }
+    }
+}
+
+/// One command / response pair.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    commanded: f64,                 // Healthy actuator's state
+    observed: f64,
+    previous: f64,                  // Observed state of the pair before
+}
+
+/// Sliding window and verdict of one actuator.
+struct Channel {
+    pending: Option<f64>,           // `CommandedState` waiting for its `ActuatorState`
+    window: VecDeque<Sample>,
+    previous: Option<f64>,
+    verdict: Option<ActuatorFault>,
+}
+
+/// Classifies the mismatches of one window.
+struct Classifier {
+    window: usize,
+    tolerance: f64,
+    relative_tolerance: f64,
+    min_mismatch: f64,
+}
+
+impl Classifier {
+    fn matches(&self, s: &Sample) -> bool {
+        (s.observed - s.commanded).abs() <= self.tolerance + self.relative_tolerance * s.commanded.abs()
+    }
+
+    /// None while the window matches (or is not full yet).
+    fn classify(&self, window: &VecDeque<Sample>) -> Option<ActuatorFault> {
+        if window.len() < self.window {
+            return None;
+        }
+        let bad = || window.iter().filter(|s| !self.matches(s));
+        let mismatched = bad().count();
+        if (mismatched as f64) < self.min_mismatch * window.len() as f64 {
+            return None;
+        }
+        let share = |n: usize| n as f64 / mismatched as f64;
+        let tol = |s: &Sample| self.tolerance + self.relative_tolerance * s.commanded.abs();
+
+        // Inversion: the response mirrors the command
+        let inverted = bad().filter(|s| s.commanded.abs() > tol(s) && (s.observed + s.commanded).abs() <= tol(s)).count();
+        if share(inverted) >= 0.8 {
+            return Some(ActuatorFault::Inversion);
+        }
+
+        // Held output: stuck if the observed state has not moved since the first mismatch (a
+        // command passing the held value matches without ending the fault), intermittent if it
+        // still follows the command between held pairs. Checked before the gain: a held output
+        // under a slowly moving command has a nearly constant ratio
+        let held = bad().filter(|s| (s.observed - s.previous).abs() <= 1e-9).count();
+        if share(held) >= 0.8 {
+            let onset = window.iter().position(|s| !self.matches(s)).unwrap_or(0);
+            let frozen = window[onset].previous;
+            let stuck = window.iter().skip(onset).all(|s| (s.observed - frozen).abs() <= 1e-9);
+            return Some(if stuck { ActuatorFault::Stuck } else { ActuatorFault::Intermittent });
+        }
+
+        // Reduced gain: a constant ratio below 1 while the command moves
+        let (lo, hi) = bad().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), s| (lo.min(s.commanded), hi.max(s.commanded)));
+        let ratios = || bad().filter(|s| s.commanded.abs() > tol(s)).map(|s| s.observed / s.commanded);
+        let n = ratios().count();
+        if hi - lo > 10.0 * self.tolerance && n > 0 && n >= mismatched / 2 {
+            let mean = ratios().sum::<f64>() / n as f64;
+            let std = (ratios().map(|r| (r - mean).powi(2)).sum::<f64>() / n as f64).sqrt();
+            if mean > 0.0 && mean < 1.0 - self.relative_tolerance && std <= 0.05 * mean {
+                return Some(ActuatorFault::ReducedGain);
+            }
+        }
+
+        // Lag: the response moves toward the command without reaching it
+        let trailing = bad()
+            .filter(|s| {
+                (s.observed - s.previous) * (s.commanded - s.previous) > 0.0
+                    && (s.commanded - s.observed).abs() < (s.commanded - s.previous).abs()
+            })
+            .count();
+        if share(trailing) >= 0.6 {
+            return Some(ActuatorFault::Lag);
+        }
+        Some(ActuatorFault::Deviation)
+    }
+}
+
+/// Scores verdicts against the scenarios (with their target index); index 0 is the baseline.
+struct Scoreboard {
+    scenarios: Vec<(usize, FaultScenario)>,
+    scores: Vec<FaultScore>,
+    grace_s: f64,
+}
+
+impl Scoreboard {
+    /// Scenarios naming no routed actuator are left out (never injected).
+    fn new(scenarios: Vec<FaultScenario>, actuators: &[&'static str], grace_s: f64) -> Self {
+        let scenarios: Vec<_> = scenarios.into_iter().filter_map(|s| Some((s.target(actuators)?, s))).collect();
+        let baseline = FaultScore { scenario: "baseline".into(), actuator: "-", ..FaultScore::default() };
+        let scores = std::iter::once(baseline)
+            .chain(scenarios.iter().map(|(target, s)| FaultScore {
+                scenario: s.name.clone(),
+                actuator: actuators[*target],
+                injected: Some(s.fault.fault()),
+                start_s: s.start_s,
+                end_s: s.end_s(),
+                ..FaultScore::default()
+            }))
+            .collect();
+        Self { scenarios, scores, grace_s }
+    }
+
+    fn running(&self, s: &FaultScenario, t_s: f64) -> bool {
+        t_s >= s.start_s && t_s < s.end_s() + self.grace_s
+    }
+
+    /// Score index of the scenario faulting actuator `target` at `t_s`.
+    fn faulting(&self, target: usize, t_s: f64) -> Option<usize> {
+        self.scenarios.iter().position(|(t, s)| *t == target && self.running(s, t_s)).map(|i| i + 1)
+    }
+
+    /// Score index the healthy-actuator checks at `t_s` count for (baseline outside every scenario).
+    fn period(&self, t_s: f64) -> usize {
+        self.scenarios.iter().position(|(_, s)| self.running(s, t_s)).map_or(0, |i| i + 1)
+    }
+
+    fn check(&mut self, target: usize, t_s: f64) {
+        if self.faulting(target, t_s).is_none() {
+            let i = self.period(t_s);
+            self.scores[i].healthy_checks += 1;
+        }
+    }
+
+    /// Records a new verdict; returns the scenario it detected, if any.
+    fn verdict(&mut self, target: usize, fault: ActuatorFault, t_s: f64) -> Option<&FaultScore> {
+        match self.faulting(target, t_s) {
+            Some(i) => {
+                let score = &mut self.scores[i];
+                if score.detected_at_s.is_some() {
+                    return None;
+                }
+                score.detected_at_s = Some(t_s);
+                score.detected_as = Some(fault);
+                Some(&self.scores[i])
+            }
+            None => {
+                let i = self.period(t_s);
+                self.scores[i].false_positives += 1;
+                None
+            }
+        }
+    }
+}
+
+/// Detector thread: pairs `CommandedState` / `ActuatorState` feedback per routed actuator
+/// (`actuators`, routing table order), publishes `ActuatorFault` feedback on each new verdict and
+/// the scores to the run metrics, until `running` clears.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_fault_detector(
+    config: FaultsConfig,
+    origin: Instant,
+    actuators: Vec<&'static str>,
+    feedback_rx: Subscription<Feedback>,
+    feedback: FeedbackLoop,
+    metrics: SharedMetrics,
+    event_recorder: Arc<EventRecorder>,
+    running: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    thread::Builder::new()
+        .name("fault_detector".into())
+        .spawn(move || {
+            event_recorder.attach_thread();
+            let window = config.window.clamp(2, MAX_WINDOW);
+            let classifier = Classifier {
+                window,
+                tolerance: config.tolerance,
+                relative_tolerance: config.relative_tolerance,
+                min_mismatch: config.min_mismatch,
+            };
+            let mut channels: Vec<Channel> = actuators
+                .iter()
+                .map(|_| Channel {
+                    pending: None,
+                    window: VecDeque::with_capacity(window + 1),
+                    previous: None,
+                    verdict: None,
+                })
+                .collect();
+            let mut board = Scoreboard::new(config.scenario, &actuators, config.grace_s);
+            metrics.lock_run().fault_detection = board.scores.clone();
+            let mut ticks = 0u32;
+
+            while running.load(Ordering::Acquire) {
+                thread::sleep(Duration::from_millis(DETECTOR_TICK_MS));
+
+                while let Ok(msg) = feedback_rx.try_recv() {
+                    let Some(target) = actuators.iter().position(|&name| name == msg.actuator) else { continue };
+                    let channel = &mut channels[target];
+                    let (commanded, observed) = match msg.kind {
+                        FeedbackKind::CommandedState(value) => {
+                            channel.pending = Some(value);
+                            continue;
+                        }
+                        FeedbackKind::ActuatorState(value) => match channel.pending.take() {
+                            Some(commanded) => (commanded, value),
+                            None => continue,
+                        },
+                        _ => continue,
+                    };
+
+                    let t_s = msg.timestamp.saturating_duration_since(origin).as_secs_f64();
+                    let previous = channel.previous.replace(observed).unwrap_or(observed);
+                    channel.window.push_back(Sample { commanded, observed, previous });
+                    if channel.window.len() > window {
+                        channel.window.pop_front();
+                    }
+                    board.check(target, t_s);
+
+                    let verdict = classifier.classify(&channel.window);
+                    if verdict == channel.verdict {
+                        continue;
+                    }
+                    channel.verdict = verdict;
+                    let Some(fault) = verdict else { continue };
+
+                    feedback.emit(actuators[target], FeedbackKind::ActuatorFault(fault), Instant::now());
+                    if let Some(score) = board.verdict(target, fault, t_s) {
+                        info!(
+                            "[FaultDetector] {} {} detected as {} at {:.3} s ({}, latency {:.0} ms)",
+                            score.actuator,
+                            score.injected.map_or("-", ActuatorFault::name),
+                            fault.name(),
+                            t_s,
+                            score.scenario,
+                            score.latency_s().unwrap_or(0.0) * 1e3
+                        );
+                    }
+                }
+
+                ticks += 1;
+                if ticks.is_multiple_of(PUBLISH_TICKS) {
+                    metrics.lock_run().fault_detection = board.scores.clone();
+                }
+            }
+            metrics.lock_run().fault_detection = board.scores;
+        })
+        .expect("Failed to spawn fault detector")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classifier() -> Classifier {
+        Classifier { window: 20, tolerance: 0.01, relative_tolerance: 0.02, min_mismatch: 0.2 }
+    }
+
+    /// Window of `(commanded, observed)` pairs; `previous` is the observed state of the pair before.
+    fn window(pairs: impl IntoIterator<Item = (f64, f64)>) -> VecDeque<Sample> {
+        let mut previous = None;
+        pairs
+            .into_iter()
+            .map(|(commanded, observed)| {
+                let previous = previous.replace(observed).unwrap_or(observed);
+                Sample { commanded, observed, previous }
+            })
+            .collect()
+    }
+
+    /// Ramp command 10, 11, ... with `fault` applied to the response from sample 10 on.
+    fn ramp_with(fault: impl Fn(f64) -> f64) -> VecDeque<Sample> {
+        window((0..20).map(|i| {
+            let commanded = 10.0 + i as f64;
+            (commanded, if i < 10 { commanded } else { fault(commanded) })
+        }))
+    }
+
+    #[test]
+    fn healthy_or_partial_window_has_no_verdict() {
+        let c = classifier();
+        assert_eq!(c.classify(&ramp_with(|commanded| commanded)), None);
+        assert_eq!(c.classify(&window((0..10).map(|i| (i as f64, -(i as f64))))), None);
+    }
+
+    #[test]
+    fn frozen_output_is_stuck() {
+        assert_eq!(classifier().classify(&ramp_with(|_| 19.0)), Some(ActuatorFault::Stuck));
+    }
+
+    #[test]
+    fn frozen_output_stays_stuck_when_the_command_passes_it() {
+        // Command rises past the held value (17) and falls back through it: the crossing pair
+        // matches, so the mismatches are not one trailing run
+        let commands = [10., 11., 12., 13., 14., 15., 16., 17., 18., 19., 20., 19., 18., 17., 16., 15., 14., 13., 12., 11.];
+        let w = window(commands.iter().enumerate().map(|(i, &c)| (c, if i < 8 { c } else { 17.0 })));
+        let trailing = w.iter().rev().take_while(|s| !classifier().matches(s)).count();
+        let mismatched = w.iter().filter(|s| !classifier().matches(s)).count();
+        assert!(trailing < mismatched);
+        assert_eq!(classifier().classify(&w), Some(ActuatorFault::Stuck));
+    }
+
+    #[test]
+    fn dropped_commands_are_intermittent() {
+        // Every dropped command holds the previous state; the others are followed
+        let dropped = [5, 6, 10, 14, 15, 18];
+        let mut observed = 0.0;
+        let w = window((0..20).map(|i| {
+            let commanded = 5.0 * i as f64;
+            if !dropped.contains(&i) {
+                observed = commanded;
+            }
+            (commanded, observed)
+        }));
+        assert_eq!(classifier().classify(&w), Some(ActuatorFault::Intermittent));
+    }
+
+    #[test]
+    fn constant_ratio_is_reduced_gain() {
+        assert_eq!(classifier().classify(&ramp_with(|commanded| 0.5 * commanded)), Some(ActuatorFault::ReducedGain));
+    }
+
+    #[test]
+    fn mirrored_output_is_inversion() {
+        assert_eq!(classifier().classify(&ramp_with(|commanded| -commanded)), Some(ActuatorFault::Inversion));
+    }
+
+    #[test]
+    fn slow_approach_is_lag() {
+        // Command steps 10 → 20; the response closes 30 % of the gap per sample
+        let mut observed = 10.0;
+        let w = window((0..20).map(|i| {
+            let commanded = if i < 10 { 10.0 } else { 20.0 };
+            observed += 0.3 * (commanded - observed);
+            (commanded, observed)
+        }));
+        assert_eq!(classifier().classify(&w), Some(ActuatorFault::Lag));
+    }
+
+    #[test]
+    fn offset_output_is_a_deviation() {
+        assert_eq!(classifier().classify(&ramp_with(|commanded| commanded + 5.0)), Some(ActuatorFault::Deviation));
+    }
+}
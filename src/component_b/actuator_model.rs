@@ -0,0 +1,264 @@
+//! actuator_model.rs
+//! Actuator dynamics between the controller's command and the state it actually reaches.
+//!
+//! - Stages, in order: transport delay → saturation (position / force limits) → dead band →
+//!   slew-rate limit → first-order lag → backlash. Every stage is off at its default, so an
+//!   unconfigured actuator follows its command exactly (the former behaviour).
+//! - Advanced at each control instant (`apply`) over the time since the previous one; the
+//!   delay line is a fixed ring allocated when the model is built, so `apply` is allocation-free
+//!   and runs on the actuator thread.
+//! - `saturated()` / `rate_limited()` tell whether the last command hit a limit; the controller
+//!   reports the onsets as feedback and hands the limited command back to its control law
+//!   (`ControlLaw::actuator_limited`, anti-windup).
+
+use std::time::Instant;
+
+use serde::Deserialize;
+
+const DELAY_SLOTS: usize = 1024;    // Delayed commands in flight (≥ 5 s at the 5 ms sensor period)
+
+/// `[actuator.<actuator>]`: dynamics of one actuator (missing keys: stage off).
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ActuatorModelConfig {
+    pub min: f64,           // Saturation: lowest reachable position / force (-inf = none)
+    pub max: f64,           // Highest reachable position / force (inf = none)
+    pub rate_limit: f64,    // Slew-rate limit (units/s); 0 = none
+    pub dead_band: f64,     // Command changes smaller than this do not move the actuator
+    pub backlash: f64,      // Total play: the output follows once a reversal takes it up
+    pub lag_s: f64,         // First-order lag time constant (s); 0 = none
+    pub delay_s: f64,       // Transport delay (s); 0 = none
+}
+
+impl Default for ActuatorModelConfig {
+    fn default() -> Self {
+        Self {
+            min: f64::NEG_INFINITY,
+            max: f64::INFINITY,
+            rate_limit: 0.0,
+            dead_band: 0.0,
+            backlash: 0.0,
+            lag_s: 0.0,
+            delay_s: 0.0,
+        }
+    }
+}
+
+impl ActuatorModelConfig {
+    /// No stage configured: the actuator needs no model.
+    pub fn is_ideal(&self) -> bool {
+        self.min == f64::NEG_INFINITY
+            && self.max == f64::INFINITY
+            && self.rate_limit <= 0.0
+            && self.dead_band <= 0.0
+            && self.backlash <= 0.0
+            && self.lag_s <= 0.0
+            && self.delay_s <= 0.0
+    }
+
+    /// Builds the model at rest at 0 (clamped into the limits).
+    pub fn build(&self) -> ActuatorModel {
+        let start = 0.0_f64.clamp(self.min.min(self.max), self.max.max(self.min));
+        ActuatorModel {
+            config: *self,
+            delay: (self.delay_s > 0.0).then(|| DelayLine::new(start)),
+            origin: Instant::now(),
+            last: None,
+            slewed: start,
+            lagged: start,
+            output: start,
+            command: start,
+            excess: 0.0,
+            saturated: false,
+            rate_limited: false,
+        }
+    }
+}
+
+/// Commands in flight, oldest first; when full the oldest is delivered early.
+#[derive(Debug, Clone)]
+struct DelayLine {
+    slots: Box<[(f64, f64)]>,       // `(due_s, command)` ring
+    head: usize,
+    len: usize,
+    current: f64,                   // Last delivered command
+}
+
+impl DelayLine {
+    fn new(start: f64) -> Self {
+        Self { slots: vec![(0.0, 0.0); DELAY_SLOTS].into_boxed_slice(), head: 0, len: 0, current: start }
+    }
+
+    fn push(&mut self, due_s: f64, command: f64) {
+        if self.len == self.slots.len() {
+            self.current = self.slots[self.head].1;
+            self.head = (self.head + 1) % self.slots.len();
+            self.len -= 1;
+        }
+        let tail = (self.head + self.len) % self.slots.len();
+        self.slots[tail] = (due_s, command);
+        self.len += 1;
+    }
+
+    /// Latest command due at `t_s`.
+    fn read(&mut self, t_s: f64) -> f64 {
+        while self.len > 0 && self.slots[self.head].0 <= t_s {
+            self.current = self.slots[self.head].1;
+            self.head = (self.head + 1) % self.slots.len();
+            self.len -= 1;
+        }
+        self.current
+    }
+}
+
+/// State of one actuator's dynamics; owned by its controller.
+#[derive(Debug, Clone)]
+pub struct ActuatorModel {
+    config: ActuatorModelConfig,
+    delay: Option<DelayLine>,
+    origin: Instant,                // Time base of the delay line
+    last: Option<Instant>,          // Previous `apply`
+    slewed: f64,                    // After dead band and rate limit
+    lagged: f64,                    // After the lag (input side of the backlash)
+    output: f64,                    // Reached state
+    command: f64,                   // Last command after the delay
+    excess: f64,                    // Change saturation and rate limit made to it
+    saturated: bool,
+    rate_limited: bool,
+}
+
+impl ActuatorModel {
+    /// Advances the dynamics to `now` toward `command`; returns the state reached.
+    pub fn apply(&mut self, command: f64, now: Instant) -> f64 {
+        let c = &self.config;
+        let dt = self.last.map_or(0.0, |last| now.saturating_duration_since(last).as_secs_f64());
+        self.last = Some(now);
+
+        // Transport delay
+        let command = match &mut self.delay {
+            Some(delay) => {
+                let t_s = now.saturating_duration_since(self.origin).as_secs_f64();
+                delay.push(t_s + c.delay_s, command);
+                delay.read(t_s)
+            }
+            None => command,
+        };
+        self.command = command;
+
+        // Saturation
+        let clamped = command.max(c.min).min(c.max);
+        self.saturated = clamped != command;
+
+        // Dead band: small corrections do not overcome static friction
+        let target = if (clamped - self.slewed).abs() <= c.dead_band { self.slewed } else { clamped };
+
+        // Slew-rate limit
+        let max_step = c.rate_limit * dt;
+        self.rate_limited = c.rate_limit > 0.0 && (target - self.slewed).abs() > max_step;
+        self.slewed = if self.rate_limited {
+            self.slewed + (target - self.slewed).clamp(-max_step, max_step)
+        } else {
+            target
+        };
+        self.excess = if self.rate_limited { self.slewed } else { clamped } - command;
+
+        // First-order lag
+        self.lagged = if c.lag_s > 0.0 {
+            self.lagged + (self.slewed - self.lagged) * dt / (c.lag_s + dt)
+        } else {
+            self.slewed
+        };
+
+        // Backlash: the output moves only once the play is taken up
+        let half = c.backlash / 2.0;
+        if self.lagged - self.output > half {
+            self.output = self.lagged - half;
+        } else if self.output - self.lagged > half {
+            self.output = self.lagged + half;
+        }
+        self.output
+    }
+
+    /// Last command to reach the actuator (after the transport delay).
+    #[inline]
+    pub fn command(&self) -> f64 {
+        self.command
+    }
+
+    /// Change saturation and rate limit made to `command()`: the actuator followed
+    /// `command() + excess()`.
+    #[inline]
+    pub fn excess(&self) -> f64 {
+        self.excess
+    }
+
+    /// The last command lay outside `[min, max]`.
+    #[inline]
+    pub fn saturated(&self) -> bool {
+        self.saturated
+    }
+
+    /// The last command asked for a faster change than `rate_limit`.
+    #[inline]
+    pub fn rate_limited(&self) -> bool {
+        self.rate_limited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn delay_line_delivers_commands_when_due() {
+        let mut delay = DelayLine::new(0.0);
+        delay.push(0.02, 1.0);
+        delay.push(0.03, 2.0);
+        assert_eq!(delay.read(0.01), 0.0);      // Nothing due: the start value
+        assert_eq!(delay.read(0.02), 1.0);
+        assert_eq!(delay.read(0.05), 2.0);
+        assert_eq!(delay.read(1.0), 2.0);       // Held once drained
+    }
+
+    #[test]
+    fn delay_line_overflow_delivers_oldest_early() {
+        let mut delay = DelayLine::new(-1.0);
+        for i in 0..DELAY_SLOTS {
+            delay.push(100.0 + i as f64, i as f64);
+        }
+        assert_eq!(delay.read(0.0), -1.0);
+        assert_eq!(delay.len, DELAY_SLOTS);
+
+        // Full: each push delivers the oldest command in flight
+        delay.push(2000.0, 1e6);
+        assert_eq!(delay.len, DELAY_SLOTS);
+        assert_eq!(delay.read(0.0), 0.0);
+        delay.push(2001.0, 2e6);
+        assert_eq!(delay.read(0.0), 1.0);
+
+        // The ring wrapped: the rest still come out in order
+        assert_eq!(delay.read(100.0 + (DELAY_SLOTS - 1) as f64), (DELAY_SLOTS - 1) as f64);
+        assert_eq!(delay.read(2000.5), 1e6);
+        assert_eq!(delay.read(f64::INFINITY), 2e6);
+        assert_eq!(delay.len, 0);
+    }
+
+    #[test]
+    fn saturation_and_rate_limit_report_excess() {
+        let config = ActuatorModelConfig { min: -10.0, max: 10.0, rate_limit: 100.0, ..ActuatorModelConfig::default() };
+        let mut model = config.build();
+        let t0 = Instant::now();
+        model.apply(0.0, t0);
+
+        // 10 ms at 100 units/s: at most 1 unit toward the clamped command
+        let reached = model.apply(50.0, t0 + Duration::from_millis(10));
+        assert!(model.saturated() && model.rate_limited());
+        assert!((reached - 1.0).abs() < 1e-9);
+        assert!((model.command() + model.excess() - reached).abs() < 1e-9);
+
+        let reached = model.apply(5.0, t0 + Duration::from_millis(1000));
+        assert!(!model.saturated() && !model.rate_limited());
+        assert_eq!(reached, 5.0);
+    }
+}
@@ -0,0 +1,465 @@
+//! autotune.rs
+//! Relay-feedback PID auto-tuning (Åström-Hägglund), run by the controller against its plant.
+//!
+//! - Settling: the configured law holds the loop for `settle_s`; its mean output becomes the
+//!   relay bias u0 (the static input the plant needs near the setpoint).
+//! - Relay: u = u0 ± d, switching when the error leaves ±`hysteresis`. The loop falls into a
+//!   limit cycle; the bias is re-centred every cycle on the mean output so the oscillation stays
+//!   symmetric, and nudged by d/2 when the relay sticks on one side.
+//! - Identification: once `cycles` consecutive periods agree (after the first, settling cycles),
+//!   Pu = mean period and a = mean half peak-to-peak; the describing function of a relay with
+//!   hysteresis ε gives Ku = 4d / (π·√(a² − ε²)).
+//! - A tuning rule maps (Ku, Pu) to PID gains; the controller applies them or only exports them.
+//!
+//! Allocation-free: the experiment runs on the RT threads, between packets.
+
+use serde::Deserialize;
+
+use crate::component_b::control_law::LawConfig;
+
+const SETTLING_CYCLES: usize = 2;   // Limit-cycle periods discarded before measuring
+const MAX_CYCLES: usize = 8;        // Measured periods kept (`cycles` is capped to this)
+const BIAS_TAU_S: f64 = 0.5;        // Averaging time constant of the law output while settling
+const STUCK_S: f64 = 2.0;           // Relay on one side this long: move the bias by d/2
+
+/// Ziegler-Nichols-type rules from the ultimate point (Ku, Pu).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TuningRule {
+    ZieglerNichols,     // Kp 0.6·Ku, Ti Pu/2, Td Pu/8 (quarter decay, aggressive)
+    TyreusLuyben,       // Kp Ku/2.2, Ti 2.2·Pu, Td Pu/6.3 (robust, slow integral)
+    SomeOvershoot,      // Kp Ku/3, Ti Pu/2, Td Pu/3
+    NoOvershoot,        // Kp 0.2·Ku, Ti Pu/2, Td Pu/3
+}
+
+impl TuningRule {
+    pub const ALL: [TuningRule; 4] = [
+        TuningRule::ZieglerNichols,
+        TuningRule::TyreusLuyben,
+        TuningRule::SomeOvershoot,
+        TuningRule::NoOvershoot,
+    ];
+
+    /// Parallel-form gains `[kp, ki, kd]` (Ki = Kp/Ti, Kd = Kp·Td).
+    pub fn gains(self, ultimate_gain: f64, ultimate_period_s: f64) -> [f64; 3] {
+        let (ku, pu) = (ultimate_gain, ultimate_period_s);
+        let (kp, ti, td) = match self {
+            TuningRule::ZieglerNichols => (0.6 * ku, pu / 2.0, pu / 8.0),
+            TuningRule::TyreusLuyben => (ku / 2.2, 2.2 * pu, pu / 6.3),
+            TuningRule::SomeOvershoot => (ku / 3.0, pu / 2.0, pu / 3.0),
+            TuningRule::NoOvershoot => (0.2 * ku, pu / 2.0, pu / 3.0),
+        };
+        [kp, kp / ti, kp * td]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TuningRule::ZieglerNichols => "ziegler_nichols",
+            TuningRule::TyreusLuyben => "tyreus_luyben",
+            TuningRule::SomeOvershoot => "some_overshoot",
+            TuningRule::NoOvershoot => "no_overshoot",
+        }
+    }
+}
+
+/// `[control.<controller>.autotune]`: relay experiment settings.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct AutotuneConfig {
+    pub enabled: bool,      // Run when the controller starts (every sweep level tunes afresh)
+    pub rule: TuningRule,
+    pub apply: bool,        // Switch to the identified gains; false = export them only
+    pub amplitude: f64,     // Relay half-swing d (command units)
+    pub hysteresis: f64,    // Error band ±ε; keep it above the filtered measurement noise
+    pub settle_s: f64,      // Configured law runs this long first (sets the relay bias)
+    pub cycles: usize,      // Consecutive periods that must agree (≤ 8)
+    pub tolerance: f64,     // Relative spread of those periods, and max on/off asymmetry
+    pub timeout_s: f64,     // Give up (keep the configured gains) after this long
+}
+
+impl Default for AutotuneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rule: TuningRule::ZieglerNichols,
+            apply: true,
+            amplitude: 10.0,
+            hysteresis: 0.5,
+            settle_s: 3.0,
+            cycles: 3,
+            tolerance: 0.1,
+            timeout_s: 20.0,
+        }
+    }
+}
+
+/// Experiment settings plus the command range of the controller being tuned.
+#[derive(Debug, Clone, Copy)]
+pub struct AutotuneSetup {
+    pub config: AutotuneConfig,
+    pub output_min: f64,
+    pub output_max: f64,
+}
+
+impl AutotuneSetup {
+    pub fn new(law: &LawConfig) -> Self {
+        Self { config: law.autotune, output_min: law.output_min, output_max: law.output_max }
+    }
+}
+
+impl Default for AutotuneSetup {
+    fn default() -> Self {
+        Self::new(&LawConfig::default())
+    }
+}
+
+/// Outcome of one experiment (also reported when it timed out).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutotuneResult {
+    pub rule: &'static str,
+    pub converged: bool,            // Consistent limit cycle identified
+    pub applied: bool,              // Gains switched to (set by the controller)
+    pub ultimate_gain: f64,         // Ku (NaN without a usable cycle)
+    pub ultimate_period_s: f64,     // Pu
+    pub oscillation: f64,           // Measured half peak-to-peak a
+    pub relay_amplitude: f64,       // d actually used (fits the command range)
+    pub bias: f64,                  // Final relay centre u0
+    pub gains: [f64; 3],            // Proposed [kp, ki, kd]
+    pub cycles: usize,              // Limit-cycle periods observed
+    pub duration_s: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Settling,
+    Relay,
+    Done,
+}
+
+/// One relay experiment; the controller asks it for a command on every control instant.
+pub struct RelayExperiment {
+    config: AutotuneConfig,
+    bias_min: f64,
+    bias_max: f64,
+    amplitude: f64,
+    phase: Phase,
+    elapsed_s: f64,
+    bias: Option<f64>,
+    high: bool,
+    last_switch_s: f64,
+    cycle_start_s: Option<f64>,     // Last switch to the high side
+    high_s: f64,                    // Time on each side during the current cycle
+    low_s: f64,
+    y_max: f64,
+    y_min: f64,
+    periods: [f64; MAX_CYCLES],     // Measured cycles (ring)
+    oscillations: [f64; MAX_CYCLES],
+    asymmetries: [f64; MAX_CYCLES],
+    cycles: usize,                  // All completed cycles, settling ones included
+    result: Option<AutotuneResult>,
+}
+
+impl RelayExperiment {
+    pub fn new(setup: &AutotuneSetup) -> Self {
+        let mut config = setup.config;
+        config.cycles = config.cycles.clamp(1, MAX_CYCLES);
+        config.hysteresis = config.hysteresis.abs();
+        let amplitude = config.amplitude.abs().min((setup.output_max - setup.output_min) / 2.0);
+
+        Self {
+            config,
+            bias_min: setup.output_min + amplitude,
+            bias_max: setup.output_max - amplitude,
+            amplitude,
+            phase: Phase::Settling,
+            elapsed_s: 0.0,
+            bias: None,
+            high: false,
+            last_switch_s: 0.0,
+            cycle_start_s: None,
+            high_s: 0.0,
+            low_s: 0.0,
+            y_max: f64::NEG_INFINITY,
+            y_min: f64::INFINITY,
+            periods: [0.0; MAX_CYCLES],
+            oscillations: [0.0; MAX_CYCLES],
+            asymmetries: [0.0; MAX_CYCLES],
+            cycles: 0,
+            result: None,
+        }
+    }
+
+    pub fn config(&self) -> &AutotuneConfig {
+        &self.config
+    }
+
+    /// Relay command for this instant, or None while the configured law should run
+    /// (settling, or once finished).
+    pub fn command(&mut self, setpoint: f64, measurement: f64, dt: f64) -> Option<f64> {
+        self.elapsed_s += dt;
+        let error = setpoint - measurement;
+
+        match self.phase {
+            Phase::Done => return None,
+            Phase::Settling if self.elapsed_s < self.config.settle_s => return None,
+            Phase::Settling => {
+                self.phase = Phase::Relay;
+                self.bias = Some(self.bias.unwrap_or(0.0).clamp(self.bias_min, self.bias_max));
+                self.high = error > 0.0;
+                self.last_switch_s = self.elapsed_s;
+            }
+            Phase::Relay => {}
+        }
+        if self.elapsed_s >= self.config.timeout_s {
+            self.finish(false);
+            return None;
+        }
+
+        if self.high {
+            self.high_s += dt;
+        } else {
+            self.low_s += dt;
+        }
+        self.y_max = self.y_max.max(measurement);
+        self.y_min = self.y_min.min(measurement);
+
+        let hysteresis = self.config.hysteresis;
+        if !self.high && error > hysteresis {
+            self.high = true;
+            self.last_switch_s = self.elapsed_s;
+            self.complete_cycle();
+            if self.phase == Phase::Done {
+                return None;
+            }
+        } else if self.high && error < -hysteresis {
+            self.high = false;
+            self.last_switch_s = self.elapsed_s;
+        } else if self.elapsed_s - self.last_switch_s > STUCK_S {
+            // The relay cannot push the output across the setpoint: move the bias that way
+            let step = self.amplitude / 2.0 * if self.high { 1.0 } else { -1.0 };
+            self.bias = self.bias.map(|b| (b + step).clamp(self.bias_min, self.bias_max));
+            self.last_switch_s = self.elapsed_s;
+        }
+
+        let bias = self.bias.unwrap_or(0.0);
+        Some(if self.high { bias + self.amplitude } else { bias - self.amplitude })
+    }
+
+    /// Output of the configured law while settling (averaged into the relay bias).
+    pub fn observe(&mut self, output: f64, dt: f64) {
+        if self.phase == Phase::Settling {
+            let bias = self.bias.get_or_insert(output);
+            *bias += dt / (BIAS_TAU_S + dt) * (output - *bias);
+        }
+    }
+
+    /// The finished experiment's result, once.
+    pub fn take_result(&mut self) -> Option<AutotuneResult> {
+        self.result.take()
+    }
+
+    /// Rising switch: closes the current cycle, re-centres the bias, checks convergence.
+    fn complete_cycle(&mut self) {
+        let now = self.elapsed_s;
+        if let Some(start) = self.cycle_start_s {
+            let period = now - start;
+            let asymmetry = (self.high_s - self.low_s) / period;
+            // Mean output over the cycle is u0 + d·asymmetry: centre the relay there
+            self.bias = self.bias.map(|b| (b + self.amplitude * asymmetry).clamp(self.bias_min, self.bias_max));
+
+            self.cycles += 1;
+            if self.cycles > SETTLING_CYCLES {
+                let slot = (self.cycles - SETTLING_CYCLES - 1) % MAX_CYCLES;
+                self.periods[slot] = period;
+                self.oscillations[slot] = (self.y_max - self.y_min) / 2.0;
+                self.asymmetries[slot] = asymmetry;
+                if self.cycles - SETTLING_CYCLES >= self.config.cycles && self.consistent() {
+                    self.finish(true);
+                }
+            }
+        }
+
+        self.cycle_start_s = Some(now);
+        self.high_s = 0.0;
+        self.low_s = 0.0;
+        self.y_max = f64::NEG_INFINITY;
+        self.y_min = f64::INFINITY;
+    }
+
+    /// Indices of the last `cycles` measured periods.
+    fn recent(&self) -> impl Iterator<Item = usize> + '_ {
+        let measured = self.cycles.saturating_sub(SETTLING_CYCLES);
+        let n = self.config.cycles.min(measured);
+        (measured - n..measured).map(|i| i % MAX_CYCLES)
+    }
+
+    fn consistent(&self) -> bool {
+        let (mut lo, mut hi, mut sum) = (f64::INFINITY, 0.0_f64, 0.0);
+        for i in self.recent() {
+            lo = lo.min(self.periods[i]);
+            hi = hi.max(self.periods[i]);
+            sum += self.periods[i];
+            if self.asymmetries[i].abs() > self.config.tolerance {
+                return false;
+            }
+        }
+        let mean = sum / self.config.cycles as f64;
+        mean > 0.0 && (hi - lo) / mean <= self.config.tolerance
+    }
+
+    fn finish(&mut self, converged: bool) {
+        let n = self.recent().count();
+        let (period, oscillation) = if n == 0 {
+            (f64::NAN, f64::NAN)
+        } else {
+            let sum = |values: &[f64; MAX_CYCLES]| self.recent().map(|i| values[i]).sum::<f64>() / n as f64;
+            (sum(&self.periods), sum(&self.oscillations))
+        };
+
+        let hysteresis = self.config.hysteresis;
+        let ultimate_gain = if oscillation > hysteresis {
+            4.0 * self.amplitude / (std::f64::consts::PI * (oscillation * oscillation - hysteresis * hysteresis).sqrt())
+        } else {
+            f64::NAN
+        };
+        let identified = converged && ultimate_gain.is_finite() && period.is_finite();
+
+        self.phase = Phase::Done;
+        self.result = Some(AutotuneResult {
+            rule: self.config.rule.name(),
+            converged: identified,
+            applied: false,
+            ultimate_gain,
+            ultimate_period_s: period,
+            oscillation,
+            relay_amplitude: self.amplitude,
+            bias: self.bias.unwrap_or(f64::NAN),
+            gains: if identified { self.config.rule.gains(ultimate_gain, period) } else { [f64::NAN; 3] },
+            cycles: self.cycles,
+            duration_s: self.elapsed_s,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component_b::plant::{FirstOrderLag, PlantModel};
+
+    const DT: f64 = 0.001;
+
+    /// Three unit-gain lags of `tau_s` in series: the phase reaches −180° at ω = √3/τ, where
+    /// the gain is 1/8, so Ku = 8 and Pu = 2πτ/√3.
+    fn three_lags(tau_s: f64) -> [FirstOrderLag; 3] {
+        std::array::from_fn(|_| FirstOrderLag::new(1.0, tau_s))
+    }
+
+    fn setup(amplitude: f64, settle_s: f64, timeout_s: f64) -> AutotuneSetup {
+        AutotuneSetup {
+            config: AutotuneConfig {
+                enabled: true,
+                amplitude,
+                hysteresis: 0.002,
+                settle_s,
+                timeout_s,
+                ..AutotuneConfig::default()
+            },
+            output_min: -10.0,
+            output_max: 10.0,
+        }
+    }
+
+    /// Runs the experiment on the lag chain; the configured law is stood in for by a constant
+    /// `settle_input` while settling. `vary` may change the plant at each instant.
+    fn run(
+        experiment: &mut RelayExperiment,
+        plant: &mut [FirstOrderLag; 3],
+        setpoint: f64,
+        settle_input: f64,
+        max_s: f64,
+        mut vary: impl FnMut(f64, &mut [FirstOrderLag; 3]),
+    ) -> Option<AutotuneResult> {
+        for i in 0..(max_s / DT) as usize {
+            vary(i as f64 * DT, plant);
+            let measurement = plant[2].output();
+            let input = match experiment.command(setpoint, measurement, DT) {
+                Some(relay) => relay,
+                None => {
+                    experiment.observe(settle_input, DT);
+                    settle_input
+                }
+            };
+            plant[0].step(input, DT);
+            let stage_1 = plant[0].output();
+            plant[1].step(stage_1, DT);
+            let stage_2 = plant[1].output();
+            plant[2].step(stage_2, DT);
+
+            if let Some(result) = experiment.take_result() {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    fn assert_ultimate_point(result: &AutotuneResult, tau_s: f64) {
+        let (ku, pu) = (8.0, 2.0 * std::f64::consts::PI * tau_s / 3.0_f64.sqrt());
+        assert!(result.converged, "{:?}", result);
+        // The describing function ignores the harmonics: a few % on Ku, less on Pu
+        assert!((result.ultimate_gain - ku).abs() / ku < 0.1, "Ku {}", result.ultimate_gain);
+        assert!((result.ultimate_period_s - pu).abs() / pu < 0.05, "Pu {}", result.ultimate_period_s);
+        assert_eq!(result.gains, TuningRule::ZieglerNichols.gains(result.ultimate_gain, result.ultimate_period_s));
+        assert!(result.cycles >= SETTLING_CYCLES + 3);
+    }
+
+    #[test]
+    fn relay_identifies_the_ultimate_point_of_three_lags() {
+        let mut experiment = RelayExperiment::new(&setup(1.0, 10.0, 60.0));
+        let result = run(&mut experiment, &mut three_lags(1.0), 1.0, 1.0, 60.0, |_, _| {}).expect("experiment finished");
+
+        assert_ultimate_point(&result, 1.0);
+        // Settled on the static input the plant needs at the setpoint (unit gain)
+        assert!((result.bias - 1.0).abs() < 0.05, "bias {}", result.bias);
+        assert_eq!(experiment.command(1.0, 1.0, DT), None);
+    }
+
+    #[test]
+    fn stuck_relay_moves_its_bias_until_the_loop_oscillates() {
+        // Settling leaves u0 = 1 while the setpoint needs 5: u0 + d cannot reach it. Lags of
+        // 0.5 s keep the half period (≈ 0.9 s) well below the stuck-relay time once it oscillates
+        let mut experiment = RelayExperiment::new(&setup(1.0, 5.0, 60.0));
+        let result = run(&mut experiment, &mut three_lags(0.5), 5.0, 1.0, 60.0, |_, _| {}).expect("experiment finished");
+
+        assert_ultimate_point(&result, 0.5);
+        assert!((result.bias - 5.0).abs() < 0.1, "bias {}", result.bias);
+    }
+
+    #[test]
+    fn timeout_before_enough_cycles_reports_no_gains() {
+        // Two periods after settling are only the discarded settling cycles
+        let mut experiment = RelayExperiment::new(&setup(1.0, 10.0, 18.0));
+        let result = run(&mut experiment, &mut three_lags(1.0), 1.0, 1.0, 30.0, |_, _| {}).expect("experiment timed out");
+
+        assert!(!result.converged);
+        assert!((result.duration_s - 18.0).abs() < 2.0 * DT);
+        assert!(result.cycles <= SETTLING_CYCLES + 1, "cycles {}", result.cycles);
+        assert!(result.ultimate_gain.is_nan() && result.ultimate_period_s.is_nan());
+        assert!(result.gains.iter().all(|g| g.is_nan()));
+        assert_eq!(experiment.command(1.0, 1.0, DT), None);
+    }
+
+    #[test]
+    fn timeout_with_drifting_periods_reports_the_estimate_without_gains() {
+        let mut experiment = RelayExperiment::new(&setup(1.0, 10.0, 60.0));
+        experiment.config.tolerance = 0.02;
+        // One lag slows down during the relay phase: consecutive periods never agree
+        let drift = |t: f64, plant: &mut [FirstOrderLag; 3]| plant[0].tau_s = 1.0 + 0.05 * (t - 10.0).max(0.0);
+        let result = run(&mut experiment, &mut three_lags(1.0), 1.0, 1.0, 70.0, drift).expect("experiment timed out");
+
+        // Periods were measured (Ku/Pu reported) but not consistent: no gains proposed
+        assert!(!result.converged);
+        assert!(result.cycles > SETTLING_CYCLES + 3);
+        assert!(result.ultimate_gain.is_finite() && result.ultimate_period_s.is_finite());
+        assert!(result.gains.iter().all(|g| g.is_nan()));
+    }
+}
@@ -0,0 +1,854 @@
+//! control_law.rs
+//! Control laws selectable per actuator (`[control.<actuator>] law = ...` in config.toml).
+//!
+//! - `Pid`: the default (Kp 1.2, Ki 0.01, Kd 0.2, ±50). Derivative on measurement through a
+//!   first-order filter, selectable anti-windup, and bumpless retuning: the integral holds the
+//!   I term itself and is re-seated on a Kp / Kd change so the output does not jump. The
+//!   anti-windup also covers limits of the actuator model downstream (`actuator_limited`).
+//! - `PidgeonPid`: pidgeon PID, the original controller (derivative on error, integral freeze).
+//!   Retuning Ki rescales its whole integral, so gain changes are not bumpless.
+//! - `CratePid`: the `pid` crate. Its gains are per sample, so Ki·dt and Kd/dt are re-applied
+//!   with the measured dt on every call; derivative on measurement (unfiltered), integral clamp
+//!   as anti-windup, and an output offset absorbs Kp / Kd changes.
+//! - `BangBang`: on/off with a hysteresis band around the setpoint.
+//! - `Lqr`: discrete infinite-horizon LQR on the driven plant's model, with a steady-state
+//!   Kalman observer and setpoint feed-forward (no offset on an exact model).
+//! - `Mpc`: horizon-limited MPC on the same model; the box-constrained QP is solved by a fixed
+//!   number of accelerated projected-gradient iterations (bounded time, no allocation).
+//!
+//! Model-based laws are designed once, at construction, for the control period (`sample_s`, at
+//! least the `CONTROL_SAMPLE_S` sensor period); without a plant model they fall back to PID.
+//! The controller times every `compute` call against the 2 ms actuator deadline
+//! (`LiveMetrics::record_law`).
+
+use log::warn;
+use pidgeon::{ControllerConfig, PidController};
+use serde::Deserialize;
+
+use crate::component_b::autotune::AutotuneConfig;
+use crate::component_b::plant::{
+    LinearModel, Mat, PlantSpec, Vector, MAX_STATES, dot, mat_mul, mat_vec, transpose,
+};
+
+/// Design sample time of the model-based laws (sensor period, 5 ms).
+pub const CONTROL_SAMPLE_S: f64 = 0.005;
+
+const MAX_HORIZON: usize = 20;              // MPC prediction steps
+const MPC_ITERATIONS: usize = 40;           // Projected-gradient iterations per cycle
+const DARE_MAX_ITERATIONS: usize = 200_000;
+const DARE_TOLERANCE: f64 = 1e-10;          // Relative change that ends the Riccati iteration
+const OBSERVER_PROCESS_NOISE: f64 = 1e-2;   // Kalman W (per state) relative to V = 1
+const OBSERVER_MAX_STEPS: f64 = 20.0;       // Prediction steps per update (long gaps are capped)
+
+/// One control algorithm: maps setpoint and measurement to an actuator command.
+pub trait ControlLaw: Send {
+    /// Command for `measurement` against `setpoint`; `dt` is the time since the previous call (s).
+    fn compute(&mut self, setpoint: f64, measurement: f64, dt: f64) -> f64;
+
+    fn name(&self) -> &'static str;
+
+    /// Changes PID gains (any subset); false when the law has none or rejects a value.
+    fn tune(&mut self, _kp: Option<f64>, _ki: Option<f64>, _kd: Option<f64>) -> bool {
+        false
+    }
+
+    /// Current PID gains `[kp, ki, kd]`; None when the law has none.
+    fn gains(&self) -> Option<[f64; 3]> {
+        None
+    }
+
+    /// The actuator saturated or rate-limited the last output and delivered `applied` instead;
+    /// laws with an integrator apply their anti-windup to it.
+    fn actuator_limited(&mut self, _applied: f64) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LawKind {
+    Pid,        // Filtered derivative, selectable anti-windup, bumpless retuning
+    Pidgeon,    // pidgeon crate (the original controller)
+    PidCrate,   // `pid` crate
+    BangBang,
+    Lqr,
+    Mpc,
+}
+
+/// What the PID integrator does while the output is saturated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AntiWindup {
+    Off,                // Keep integrating (winds up)
+    Clamp,              // Conditional integration: hold while saturated in the error's direction
+    BackCalculation,    // Bleed the integral by (u_sat − u) / Tt, Tt = √(Ti·Td) (Ti without D)
+}
+
+/// `[control.<controller>]`: law and its parameters (unused ones are ignored).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LawConfig {
+    pub law: LawKind,
+    pub kp: f64,            // PID gains (continuous time)
+    pub ki: f64,
+    pub kd: f64,
+    pub anti_windup: AntiWindup,
+    pub derivative_filter_s: f64,   // PID derivative low-pass time constant (s); 0 = unfiltered
+    pub sample_s: f64,      // Control period (s): the law runs at most this often, the command
+                            // holds in between; 0 = every packet. Model-based design period too
+    pub output_min: f64,    // Command limits (bang-bang: off / on levels)
+    pub output_max: f64,
+    pub hysteresis: f64,    // Bang-bang: half-width of the band around the setpoint
+    pub q_output: f64,      // LQR / MPC: weight on output error²
+    pub r_input: f64,       // LQR / MPC: weight on command² (deviation from steady state)
+    pub horizon: usize,     // MPC prediction steps (≤ 20)
+    pub autotune: AutotuneConfig,   // Relay auto-tuning of the PID gains (autotune.rs)
+}
+
+impl Default for LawConfig {
+    fn default() -> Self {
+        Self {
+            law: LawKind::Pid,
+            kp: 1.2,
+            ki: 0.01,
+            kd: 0.2,
+            anti_windup: AntiWindup::Clamp,
+            derivative_filter_s: 0.02,
+            sample_s: 0.0,
+            output_min: -50.0,
+            output_max: 50.0,
+            hysteresis: 1.0,
+            q_output: 1.0,
+            r_input: 0.1,
+            horizon: 10,
+            autotune: AutotuneConfig::default(),
+        }
+    }
+}
+
+impl LawConfig {
+    /// Builds the law; LQR and MPC are designed on `plant` (PID fallback without one).
+    pub fn build(&self, plant: Option<&PlantSpec>, sample_s: f64) -> Box<dyn ControlLaw> {
+        let model = plant.map(|spec| spec.linear().discretize(sample_s));
+        let law: Option<Box<dyn ControlLaw>> = match (self.law, model) {
+            (LawKind::Pid, _) => Some(Box::new(Pid::new(self))),
+            (LawKind::Pidgeon, _) => Some(Box::new(PidgeonPid::new(self))),
+            (LawKind::PidCrate, _) => Some(Box::new(CratePid::new(self))),
+            (LawKind::BangBang, _) => Some(Box::new(BangBang::new(self))),
+            (LawKind::Lqr, Some(model)) => Lqr::new(self, model, sample_s).map(|l| Box::new(l) as Box<dyn ControlLaw>),
+            (LawKind::Mpc, Some(model)) => Mpc::new(self, model, sample_s).map(|l| Box::new(l) as Box<dyn ControlLaw>),
+            (LawKind::Lqr | LawKind::Mpc, None) => None,
+        };
+
+        let law = law.unwrap_or_else(|| {
+            warn!("[Control] {:?} needs a plant model with a finite design; using PID", self.law);
+            Box::new(Pid::new(self)) as Box<dyn ControlLaw>
+        });
+        if self.autotune.enabled && self.autotune.apply && law.gains().is_none() {
+            warn!("[Autotune] {} has no PID gains; tuned gains are reported but not applied", law.name());
+        }
+        law
+    }
+}
+
+// ============================================================================
+// PID
+// ============================================================================
+
+pub struct Pid {
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    anti_windup: AntiWindup,
+    derivative_filter_s: f64,
+    output_min: f64,
+    output_max: f64,
+    integral: f64,                  // The I term itself (Ki·∫e), so a Ki change is bumpless
+    rate: f64,                      // Filtered −d(measurement)/dt
+    last_error: f64,
+    last_measurement: Option<f64>,
+    last_output: f64,
+    last_dt: f64,
+    last_integration: f64,          // Added to the integral by the last call
+}
+
+impl Pid {
+    pub fn new(config: &LawConfig) -> Self {
+        Self {
+            kp: config.kp,
+            ki: config.ki,
+            kd: config.kd,
+            anti_windup: config.anti_windup,
+            derivative_filter_s: config.derivative_filter_s.max(0.0),
+            output_min: config.output_min,
+            output_max: config.output_max,
+            integral: 0.0,
+            rate: 0.0,
+            last_error: 0.0,
+            last_measurement: None,
+            last_output: 0.0,
+            last_dt: 0.0,
+            last_integration: 0.0,
+        }
+    }
+
+    /// Back-calculation tracking time constant: √(Ti·Td) = √(Kd/Ki), or Ti = Kp/Ki without D.
+    fn tracking_s(&self) -> f64 {
+        if self.kd > 0.0 {
+            (self.kd / self.ki).sqrt()
+        } else {
+            self.kp / self.ki
+        }
+    }
+}
+
+impl ControlLaw for Pid {
+    fn compute(&mut self, setpoint: f64, measurement: f64, dt: f64) -> f64 {
+        let dt = dt.max(1e-6);
+        let error = setpoint - measurement;
+
+        // Derivative on measurement (no kick on setpoint steps), low-pass filtered
+        if let Some(previous) = self.last_measurement {
+            let raw = -(measurement - previous) / dt;
+            self.rate += dt / (self.derivative_filter_s + dt) * (raw - self.rate);
+        }
+        self.last_measurement = Some(measurement);
+        self.last_error = error;
+
+        let integral = self.integral + self.ki * error * dt;
+        let unsaturated = self.kp * error + integral + self.kd * self.rate;
+        let output = unsaturated.clamp(self.output_min, self.output_max);
+
+        let updated = match self.anti_windup {
+            AntiWindup::Off => integral,
+            // Integrate unless saturated and the error pushes further into the limit
+            AntiWindup::Clamp if output != unsaturated && (unsaturated > output) == (error > 0.0) => self.integral,
+            AntiWindup::Clamp => integral,
+            AntiWindup::BackCalculation if self.ki > 0.0 => {
+                integral + (output - unsaturated) * (dt / self.tracking_s()).min(1.0)
+            }
+            AntiWindup::BackCalculation => integral,
+        };
+        self.last_integration = updated - self.integral;
+        self.integral = updated;
+        self.last_output = output;
+        self.last_dt = dt;
+        output
+    }
+
+    fn name(&self) -> &'static str {
+        "pid"
+    }
+
+    fn tune(&mut self, kp: Option<f64>, ki: Option<f64>, kd: Option<f64>) -> bool {
+        let valid = |v: Option<f64>| v.is_none_or(f64::is_finite);
+        if !(valid(kp) && valid(ki) && valid(kd)) {
+            return false;
+        }
+        let kp = kp.unwrap_or(self.kp);
+        let kd = kd.unwrap_or(self.kd);
+
+        // Bumpless: re-seat the integral so P + I + D is unchanged at the switch
+        self.integral += (self.kp - kp) * self.last_error + (self.kd - kd) * self.rate;
+        self.kp = kp;
+        self.ki = ki.unwrap_or(self.ki);
+        self.kd = kd;
+        true
+    }
+
+    fn gains(&self) -> Option<[f64; 3]> {
+        Some([self.kp, self.ki, self.kd])
+    }
+
+    fn actuator_limited(&mut self, applied: f64) {
+        let excess = applied - self.last_output;
+        match self.anti_windup {
+            AntiWindup::Off => {}
+            // Undo the last integration if it pushed further into the actuator's limit
+            AntiWindup::Clamp if excess != 0.0 && (excess < 0.0) == (self.last_error > 0.0) => {
+                self.integral -= self.last_integration;
+                self.last_integration = 0.0;
+            }
+            AntiWindup::Clamp => {}
+            AntiWindup::BackCalculation if self.ki > 0.0 => {
+                self.integral += excess * (self.last_dt / self.tracking_s()).min(1.0);
+            }
+            AntiWindup::BackCalculation => {}
+        }
+        self.last_output = applied;
+    }
+}
+
+// ============================================================================
+// PID (pidgeon)
+// ============================================================================
+
+pub struct PidgeonPid {
+    pid: PidController,
+    setpoint: f64,
+    gains: [f64; 3],
+}
+
+impl PidgeonPid {
+    pub fn new(config: &LawConfig) -> Self {
+        let pid_config = ControllerConfig::new()
+            .with_kp(config.kp)
+            .with_ki(config.ki)
+            .with_kd(config.kd)
+            .with_output_limits(config.output_min, config.output_max)
+            .with_anti_windup(config.anti_windup != AntiWindup::Off);//prevents runaway intergral term
+
+        Self {
+            pid: PidController::new(pid_config),
+            setpoint: 0.0,
+            gains: [config.kp, config.ki, config.kd],
+        }
+    }
+}
+
+impl ControlLaw for PidgeonPid {
+    fn compute(&mut self, setpoint: f64, measurement: f64, dt: f64) -> f64 {
+        if setpoint != self.setpoint && self.pid.set_setpoint(setpoint).is_ok() {
+            self.setpoint = setpoint;
+        }
+        self.pid.compute(measurement, dt)
+    }
+
+    fn name(&self) -> &'static str {
+        "pidgeon"
+    }
+
+    fn tune(&mut self, kp: Option<f64>, ki: Option<f64>, kd: Option<f64>) -> bool {
+        // pidgeon rejects non-finite gains one by one: check first so a change is all or nothing
+        let valid = |v: Option<f64>| v.is_none_or(f64::is_finite);
+        if !(valid(kp) && valid(ki) && valid(kd)) {
+            return false;
+        }
+        if let Some(kp) = kp {
+            let _ = self.pid.set_kp(kp);
+            self.gains[0] = kp;
+        }
+        if let Some(ki) = ki {
+            let _ = self.pid.set_ki(ki);
+            self.gains[1] = ki;
+        }
+        if let Some(kd) = kd {
+            let _ = self.pid.set_kd(kd);
+            self.gains[2] = kd;
+        }
+        true
+    }
+
+    fn gains(&self) -> Option<[f64; 3]> {
+        Some(self.gains)
+    }
+}
+
+// ============================================================================
+// PID (`pid` crate)
+// ============================================================================
+
+pub struct CratePid {
+    pid: pid::Pid<f64>,
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    output_min: f64,
+    output_max: f64,
+    offset: f64,                    // Absorbs Kp / Kd changes (bumpless retuning)
+    last_error: f64,
+    last_rate: f64,                 // −d(measurement)/dt of the last call
+    last_measurement: Option<f64>,
+}
+
+impl CratePid {
+    pub fn new(config: &LawConfig) -> Self {
+        // The crate limits symmetrically: clamp to the asymmetric range afterwards
+        let limit = config.output_min.abs().max(config.output_max.abs());
+        let integral_limit = if config.anti_windup == AntiWindup::Off { f64::INFINITY } else { limit };
+        let mut pid = pid::Pid::new(0.0, limit);
+        pid.p(config.kp, limit).i(0.0, integral_limit).d(0.0, limit);
+
+        Self {
+            pid,
+            kp: config.kp,
+            ki: config.ki,
+            kd: config.kd,
+            output_min: config.output_min,
+            output_max: config.output_max,
+            offset: 0.0,
+            last_error: 0.0,
+            last_rate: 0.0,
+            last_measurement: None,
+        }
+    }
+}
+
+impl ControlLaw for CratePid {
+    fn compute(&mut self, setpoint: f64, measurement: f64, dt: f64) -> f64 {
+        let dt = dt.max(1e-6);
+        self.pid.setpoint(setpoint);
+        // The integral term stores ki·Σe, so rescaling ki every sample stays bumpless
+        self.pid.kp = self.kp;
+        self.pid.ki = self.ki * dt;
+        self.pid.kd = self.kd / dt;
+        self.last_error = setpoint - measurement;
+        self.last_rate = self.last_measurement.map_or(0.0, |previous| -(measurement - previous) / dt);
+        self.last_measurement = Some(measurement);
+
+        let output = self.pid.next_control_output(measurement).output + self.offset;
+        output.clamp(self.output_min, self.output_max)
+    }
+
+    fn name(&self) -> &'static str {
+        "pid_crate"
+    }
+
+    fn tune(&mut self, kp: Option<f64>, ki: Option<f64>, kd: Option<f64>) -> bool {
+        let valid = |v: Option<f64>| v.is_none_or(f64::is_finite);
+        if !(valid(kp) && valid(ki) && valid(kd)) {
+            return false;
+        }
+        let kp = kp.unwrap_or(self.kp);
+        let kd = kd.unwrap_or(self.kd);
+
+        // The crate's integral is private: an offset keeps P + I + D continuous instead
+        self.offset += (self.kp - kp) * self.last_error + (self.kd - kd) * self.last_rate;
+        self.kp = kp;
+        self.ki = ki.unwrap_or(self.ki);
+        self.kd = kd;
+        true
+    }
+
+    fn gains(&self) -> Option<[f64; 3]> {
+        Some([self.kp, self.ki, self.kd])
+    }
+}
+
+// ============================================================================
+// On/off with hysteresis
+// ============================================================================
+
+pub struct BangBang {
+    on_level: f64,
+    off_level: f64,
+    hysteresis: f64,
+    on: bool,
+}
+
+impl BangBang {
+    pub fn new(config: &LawConfig) -> Self {
+        Self {
+            on_level: config.output_max,
+            off_level: config.output_min,
+            hysteresis: config.hysteresis.abs(),
+            on: false,
+        }
+    }
+}
+
+impl ControlLaw for BangBang {
+    fn compute(&mut self, setpoint: f64, measurement: f64, _dt: f64) -> f64 {
+        // Switch only when the error leaves the band; inside it the last state holds
+        let error = setpoint - measurement;
+        if error > self.hysteresis {
+            self.on = true;
+        } else if error < -self.hysteresis {
+            self.on = false;
+        }
+        if self.on { self.on_level } else { self.off_level }
+    }
+
+    fn name(&self) -> &'static str {
+        "bang_bang"
+    }
+}
+
+// ============================================================================
+// Model-based laws: observer, Riccati solver, steady-state target
+// ============================================================================
+
+/// Steady-state Kalman filter on the discrete model (filtered form).
+struct Observer {
+    model: LinearModel,
+    gain: Vector,
+    x: Vector,
+    sample_s: f64,
+}
+
+impl Observer {
+    fn new(model: LinearModel, sample_s: f64) -> Option<Self> {
+        // Dual Riccati equation gives the prior covariance P; M = P·c / (cᵀ·P·c + V)
+        let w = active_diagonal(model.n, OBSERVER_PROCESS_NOISE);
+        let p = dare(&transpose(&model.a), &model.c, &w, 1.0)?;
+        let pc = mat_vec(&p, &model.c);
+        let denom = dot(&model.c, &pc) + 1.0;
+
+        Some(Self {
+            model,
+            gain: pc.map(|v| v / denom),
+            x: [0.0; MAX_STATES],
+            sample_s,
+        })
+    }
+
+    /// Predicts over `dt` under the last command, corrects with the measurement, returns x̂.
+    fn update(&mut self, measurement: f64, last_input: f64, dt: f64) -> Vector {
+        let steps = (dt / self.sample_s).round().clamp(1.0, OBSERVER_MAX_STEPS) as usize;
+        for _ in 0..steps {
+            self.x = step(&self.model, &self.x, last_input);
+        }
+        let innovation = measurement - (dot(&self.model.c, &self.x) + self.model.y0);
+        for (x, g) in self.x.iter_mut().zip(&self.gain) {
+            *x += g * innovation;
+        }
+        self.x
+    }
+}
+
+#[inline]
+fn step(model: &LinearModel, x: &Vector, u: f64) -> Vector {
+    let ax = mat_vec(&model.a, x);
+    std::array::from_fn(|i| ax[i] + model.b[i] * u)
+}
+
+fn active_diagonal(n: usize, value: f64) -> Mat {
+    std::array::from_fn(|i| std::array::from_fn(|j| if i == j && i < n { value } else { 0.0 }))
+}
+
+/// Single-input discrete algebraic Riccati equation, by fixed-point iteration:
+/// P = Q + AᵀPA − AᵀPb·bᵀPA / (r + bᵀPb). None if it does not converge.
+fn dare(a: &Mat, b: &Vector, q: &Mat, r: f64) -> Option<Mat> {
+    let at = transpose(a);
+    let mut p = *q;
+    for _ in 0..DARE_MAX_ITERATIONS {
+        let pa = mat_mul(&p, a);
+        let pb = mat_vec(&p, b);
+        let btpa: Vector = std::array::from_fn(|j| (0..MAX_STATES).map(|i| b[i] * pa[i][j]).sum());
+        let denom = r + dot(b, &pb);
+        let atpa = mat_mul(&at, &pa);
+
+        let next: Mat = std::array::from_fn(|i| {
+            std::array::from_fn(|j| q[i][j] + atpa[i][j] - btpa[i] * btpa[j] / denom)
+        });
+        if next.iter().flatten().any(|v| !v.is_finite()) {
+            return None;
+        }
+
+        let change = next.iter().flatten().zip(p.iter().flatten()).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max);
+        let size = next.iter().flatten().map(|v| v.abs()).fold(0.0, f64::max);
+        p = next;
+        if change <= DARE_TOLERANCE * size.max(1e-12) {
+            return Some(p);
+        }
+    }
+    None
+}
+
+/// Equilibrium (x, u) with output `setpoint`: (A − I)·x + b·u = 0, c·x + y0 = setpoint.
+fn steady_state(model: &LinearModel, setpoint: f64) -> Option<(Vector, f64)> {
+    const N: usize = MAX_STATES + 1;
+    let mut m = [[0.0; N + 1]; N];     // Augmented [M | rhs]
+    for (i, row) in m.iter_mut().take(MAX_STATES).enumerate() {
+        for (j, v) in row.iter_mut().take(MAX_STATES).enumerate() {
+            *v = model.a[i][j] - if i == j { 1.0 } else { 0.0 };
+        }
+        row[MAX_STATES] = model.b[i];
+    }
+    m[MAX_STATES][..MAX_STATES].copy_from_slice(&model.c);
+    m[MAX_STATES][N] = setpoint - model.y0;
+
+    // Gaussian elimination with partial pivoting
+    for col in 0..N {
+        let pivot = (col..N).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+        if m[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        m.swap(col, pivot);
+        let pivot_row = m[col];
+        for (r, row) in m.iter_mut().enumerate() {
+            if r != col {
+                let f = row[col] / pivot_row[col];
+                for (v, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *v -= f * p;
+                }
+            }
+        }
+    }
+
+    let x = std::array::from_fn(|i| m[i][N] / m[i][i]);
+    let u = m[MAX_STATES][N] / m[MAX_STATES][MAX_STATES];
+    Some((x, u))
+}
+
+/// Equilibrium for a new setpoint, cached until the setpoint changes.
+struct Target {
+    setpoint: f64,
+    x: Vector,
+    u: f64,
+}
+
+impl Target {
+    fn update(&mut self, model: &LinearModel, setpoint: f64) {
+        if setpoint == self.setpoint {
+            return;
+        }
+        // A singular system (no equilibrium for this output) keeps the previous target
+        if let Some((x, u)) = steady_state(model, setpoint) {
+            *self = Target { setpoint, x, u };
+        }
+    }
+}
+
+// ============================================================================
+// Discrete LQR
+// ============================================================================
+
+pub struct Lqr {
+    model: LinearModel,
+    gain: Vector,           // u = u_ss − K·(x̂ − x_ss)
+    observer: Observer,
+    target: Target,
+    last_input: f64,
+    output_min: f64,
+    output_max: f64,
+}
+
+impl Lqr {
+    pub fn new(config: &LawConfig, model: LinearModel, sample_s: f64) -> Option<Self> {
+        // Output weighting Q = q·cᵀc, plus a tiny diagonal so every state is penalised
+        let mut q: Mat = std::array::from_fn(|i| std::array::from_fn(|j| config.q_output * model.c[i] * model.c[j]));
+        for (i, row) in q.iter_mut().enumerate().take(model.n) {
+            row[i] += 1e-9;
+        }
+        let r = config.r_input.max(1e-9);
+        let p = dare(&model.a, &model.b, &q, r)?;
+
+        let pa = mat_mul(&p, &model.a);
+        let pb = mat_vec(&p, &model.b);
+        let denom = r + dot(&model.b, &pb);
+        let gain = std::array::from_fn(|j| (0..MAX_STATES).map(|i| model.b[i] * pa[i][j]).sum::<f64>() / denom);
+
+        Some(Self {
+            model,
+            gain,
+            observer: Observer::new(model, sample_s)?,
+            target: Target { setpoint: f64::NAN, x: [0.0; MAX_STATES], u: 0.0 },
+            last_input: 0.0,
+            output_min: config.output_min,
+            output_max: config.output_max,
+        })
+    }
+}
+
+impl ControlLaw for Lqr {
+    fn compute(&mut self, setpoint: f64, measurement: f64, dt: f64) -> f64 {
+        let x = self.observer.update(measurement, self.last_input, dt);
+        self.target.update(&self.model, setpoint);
+
+        let deviation: Vector = std::array::from_fn(|i| x[i] - self.target.x[i]);
+        let u = (self.target.u - dot(&self.gain, &deviation)).clamp(self.output_min, self.output_max);
+        self.last_input = u;
+        u
+    }
+
+    fn name(&self) -> &'static str {
+        "lqr"
+    }
+}
+
+// ============================================================================
+// Horizon-limited MPC
+// ============================================================================
+
+/// min Σₖ q·(yₖ − r)² + ρ·Σⱼ (uⱼ − u_ss)²  s.t. u_min ≤ uⱼ ≤ u_max, over `horizon` steps.
+pub struct Mpc {
+    model: LinearModel,
+    observer: Observer,
+    target: Target,
+    horizon: usize,
+    q: f64,
+    rho: f64,
+    impulse: [f64; MAX_HORIZON],                    // hᵢ = c·Aⁱ·b
+    hessian: [[f64; MAX_HORIZON]; MAX_HORIZON],     // q·GᵀG + ρ·I
+    step_size: f64,                                 // 1 / Lipschitz bound of the gradient
+    plan: [f64; MAX_HORIZON],                       // Last solution (warm start)
+    last_input: f64,
+    output_min: f64,
+    output_max: f64,
+}
+
+impl Mpc {
+    pub fn new(config: &LawConfig, model: LinearModel, sample_s: f64) -> Option<Self> {
+        let horizon = config.horizon.clamp(1, MAX_HORIZON);
+        let q = config.q_output;
+        let rho = config.r_input.max(1e-9);
+
+        let mut impulse = [0.0; MAX_HORIZON];
+        let mut ab = model.b;
+        for h in impulse.iter_mut().take(horizon) {
+            *h = dot(&model.c, &ab);
+            ab = mat_vec(&model.a, &ab);
+        }
+
+        // y_{k+1} depends on u_j (j ≤ k) through G[k][j] = h_{k−j}
+        let mut hessian = [[0.0; MAX_HORIZON]; MAX_HORIZON];
+        for (i, row) in hessian.iter_mut().enumerate().take(horizon) {
+            for (j, cell) in row.iter_mut().enumerate().take(horizon) {
+                let lo = i.max(j);
+                *cell = q * (lo..horizon).map(|k| impulse[k - i] * impulse[k - j]).sum::<f64>();
+            }
+            row[i] += rho;
+        }
+        // Gershgorin bound on the largest eigenvalue
+        let lipschitz = hessian.iter().map(|row| row.iter().map(|v| v.abs()).sum::<f64>()).fold(0.0, f64::max);
+        if !lipschitz.is_finite() || lipschitz <= 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            model,
+            observer: Observer::new(model, sample_s)?,
+            target: Target { setpoint: f64::NAN, x: [0.0; MAX_STATES], u: 0.0 },
+            horizon,
+            q,
+            rho,
+            impulse,
+            hessian,
+            step_size: 1.0 / lipschitz,
+            plan: [0.0; MAX_HORIZON],
+            last_input: 0.0,
+            output_min: config.output_min,
+            output_max: config.output_max,
+        })
+    }
+}
+
+impl ControlLaw for Mpc {
+    fn compute(&mut self, setpoint: f64, measurement: f64, dt: f64) -> f64 {
+        let n = self.horizon;
+        let mut x = self.observer.update(measurement, self.last_input, dt);
+        self.target.update(&self.model, setpoint);
+
+        // Free response error (no future input): e_k = c·A^(k+1)·x̂ + y0 − r
+        let mut error = [0.0; MAX_HORIZON];
+        for e in error.iter_mut().take(n) {
+            x = mat_vec(&self.model.a, &x);
+            *e = dot(&self.model.c, &x) + self.model.y0 - setpoint;
+        }
+
+        // Linear term of the gradient: q·Gᵀe − ρ·u_ss
+        let mut linear = [0.0; MAX_HORIZON];
+        for (j, l) in linear.iter_mut().enumerate().take(n) {
+            *l = self.q * (j..n).map(|k| self.impulse[k - j] * error[k]).sum::<f64>() - self.rho * self.target.u;
+        }
+
+        // Warm start: previous plan shifted by one step
+        self.plan.copy_within(1..n, 0);
+        let mut u = self.plan;
+        let mut z = u;
+        let mut t = 1.0_f64;
+        for _ in 0..MPC_ITERATIONS {
+            let mut next = [0.0; MAX_HORIZON];
+            for i in 0..n {
+                let grad = (0..n).map(|j| self.hessian[i][j] * z[j]).sum::<f64>() + linear[i];
+                next[i] = (z[i] - self.step_size * grad).clamp(self.output_min, self.output_max);
+            }
+            let t_next = (1.0 + (1.0 + 4.0 * t * t).sqrt()) / 2.0;
+            let momentum = (t - 1.0) / t_next;
+            for i in 0..n {
+                z[i] = next[i] + momentum * (next[i] - u[i]);
+            }
+            u = next;
+            t = t_next;
+        }
+
+        self.plan = u;
+        self.last_input = u[0];
+        u[0]
+    }
+
+    fn name(&self) -> &'static str {
+        "mpc"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(v: f64) -> Mat {
+        let mut m = [[0.0; MAX_STATES]; MAX_STATES];
+        m[0][0] = v;
+        m
+    }
+
+    /// Positive root of b²P² + (r(1 − a²) − q·b²)·P − q·r = 0 (the scalar Riccati equation).
+    fn scalar_riccati(a: f64, b: f64, q: f64, r: f64) -> f64 {
+        let linear = r * (1.0 - a * a) - q * b * b;
+        (-linear + (linear * linear + 4.0 * b * b * q * r).sqrt()) / (2.0 * b * b)
+    }
+
+    #[test]
+    fn dare_matches_the_scalar_closed_form() {
+        // (a, b, q, r): integrator (P = golden ratio), stable lag, unstable pole, cheap input
+        for (a, b, q, r) in [(1.0, 1.0, 1.0, 1.0), (0.9, 0.5, 2.0, 0.1), (2.0, 1.0, 1.0, 1.0), (0.5, 3.0, 1.0, 1e-3)] {
+            let mut input = [0.0; MAX_STATES];
+            input[0] = b;
+            let p = dare(&scalar(a), &input, &scalar(q), r).expect("converges");
+            let expected = scalar_riccati(a, b, q, r);
+            assert!((p[0][0] - expected).abs() <= 1e-8 * expected, "a {} b {}: P {} vs {}", a, b, p[0][0], expected);
+            // Padding states stay zero
+            assert!(p.iter().flatten().skip(1).all(|v| *v == 0.0));
+        }
+        assert!((scalar_riccati(1.0, 1.0, 1.0, 1.0) - (1.0 + 5.0_f64.sqrt()) / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn dare_rejects_an_unstabilisable_system() {
+        // Unstable pole the input cannot reach: P grows without bound
+        assert!(dare(&scalar(2.0), &[0.0; MAX_STATES], &scalar(1.0), 1.0).is_none());
+    }
+
+    #[test]
+    fn steady_state_of_a_first_order_lag() {
+        let model = PlantSpec::FirstOrderLag { gain: 4.0, tau_s: 0.2 }.linear().discretize(CONTROL_SAMPLE_S);
+        let (x, u) = steady_state(&model, 30.0).expect("equilibrium");
+        assert!((u - 7.5).abs() < 1e-9 && (x[0] - 30.0).abs() < 1e-9, "x {:?} u {}", x, u);
+        // An equilibrium indeed: x⁺ = x
+        assert!((step(&model, &x, u)[0] - x[0]).abs() < 1e-9);
+
+        // Output offset: 25 °C is 5 °C above the 20 °C ambient, held by 10 W
+        let thermal = PlantSpec::ThermalRc { resistance: 0.5, capacitance: 4.0, ambient: 20.0 }
+            .linear()
+            .discretize(CONTROL_SAMPLE_S);
+        let (x, u) = steady_state(&thermal, 25.0).expect("equilibrium");
+        assert!((u - 10.0).abs() < 1e-9 && (x[0] - 5.0).abs() < 1e-9, "x {:?} u {}", x, u);
+    }
+
+    #[test]
+    fn steady_state_without_an_input_path_is_none() {
+        let mut model = PlantSpec::FirstOrderLag { gain: 4.0, tau_s: 0.2 }.linear().discretize(CONTROL_SAMPLE_S);
+        model.b = [0.0; MAX_STATES];
+        assert!(steady_state(&model, 1.0).is_none());
+    }
+
+    #[test]
+    fn mpc_respects_its_limits_and_settles_on_an_exact_model() {
+        let config = LawConfig { law: LawKind::Mpc, output_min: -10.0, output_max: 10.0, ..LawConfig::default() };
+        let model = PlantSpec::FirstOrderLag { gain: 4.0, tau_s: 0.2 }.linear().discretize(CONTROL_SAMPLE_S);
+        let mut mpc = Mpc::new(&config, model, CONTROL_SAMPLE_S).expect("finite design");
+
+        // 30 needs u = 7.5; from rest the first commands saturate at the upper limit
+        let (setpoint, mut x, mut saturated) = (30.0, [0.0; MAX_STATES], 0);
+        let mut u = 0.0;
+        for _ in 0..400 {
+            u = mpc.compute(setpoint, x[0], CONTROL_SAMPLE_S);
+            assert!((config.output_min..=config.output_max).contains(&u), "command {}", u);
+            saturated += usize::from(u == config.output_max);
+            x = step(&model, &x, u);
+        }
+
+        assert!(saturated > 0);
+        assert!((x[0] - setpoint).abs() < 1e-3, "output {}", x[0]);
+        assert!((u - 7.5).abs() < 1e-3, "command {}", u);
+    }
+}
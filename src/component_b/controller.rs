@@ -12,33 +12,101 @@
 //! REQUIREMENT 1: Virtual actuator responding to sensor inputs (grip, motor, stabilizer correction).
 //! REQUIREMENT 2: PID control algorithm (Kp=1.2, Ki=0.01, Kd=0.2, anti-windup).
 //! REQUIREMENT 3: Real-time scheduling (2 ms deadline enforcement, deadline miss tracking).
+//! Setpoints (`with_setpoint`): one generator per sensor (`setpoint.rs`), evaluated on the time
+//! since the controller started; the active setpoint is logged with each `ControllerComplete`.
+//! Primary sensor (`with_primary`, actuators): with several routed sensors (fan-in) only the
+//! primary's packets set the target, run the law and feed the quality analysis; packets from the
+//! others hold the last command.
+//! Control quality (`with_quality_tracking`, actuators): every control instant feeds the
+//! step-response analyzer (`control_quality.rs`) and is logged as a `ControlSample` event, each
+//! setpoint jump as a `SegmentStart`; the running figures go to the live metrics.
+//! Remote commands: setpoint overrides and PID gain changes arrive on the bus `commands` topic
+//! (e.g. from the MQTT bridge) and are applied between packets. A gain change can target one
+//! controller by name; it is applied bumplessly and recorded as a `ParamChange` event.
+//! Control period (`with_sample_time`): the law runs at most once per period; in between the
+//! last command holds.
+//! Auto-tune (`with_autotune`, or an `Autotune` command): a relay experiment takes over the
+//! command until it has identified the loop (`autotune.rs`); the proposed gains are published
+//! to the live metrics and, if configured, applied like a tuning command.
+//! Control law (`with_law`): PID by default; bang-bang, LQR or MPC per actuator
+//! (`control_law.rs`). Each computation is timed against the 2 ms deadline.
+//! Plant (optional, `with_plant`): the control signal becomes the plant input (absolute
+//! command) instead of an increment integrated into the virtual actuator state.
+//! Actuator dynamics (optional, `with_dynamics`): the command passes through the actuator
+//! model (`actuator_model.rs`) before it reaches the state or the plant; the onsets of
+//! saturation and rate limiting are reported as feedback and counted, and the limited command
+//! goes back to the law for anti-windup.
+//! Fault injection (optional, `with_faults`): the state the model reaches passes through the
+//! actuator's fault scenarios (`actuator_fault.rs`); `expected_state` keeps the healthy one.
 
 use std::{
     sync::Arc, 
     time::Instant, 
 }; 
 
-use pidgeon::{ControllerConfig, PidController};
-
 use crate::component_a::{
     processor::ProcessedPacket,
-    sensor::SensorType,
+    sensor::{SensorType, SENSOR_COUNT},
     sync_manager::SyncManager,
 };
+use crate::component_b::actuator_fault::FaultInjector;
+use crate::component_b::actuator_model::ActuatorModel;
+use crate::component_b::autotune::{AutotuneResult, AutotuneSetup, RelayExperiment, TuningRule};
+use crate::component_b::control_law::{ControlLaw, LawConfig, Pid};
 use crate::component_b::feedback::{FeedbackLoop, FeedbackKind};
-use crate::utils::metrics::{SharedMetrics, push_capped, EventRecorder, Event};
+use crate::component_b::plant::PlantInput;
+use crate::component_b::setpoint::{SetpointGenerator, SetpointSpec};
+use crate::utils::metrics::{SharedMetrics, EventRecorder, Event};
+use crate::utils::bus::{OverflowPolicy, Subscription, Topic};
+use crate::utils::control_quality::QualityTracker;
+
+
+const GAIN_NAMES: [&str; 3] = ["kp", "ki", "kd"];
+const SAMPLE_SLACK: f64 = 0.9;      // A packet this close to the next control instant runs the law
 
-/// Virtual actuator controller: maintains state, computes PID control signals.
+/// Remote control command (bus `commands` topic).
+#[derive(Debug, Clone)]
+pub enum ControlCommand {
+    Setpoint { sensor: SensorType, value: Option<f64> },                // Inject a target; None = resume the profile
+    Tuning {                                                            // Change any subset of gains
+        target: Option<&'static str>,                                   // Controller name (receiver or actuator); None = all
+        kp: Option<f64>,
+        ki: Option<f64>,
+        kd: Option<f64>,
+    },
+    Autotune {                                                          // Start a relay experiment
+        target: Option<&'static str>,                                   // Controller name; None = all with a plant
+        rule: Option<TuningRule>,                                       // None = configured rule
+    },
+}
+
+/// Virtual actuator controller: maintains state, computes control signals.
 pub struct Controller {
-    pid: PidController,
-    last_update: Instant,
+    name: &'static str,                     // Tuning target and `ParamChange` label
+    law: Box<dyn ControlLaw>,
+    sample_s: f64,                          // Control period; 0 = every packet
+    last_update: Instant,                   // Last law computation
+    last_output: f64,                       // Held between control instants
+    started: Instant,                       // Time origin of the setpoint profiles
     current_target: f64,
-    actuator_state: f64,      // Virtual actuator state (integration of control signals)
+    actuator_state: f64,      // Virtual actuator state (integration of control signals; plant command with a plant)
     sync: Arc<SyncManager>,
     deadline_us: u64,
     feedback: FeedbackLoop,
     metrics: SharedMetrics,
     event_recorder: Arc<EventRecorder>,
+    commands: Option<Subscription<ControlCommand>>,
+    setpoints: [SetpointGenerator; SENSOR_COUNT], // Per-sensor targets (by `SensorType::index`)
+    primary: Option<SensorType>,            // Only sensor that drives the law; None = every sensor
+    plant: Option<PlantInput>,              // Driven plant; None = integrate into `actuator_state`
+    autotune_setup: AutotuneSetup,          // Settings for experiments started by command
+    autotune: Option<RelayExperiment>,      // Running experiment
+    quality: Option<QualityTracker>,        // Step-response analysis of the tracked setpoint
+    dynamics: Option<ActuatorModel>,        // Actuator model; None = the command is reached at once
+    saturated: bool,                        // Limits hit by the last command (onsets are reported)
+    rate_limited: bool,
+    faults: Option<FaultInjector>,          // Injected actuator faults; None = healthy actuator
+    expected_state: f64,                    // State a healthy actuator would have reached
 }
 
 impl Controller {
@@ -51,16 +119,13 @@ impl Controller {
         event_recorder: Arc<EventRecorder>,
     ) -> Self {
         // PID configuration: Kp=1.2 (proportional), Ki=0.01 (integral), Kd=0.2 (derivative)
-        let config = ControllerConfig::new()
-            .with_kp(1.2)
-            .with_ki(0.01)
-            .with_kd(0.2)
-            .with_output_limits(-50.0, 50.0)
-            .with_anti_windup(true);//prevents runaway intergral term
-
         Self {
-            pid: PidController::new(config),
+            name: "Controller",
+            law: Box::new(Pid::new(&LawConfig::default())),
+            sample_s: 0.0,
             last_update: Instant::now(),
+            last_output: 0.0,
+            started: Instant::now(),
             current_target: 0.0,
             actuator_state: 0.0,
             sync,
@@ -68,7 +133,88 @@ impl Controller {
             feedback,
             metrics,
             event_recorder,
+            commands: None,
+            setpoints: SensorType::ALL.map(|sensor| SetpointSpec::default_for(sensor).build(sensor)),
+            primary: None,
+            plant: None,
+            autotune_setup: AutotuneSetup::default(),
+            autotune: None,
+            quality: None,
+            dynamics: None,
+            saturated: false,
+            rate_limited: false,
+            faults: None,
+            expected_state: 0.0,
+        }
+    }
+
+    /// Names the controller (`RECEIVER` or the actuator's route) for targeted tuning and
+    /// parameter-change events.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Runs the law at most once per `sample_s` (0 = on every packet).
+    pub fn with_sample_time(mut self, sample_s: f64) -> Self {
+        self.sample_s = sample_s.max(0.0);
+        self
+    }
+
+    /// Replaces the default PID with another control law.
+    pub fn with_law(mut self, law: Box<dyn ControlLaw>) -> Self {
+        self.law = law;
+        self
+    }
+
+    /// Tracks `setpoint` on packets from `sensor` (nominal constant targets otherwise).
+    pub fn with_setpoint(mut self, sensor: SensorType, setpoint: SetpointGenerator) -> Self {
+        self.setpoints[sensor.index()] = setpoint;
+        self
+    }
+
+    /// Controls on packets from `sensor` only; packets from other routed sensors hold the command.
+    pub fn with_primary(mut self, sensor: SensorType) -> Self {
+        self.primary = Some(sensor);
+        self
+    }
+
+    /// Drives `plant` with the control signal (closed loop through the sensor).
+    pub fn with_plant(mut self, plant: PlantInput) -> Self {
+        self.plant = Some(plant);
+        self
+    }
+
+    /// Passes every command through `model` (saturation, rate limit, dead band, backlash, lag, delay).
+    pub fn with_dynamics(mut self, model: ActuatorModel) -> Self {
+        self.dynamics = Some(model);
+        self
+    }
+
+    /// Injects the actuator's fault scenarios after its dynamics.
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Relay auto-tuning settings; starts an experiment right away when `enabled`.
+    pub fn with_autotune(mut self, setup: AutotuneSetup) -> Self {
+        self.autotune_setup = setup;
+        if setup.config.enabled {
+            self.autotune = Some(RelayExperiment::new(&setup));
         }
+        self
+    }
+
+    /// Analyses how well the controller tracks its setpoint (see `control_quality.rs`).
+    pub fn with_quality_tracking(mut self) -> Self {
+        self.quality = Some(QualityTracker::default());
+        self
+    }
+
+    /// Accepts setpoint/tuning commands from the bus `commands` topic.
+    pub fn subscribe_commands(&mut self, commands: &Topic<ControlCommand>) {
+        self.commands = Some(commands.subscribe(self.name, 16, OverflowPolicy::DropOldest));
     }
 
     pub fn record_rx_latency(&self, latency_us: u64) {
@@ -80,16 +226,26 @@ impl Controller {
         self.actuator_state
     }
 
+    /// State the last command reaches on a healthy actuator (equals `current_state` without
+    /// an injected fault).
+    #[inline]
+    pub fn expected_state(&self) -> f64 {
+        self.expected_state
+    }
+
     /// Process sensor packet: compute PID control signal, update actuator state.
     /// Detects sensor anomalies, enforces 2 ms deadline, emits feedback events.
     pub fn handle_packet(&mut self, pkt: &ProcessedPacket) {
         let cycle_start = Instant::now();
+        self.apply_commands(cycle_start, pkt.seq);
 
-        // Compute time step (clamped for stability)
+        // Time since the last computation; below the control period the command holds, as it
+        // does for packets from a sensor other than the primary (one target and one law)
         let now = Instant::now();
-        let mut dt = now.duration_since(self.last_update).as_secs_f64();
-        self.last_update = now;
-        dt = dt.clamp(1e-6, 0.05);//clamped btwn 1e-6 to 0.05 for stability
+        let since_update = now.duration_since(self.last_update).as_secs_f64();
+        let tracked = self.primary.is_none_or(|sensor| sensor == pkt.sensor_type);
+        let due = tracked && since_update >= self.sample_s * SAMPLE_SLACK;
+        let dt = since_update.clamp(1e-6, 0.05 + self.sample_s);//clamped btwn 1e-6 and 50 ms past the period for stability
 
         // ====================================================================
         // REQUIREMENT 1: Virtual Actuator Responding to Sensor Input
@@ -104,16 +260,15 @@ impl Controller {
             );
         }
 
-        // Update setpoint based on sensor type (e.g, grip force, position correction)
+        // Setpoint of this sensor's profile (or the injected one) at the current run time
         // ensures the actuator reacts differently
-        let new_target = match pkt.sensor_type {
-            SensorType::Force => 100.0,
-            SensorType::Position => 0.0,
-            SensorType::Temperature => 25.0,
-        };
+        let t_s = now.duration_since(self.started).as_secs_f64();
+        let new_target = if tracked { self.setpoints[pkt.sensor_type.index()].value(t_s) } else { self.current_target };
 
         if (new_target - self.current_target).abs() > f64::EPSILON {
-            if let Err(_) = self.pid.set_setpoint(new_target) {
+            if new_target.is_finite() {
+                self.current_target = new_target;
+            } else {
                 self.sync.record_proc_miss();
                 self.feedback.emit(
                     "Controller",
@@ -121,21 +276,61 @@ impl Controller {
                     cycle_start,
                 );
             }
-            self.current_target = new_target;
         }
       
 
         // ====================================================================
-        // REQUIREMENT 2: Predictive Control Algorithm (PID)
+        // REQUIREMENT 2: Predictive Control Algorithm (PID or configured law)
         // ====================================================================
-        // Compute PID control signal: adjusts actuation dynamically
-        let control_signal = self.pid.compute(pkt.filtered, dt);
+        // Compute control signal: adjusts actuation dynamically; compute time vs. deadline
+        let control_signal = if due {
+            self.last_update = now;
 
-        // ====================================================================
-        // REQUIREMENT 1: Virtual Actuator State Integration
-        // ====================================================================
-        // Apply control signal to virtual actuator (simulates grip, motor, stabilizer)
-        self.apply_to_actuator(control_signal);
+            // Relay experiment (auto-tune) overrides the law until it has identified the loop
+            let mut relay = None;
+            if let Some(experiment) = &mut self.autotune {
+                relay = experiment.command(self.current_target, pkt.filtered, dt);
+                if let Some(result) = experiment.take_result() {
+                    let apply = experiment.config().apply;
+                    self.autotune = None;
+                    self.finish_autotune(result, apply, pkt.seq);
+                }
+            }
+
+            let from_law = relay.is_none();
+            let control_signal = match relay {
+                Some(command) => command,
+                None => {
+                    let law_start = Instant::now();
+                    let control_signal = self.law.compute(self.current_target, pkt.filtered, dt);
+                    let compute_ns = law_start.elapsed().as_nanos() as u64;
+                    self.metrics
+                        .live()
+                        .record_law(self.law.name(), compute_ns, compute_ns > self.deadline_us * 1_000);
+                    if let Some(experiment) = &mut self.autotune {
+                        experiment.observe(control_signal, dt);
+                    }
+                    control_signal
+                }
+            };
+            self.track_quality(pkt);
+
+            // ====================================================================
+            // REQUIREMENT 1: Virtual Actuator State Integration
+            // ====================================================================
+            // Apply control signal to virtual actuator (simulates grip, motor, stabilizer)
+            // With a plant the signal is the command itself; the plant integrates it
+            if self.plant.is_some() {
+                self.command(control_signal);
+            } else {
+                self.apply_to_actuator(control_signal);
+            }
+            self.report_limits(control_signal, from_law, cycle_start);
+            self.last_output = control_signal;
+            control_signal
+        } else {
+            self.last_output
+        };
 
         // T4: ControllerComplete event (after control computation)
         let exec_us = cycle_start.elapsed().as_micros() as u64;
@@ -145,6 +340,7 @@ impl Controller {
             ts_ns: t4_ns,
             control_output: control_signal,
             exec_us,
+            setpoint: self.current_target,
         });
 
         // ====================================================================
@@ -168,6 +364,152 @@ impl Controller {
         }
     }
 
+    /// Drains pending remote commands (non-blocking); `seq` is the packet about to be computed.
+    fn apply_commands(&mut self, cycle_start: Instant, seq: u64) {
+        let Some(commands) = &self.commands else { return };
+
+        let mut failed = false;
+        while let Ok(cmd) = commands.try_recv() {
+            match cmd {
+                ControlCommand::Setpoint { sensor, value } => {
+                    self.setpoints[sensor.index()].inject(value);
+                }
+                ControlCommand::Tuning { target, kp, ki, kd } => {
+                    if target.is_some_and(|target| target != self.name) {
+                        continue;
+                    }
+                    let old = self.law.gains();
+                    if !self.law.tune(kp, ki, kd) {
+                        failed = true;
+                        continue;
+                    }
+                    self.record_gain_changes(old, seq);
+                }
+                ControlCommand::Autotune { target, rule } => {
+                    if target.is_some_and(|target| target != self.name) {
+                        continue;
+                    }
+                    // The relay needs a loop of its own: a controller without a plant (the
+                    // receiver, a second actuator on a shared sensor) would only disturb the
+                    // others. Skipped when broadcast, rejected when targeted.
+                    if self.plant.is_none() {
+                        if target.is_some() {
+                            self.feedback.emit("Controller", FeedbackKind::Error("autotune_no_plant"), cycle_start);
+                        }
+                        continue;
+                    }
+                    let mut setup = self.autotune_setup;
+                    setup.config.rule = rule.unwrap_or(setup.config.rule);
+                    self.autotune = Some(RelayExperiment::new(&setup));
+                }
+            }
+        }
+
+        if failed {
+            self.feedback.emit(
+                "Controller",
+                FeedbackKind::Error("pid_config_failed"),
+                cycle_start,
+            );
+        }
+    }
+
+    /// Feeds one control instant to the quality tracker; logs segment starts and samples.
+    fn track_quality(&mut self, pkt: &ProcessedPacket) {
+        let Some(quality) = &mut self.quality else { return };
+        let ts_ns = self.event_recorder.now_ns();
+        let t_s = ts_ns as f64 / 1e9;
+        let setpoint = self.current_target;
+        let continuous = self.setpoints[pkt.sensor_type.index()].continuous();
+
+        if let Some(kind) = quality.next_segment(continuous, setpoint) {
+            quality.start(kind, t_s);
+            self.event_recorder.record(Event::SegmentStart {
+                seq: pkt.seq,
+                ts_ns,
+                controller: self.name,
+                kind,
+                setpoint,
+            });
+        }
+        quality.sample(t_s, setpoint, pkt.filtered);
+        self.event_recorder.record(Event::ControlSample {
+            seq: pkt.seq,
+            ts_ns,
+            controller: self.name,
+            setpoint,
+            measurement: pkt.filtered,
+        });
+        self.metrics.live().record_control_quality(self.name, quality.snapshot());
+    }
+
+    /// Closes the running quality segment and publishes the final figures (end of run).
+    pub fn finish_quality(&mut self) {
+        let Some(quality) = &mut self.quality else { return };
+        quality.finish();
+        self.metrics.live().record_control_quality(self.name, quality.snapshot());
+    }
+
+    /// After a command through the actuator model: feedback and counters on the onset of
+    /// saturation or rate limiting; the law learns what the actuator followed (anti-windup).
+    fn report_limits(&mut self, control_signal: f64, from_law: bool, cycle_start: Instant) {
+        let Some(model) = &self.dynamics else { return };
+        let (saturated, rate_limited) = (model.saturated(), model.rate_limited());
+        let (requested, excess) = (model.command(), model.excess());
+
+        if from_law && (saturated || rate_limited) {
+            self.law.actuator_limited(control_signal + excess);
+        }
+        if saturated && !self.saturated {
+            self.metrics.live().record_saturation(self.name);
+            self.feedback.emit(self.name, FeedbackKind::Saturated(requested), cycle_start);
+        }
+        if rate_limited && !self.rate_limited {
+            self.metrics.live().record_rate_limit(self.name);
+            self.feedback.emit(self.name, FeedbackKind::RateLimited(requested), cycle_start);
+        }
+        self.saturated = saturated;
+        self.rate_limited = rate_limited;
+    }
+
+    /// Publishes the experiment's result; applies the gains if asked and the law accepts them.
+    fn finish_autotune(&mut self, mut result: AutotuneResult, apply: bool, seq: u64) {
+        if result.converged && apply {
+            let [kp, ki, kd] = result.gains;
+            let old = self.law.gains();
+            result.applied = self.law.tune(Some(kp), Some(ki), Some(kd));
+            if result.applied {
+                self.record_gain_changes(old, seq);
+            }
+        }
+        self.metrics.live().record_autotune(self.name, result);
+    }
+
+    /// One `ParamChange` event per gain that differs from `old`.
+    fn record_gain_changes(&self, old: Option<[f64; 3]>, seq: u64) {
+        let (Some(old), Some(new)) = (old, self.law.gains()) else { return };
+        let ts_ns = self.event_recorder.now_ns();
+        for ((param, old), new) in GAIN_NAMES.into_iter().zip(old).zip(new) {
+            if old != new {
+                self.event_recorder.record(Event::ParamChange {
+                    seq,
+                    ts_ns,
+                    controller: self.name,
+                    param,
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+
+    /// Stale-command fallback: moves actuator state toward `safe_value` by at most `max_step`.
+    /// Bypasses the PID (no new measurement to act on).
+    pub fn ramp_toward(&mut self, safe_value: f64, max_step: f64) {
+        let step = (safe_value - self.actuator_state).clamp(-max_step, max_step);
+        self.command(self.actuator_state + step);
+    }
+
     pub fn get_sync(&self) -> &Arc<SyncManager> {
         &self.sync
     }
@@ -176,16 +518,29 @@ impl Controller {
     /// Simulates physical response (grip, motor, stabilizer correction).
     /// Updates metrics for monitoring.
     fn apply_to_actuator(&mut self, control_signal: f64) {
-        self.actuator_state += control_signal;
+        self.command(self.actuator_state + control_signal);
+    }
 
-        {
-            let mut m = match self.metrics.lock() {
-                Ok(guard) => guard,
-                Err(poisoned) => poisoned.into_inner(),
-            };
-            
-            push_capped(&mut m.gripper, self.actuator_state);
-            push_capped(&mut m.motor, self.actuator_state);
+    /// Sets the actuator state (and the plant input, if any) to what the actuator model reaches
+    /// toward `state`; updates metrics for monitoring.
+    fn command(&mut self, state: f64) {
+        let now = Instant::now();
+        self.expected_state = match &mut self.dynamics {
+            Some(model) => model.apply(state, now),
+            None => state,
+        };
+        self.actuator_state = match &mut self.faults {
+            Some(faults) => faults.apply(self.expected_state, now),
+            None => self.expected_state,
+        };
+        if let Some(plant) = &self.plant {
+            // Closed loop: the actuator thread publishes the command to its own series
+            plant.set(self.actuator_state);
+            return;
         }
+
+        let live = self.metrics.live();
+        live.gripper.push(self.actuator_state);
+        live.motor.push(self.actuator_state);
     }
 }
\ No newline at end of file
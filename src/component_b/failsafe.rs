@@ -0,0 +1,505 @@
+//! failsafe.rs
+//! Fail-safe mode manager: watchdogs, system mode state machine, safe action per actuator.
+//!
+//! - Modes: `init` (until every sensor and actuator has been heard from, or `init_timeout_s`),
+//!   `nominal`, `degraded` (a watchdog is late), `safe_stop` (a watchdog is lost) and
+//!   `recovering` (every watchdog healthy again). Recovery is hysteretic: `recovering` becomes
+//!   `nominal` only after `recovery_s` without a fault, and falls back to the mode it left on
+//!   any fault; `safe_stop` is left only once everything is healthy.
+//! - Watchdogs: sensor heartbeats (every release), processor overrun streak, actuator
+//!   staleness (time since the last fresh command, per routed actuator), feedback error rate
+//!   (bus `feedback` topic). RT threads only store atomics (`Watchdogs`); the supervisor thread
+//!   evaluates them every `tick_ms`, drives the state machine and publishes the mode.
+//! - Safe action per actuator role (`[failsafe.<role>]`): `continue`, `hold` the last command,
+//!   or `ramp` toward a safe value; `degraded` applies while degraded or recovering, `safe_stop`
+//!   in safe stop. Actuators read the mode with one atomic load per packet.
+//! - Every transition is logged, recorded as a `ModeChange` event and published to the live
+//!   metrics (dashboard, run summary).
+
+use std::{
+    sync::{Arc, atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicU64, Ordering}},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::component_a::sensor::{SensorType, SENSOR_COUNT};
+use crate::component_b::feedback::{Feedback, FeedbackKind};
+use crate::component_b::routing::ActuatorRole;
+use crate::utils::bus::Subscription;
+use crate::utils::metrics::{Event, EventRecorder, SharedMetrics};
+
+pub const MODE_COUNT: usize = 5;
+
+const ERROR_RATE_TAU_S: f64 = 1.0;  // Smoothing of the feedback error rate
+
+/// Operating mode of the whole pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum SystemMode {
+    #[default]
+    Init,
+    Nominal,
+    Degraded,
+    SafeStop,
+    Recovering,
+}
+
+impl SystemMode {
+    pub const ALL: [SystemMode; MODE_COUNT] =
+        [SystemMode::Init, SystemMode::Nominal, SystemMode::Degraded, SystemMode::SafeStop, SystemMode::Recovering];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SystemMode::Init => "init",
+            SystemMode::Nominal => "nominal",
+            SystemMode::Degraded => "degraded",
+            SystemMode::SafeStop => "safe_stop",
+            SystemMode::Recovering => "recovering",
+        }
+    }
+
+    #[inline]
+    fn from_u8(value: u8) -> Self {
+        Self::ALL.get(value as usize).copied().unwrap_or(SystemMode::SafeStop)
+    }
+}
+
+/// What an actuator does instead of (or while) controlling, tagged by `action`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SafeAction {
+    Continue,                               // Keep controlling
+    Hold,                                   // Keep the last command
+    Ramp { value: f64, max_step: f64 },     // Move the command toward `value` by ≤ `max_step` per packet
+}
+
+/// `[failsafe.<actuator>]`: the actuator's action per mode.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct SafeActions {
+    pub degraded: SafeAction,       // Degraded and recovering
+    pub safe_stop: SafeAction,
+}
+
+impl Default for SafeActions {
+    fn default() -> Self {
+        Self { degraded: SafeAction::Continue, safe_stop: SafeAction::Hold }
+    }
+}
+
+impl SafeActions {
+    #[inline]
+    pub fn for_mode(&self, mode: SystemMode) -> SafeAction {
+        match mode {
+            SystemMode::Init | SystemMode::Nominal => SafeAction::Continue,
+            SystemMode::Degraded | SystemMode::Recovering => self.degraded,
+            SystemMode::SafeStop => self.safe_stop,
+        }
+    }
+}
+
+/// `[failsafe]`: watchdog limits, recovery hysteresis and safe actions.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FailSafeConfig {
+    pub enabled: bool,              // false = no supervisor; the actuators always control
+    pub tick_ms: u64,               // Watchdog evaluation period
+    pub init_timeout_s: f64,        // Longest `init` before missing heartbeats count as faults
+    pub sensor_late_ms: u64,        // Sensor silent this long → degraded
+    pub sensor_lost_ms: u64,        // ... → safe stop
+    pub overrun_degraded: u32,      // Consecutive processor overruns → degraded
+    pub overrun_critical: u32,      // ... → safe stop
+    pub actuator_stale_ms: u64,     // No fresh command at an actuator for this long → degraded
+    pub actuator_lost_ms: u64,      // ... → safe stop
+    pub feedback_errors_per_s: f64, // Error feedback rate (smoothed over ~1 s) → degraded
+    pub recovery_s: f64,            // Healthy this long in `recovering` → nominal
+    pub gripper: SafeActions,
+    pub motor: SafeActions,
+    pub stabiliser: SafeActions,
+}
+
+impl Default for FailSafeConfig {
+    /// Gripper keeps its grip, motor and stabiliser ramp their command to 0 (stop, heater off).
+    fn default() -> Self {
+        let ramp_to_zero = SafeAction::Ramp { value: 0.0, max_step: 1.0 };
+        Self {
+            enabled: true,
+            tick_ms: 10,
+            init_timeout_s: 2.0,
+            sensor_late_ms: 25,
+            sensor_lost_ms: 200,
+            overrun_degraded: 5,
+            overrun_critical: 50,
+            actuator_stale_ms: 50,
+            actuator_lost_ms: 250,
+            feedback_errors_per_s: 100.0,
+            recovery_s: 1.0,
+            gripper: SafeActions::default(),
+            motor: SafeActions { safe_stop: ramp_to_zero, ..SafeActions::default() },
+            stabiliser: SafeActions { safe_stop: ramp_to_zero, ..SafeActions::default() },
+        }
+    }
+}
+
+impl FailSafeConfig {
+    /// Safe actions of an actuator with `role`.
+    pub fn actions(&self, role: ActuatorRole) -> SafeActions {
+        match role {
+            ActuatorRole::Gripper => self.gripper,
+            ActuatorRole::Motor => self.motor,
+            ActuatorRole::Stabiliser => self.stabiliser,
+        }
+    }
+}
+
+/// Heartbeats and the current mode, shared by the RT threads and the supervisor.
+pub struct Watchdogs {
+    origin: Instant,
+    mode: AtomicU8,
+    sensor_beats: [AtomicU64; SENSOR_COUNT],    // ns since `origin` + 1 of the last release; 0 = never
+    actuator_beats: Box<[(&'static str, AtomicU64)]>, // Last fresh command, by routing table index
+    overrun_streak: AtomicU32,                  // Consecutive processor deadline overruns
+}
+
+impl Watchdogs {
+    /// Starts in `init` when a supervisor will run, in `nominal` otherwise; one actuator
+    /// watchdog per routed actuator (`actuators` in routing table order).
+    pub fn new(enabled: bool, actuators: &[&'static str]) -> Self {
+        let mode = if enabled { SystemMode::Init } else { SystemMode::Nominal };
+        Self {
+            origin: Instant::now(),
+            mode: AtomicU8::new(mode as u8),
+            sensor_beats: std::array::from_fn(|_| AtomicU64::new(0)),
+            actuator_beats: actuators.iter().map(|&name| (name, AtomicU64::new(0))).collect(),
+            overrun_streak: AtomicU32::new(0),
+        }
+    }
+
+    /// Sensor released a sample.
+    #[inline]
+    pub fn sensor_beat(&self, sensor: SensorType) {
+        self.sensor_beats[sensor.index()].store(self.stamp(), Ordering::Relaxed);
+    }
+
+    /// Actuator `index` (routing table order) received a fresh command.
+    #[inline]
+    pub fn actuator_beat(&self, index: usize) {
+        if let Some((_, beat)) = self.actuator_beats.get(index) {
+            beat.store(self.stamp(), Ordering::Relaxed);
+        }
+    }
+
+    #[inline]
+    pub fn set_overrun_streak(&self, streak: u32) {
+        self.overrun_streak.store(streak, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn mode(&self) -> SystemMode {
+        SystemMode::from_u8(self.mode.load(Ordering::Acquire))
+    }
+
+    #[inline]
+    fn stamp(&self) -> u64 {
+        self.origin.elapsed().as_nanos() as u64 + 1
+    }
+
+    fn now_s(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64()
+    }
+
+    /// Seconds since the last beat (since start if never) and whether there was one.
+    fn silence_s(beat: &AtomicU64, now_s: f64) -> (f64, bool) {
+        match beat.load(Ordering::Relaxed) {
+            0 => (now_s, false),
+            stamp => ((now_s - (stamp - 1) as f64 / 1e9).max(0.0), true),
+        }
+    }
+}
+
+/// Watchdog verdict, worst first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Health {
+    Healthy,
+    Late,           // → degraded
+    Lost,           // → safe stop
+}
+
+/// Outcome of one watchdog evaluation: the worst finding and its source.
+#[derive(Debug, Clone, Copy)]
+struct Assessment {
+    health: Health,
+    reason: &'static str,
+    source: &'static str,
+    ready: bool,                    // Every sensor and actuator has been heard from
+}
+
+impl Assessment {
+    fn raise(&mut self, health: Health, reason: &'static str, source: &'static str) {
+        if health > self.health {
+            *self = Self { health, reason, source, ..*self };
+        }
+    }
+}
+
+/// One mode change.
+#[derive(Debug, Clone, Copy)]
+pub struct ModeTransition {
+    pub from: SystemMode,
+    pub to: SystemMode,
+    pub reason: &'static str,       // Watchdog finding (`sensor_lost`, ...) or `healthy`/`started`
+    pub source: &'static str,       // Sensor, actuator, `processor` or `feedback`; `-` if none
+    pub at_s: f64,                  // Since the watchdogs were created
+}
+
+/// Mode and its history so far (live metrics, dashboard, run summary).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModeStatus {
+    pub enabled: bool,
+    pub mode: SystemMode,
+    pub since_s: f64,
+    pub transitions: u64,
+    pub time_in_mode_s: [f64; MODE_COUNT],     // By `SystemMode as usize`
+    pub last: Option<ModeTransition>,
+}
+
+/// Mode state machine (supervisor thread).
+struct ModeManager {
+    status: ModeStatus,
+    resume: SystemMode,             // Where `recovering` falls back to on a fault
+    last_s: f64,
+    init_timeout_s: f64,
+    recovery_s: f64,
+}
+
+impl ModeManager {
+    fn new(config: &FailSafeConfig) -> Self {
+        Self {
+            status: ModeStatus { enabled: true, ..ModeStatus::default() },
+            resume: SystemMode::Degraded,
+            last_s: 0.0,
+            init_timeout_s: config.init_timeout_s,
+            recovery_s: config.recovery_s,
+        }
+    }
+
+    fn update(&mut self, a: Assessment, now_s: f64) -> Option<ModeTransition> {
+        use SystemMode::*;
+
+        let mode = self.status.mode;
+        self.status.time_in_mode_s[mode as usize] += (now_s - self.last_s).max(0.0);
+        self.last_s = now_s;
+
+        let next = match (mode, a.health) {
+            (Init, _) if !a.ready && now_s < self.init_timeout_s => None,
+            (Init, Health::Healthy) => Some(Nominal),
+            (Init, Health::Late) => Some(Degraded),
+            (SafeStop, Health::Lost) => None,
+            (_, Health::Lost) => Some(SafeStop),
+            (Nominal, Health::Late) => Some(Degraded),
+            (Degraded | SafeStop, Health::Healthy) => Some(Recovering),
+            (Recovering, Health::Healthy) => (now_s - self.status.since_s >= self.recovery_s).then_some(Nominal),
+            (Recovering, Health::Late) => Some(self.resume),
+            _ => None,
+        }?;
+
+        let (reason, source) = match (mode, a.health) {
+            (Init, Health::Healthy) => ("started", "-"),
+            (_, Health::Healthy) => ("healthy", "-"),
+            _ => (a.reason, a.source),
+        };
+        if next == Recovering {
+            self.resume = mode;
+        }
+        let transition = ModeTransition { from: mode, to: next, reason, source, at_s: now_s };
+        self.status.mode = next;
+        self.status.since_s = now_s;
+        self.status.transitions += 1;
+        self.status.last = Some(transition);
+        Some(transition)
+    }
+}
+
+/// Evaluates every watchdog against the configured limits.
+fn assess(config: &FailSafeConfig, watchdogs: &Watchdogs, error_rate: f64) -> Assessment {
+    let now_s = watchdogs.now_s();
+    let mut a = Assessment { health: Health::Healthy, reason: "healthy", source: "-", ready: true };
+    let ms = |limit_ms: u64| limit_ms as f64 / 1e3;
+
+    for sensor in SensorType::ALL {
+        let (silence, seen) = Watchdogs::silence_s(&watchdogs.sensor_beats[sensor.index()], now_s);
+        a.ready &= seen;
+        if silence > ms(config.sensor_lost_ms) {
+            a.raise(Health::Lost, "sensor_lost", sensor.name());
+        } else if silence > ms(config.sensor_late_ms) {
+            a.raise(Health::Late, "sensor_late", sensor.name());
+        }
+    }
+
+    for &(actuator, ref beat) in watchdogs.actuator_beats.iter() {
+        let (silence, seen) = Watchdogs::silence_s(beat, now_s);
+        a.ready &= seen;
+        if silence > ms(config.actuator_lost_ms) {
+            a.raise(Health::Lost, "actuator_lost", actuator);
+        } else if silence > ms(config.actuator_stale_ms) {
+            a.raise(Health::Late, "actuator_stale", actuator);
+        }
+    }
+
+    let streak = watchdogs.overrun_streak.load(Ordering::Relaxed);
+    if streak >= config.overrun_critical {
+        a.raise(Health::Lost, "processor_overload", "processor");
+    } else if streak >= config.overrun_degraded {
+        a.raise(Health::Late, "processor_overrun", "processor");
+    }
+
+    if error_rate > config.feedback_errors_per_s {
+        a.raise(Health::Late, "feedback_errors", "feedback");
+    }
+    a
+}
+
+/// Supervisor thread: evaluates the watchdogs every `tick_ms` until `running` clears, switches
+/// the mode and reports each transition (log, `ModeChange` event, live metrics).
+pub fn spawn_supervisor(
+    config: FailSafeConfig,
+    watchdogs: Arc<Watchdogs>,
+    feedback: Subscription<Feedback>,
+    metrics: SharedMetrics,
+    event_recorder: Arc<EventRecorder>,
+    running: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    thread::Builder::new()
+        .name("failsafe".into())
+        .spawn(move || {
+            let tick = Duration::from_millis(config.tick_ms.max(1));
+            let mut manager = ModeManager::new(&config);
+            let mut error_rate = 0.0;
+            let mut last_tick = Instant::now();
+            metrics.live().record_mode(manager.status);
+
+            loop {
+                thread::sleep(tick);
+                if !running.load(Ordering::Acquire) {
+                    break;
+                }
+                let now = Instant::now();
+                let dt = now.duration_since(last_tick).as_secs_f64().max(1e-6);
+                last_tick = now;
+
+                // Error feedback per second, smoothed
+                let mut errors = 0u32;
+                while let Ok(fb) = feedback.try_recv() {
+                    if matches!(fb.kind, FeedbackKind::Error(_)) {
+                        errors += 1;
+                    }
+                }
+                error_rate += (f64::from(errors) / dt - error_rate) * dt / (ERROR_RATE_TAU_S + dt);
+
+                let assessment = assess(&config, &watchdogs, error_rate);
+                if let Some(t) = manager.update(assessment, watchdogs.now_s()) {
+                    watchdogs.mode.store(t.to as u8, Ordering::Release);
+                    report(&t, &event_recorder);
+                }
+                metrics.live().record_mode(manager.status);
+            }
+        })
+        .expect("Failed to spawn failsafe supervisor")
+}
+
+/// Logs one transition (warning when the mode gets worse) and records it as an event.
+fn report(t: &ModeTransition, event_recorder: &EventRecorder) {
+    let message = format!(
+        "[FailSafe] {} → {} at {:.3} s ({}: {})",
+        t.from.name(), t.to.name(), t.at_s, t.reason, t.source
+    );
+    match t.to {
+        SystemMode::Degraded | SystemMode::SafeStop => warn!("{}", message),
+        _ => info!("{}", message),
+    }
+    event_recorder.record(Event::ModeChange {
+        seq: 0,
+        ts_ns: event_recorder.now_ns(),
+        from: t.from,
+        to: t.to,
+        reason: t.reason,
+        source: t.source,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assessment(health: Health) -> Assessment {
+        Assessment { health, reason: "test", source: "-", ready: true }
+    }
+
+    /// Manager past `init` at t = 0 (recovery_s = 1.0 by default).
+    fn nominal() -> ModeManager {
+        let mut manager = ModeManager::new(&FailSafeConfig::default());
+        manager.update(assessment(Health::Healthy), 0.0);
+        assert_eq!(manager.status.mode, SystemMode::Nominal);
+        manager
+    }
+
+    fn mode_after(manager: &mut ModeManager, health: Health, now_s: f64) -> SystemMode {
+        manager.update(assessment(health), now_s);
+        manager.status.mode
+    }
+
+    #[test]
+    fn init_waits_for_every_heartbeat_until_timeout() {
+        let mut manager = ModeManager::new(&FailSafeConfig::default());
+        let not_ready = Assessment { ready: false, ..assessment(Health::Lost) };
+        assert!(manager.update(not_ready, 1.0).is_none());
+        assert_eq!(manager.status.mode, SystemMode::Init);
+
+        // Past init_timeout_s the missing heartbeats count
+        let t = manager.update(not_ready, 2.5).expect("init timed out");
+        assert_eq!((t.from, t.to), (SystemMode::Init, SystemMode::SafeStop));
+    }
+
+    #[test]
+    fn recovery_needs_a_healthy_hold_time() {
+        let mut manager = nominal();
+        assert_eq!(mode_after(&mut manager, Health::Late, 1.0), SystemMode::Degraded);
+        assert_eq!(mode_after(&mut manager, Health::Healthy, 1.1), SystemMode::Recovering);
+
+        // Not before recovery_s without a fault
+        assert_eq!(mode_after(&mut manager, Health::Healthy, 1.5), SystemMode::Recovering);
+        assert_eq!(mode_after(&mut manager, Health::Healthy, 2.05), SystemMode::Recovering);
+        let t = manager.update(assessment(Health::Healthy), 2.1).expect("recovered");
+        assert_eq!((t.from, t.to, t.reason), (SystemMode::Recovering, SystemMode::Nominal, "healthy"));
+    }
+
+    #[test]
+    fn fault_while_recovering_returns_to_the_previous_mode() {
+        let mut manager = nominal();
+        assert_eq!(mode_after(&mut manager, Health::Lost, 1.0), SystemMode::SafeStop);
+        assert_eq!(mode_after(&mut manager, Health::Lost, 1.5), SystemMode::SafeStop);
+        assert_eq!(mode_after(&mut manager, Health::Healthy, 2.0), SystemMode::Recovering);
+
+        // A late watchdog falls back to where recovery started (safe stop), not to degraded
+        assert_eq!(mode_after(&mut manager, Health::Late, 2.5), SystemMode::SafeStop);
+
+        // The hold time restarts with each attempt
+        assert_eq!(mode_after(&mut manager, Health::Healthy, 3.0), SystemMode::Recovering);
+        assert_eq!(mode_after(&mut manager, Health::Healthy, 3.5), SystemMode::Recovering);
+        assert_eq!(mode_after(&mut manager, Health::Healthy, 4.0), SystemMode::Nominal);
+        assert_eq!(manager.status.transitions, 6);
+    }
+
+    #[test]
+    fn time_in_mode_accumulates_per_mode() {
+        let mut manager = nominal();
+        mode_after(&mut manager, Health::Late, 2.0);
+        mode_after(&mut manager, Health::Late, 3.5);
+        let time = manager.status.time_in_mode_s;
+        assert!((time[SystemMode::Nominal as usize] - 2.0).abs() < 1e-9);
+        assert!((time[SystemMode::Degraded as usize] - 1.5).abs() < 1e-9);
+    }
+}
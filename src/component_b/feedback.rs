@@ -4,13 +4,22 @@
 //! REQUIREMENT 1: Send feedback (acks, actuator state, error data) to sensor module.
 //! REQUIREMENT 2: Enable dynamic recalibration/threshold adjustment via feedback.
 //! REQUIREMENT 3: Enforce 0.5 ms feedback deadline.
+//!
+//! With fault detection the actuators also report the state a healthy actuator would have
+//! reached (`CommandedState`); the detector answers with typed `ActuatorFault` feedback.
+//!
+//! Feedback is published on the bus `feedback` topic; consumers (processor, CSV logger, ...)
+//! subscribe there with their own queue instead of sharing one channel.
 
-use crossbeam::channel::{bounded, Sender, Receiver};
 use std::{
     time::Instant,
     sync::Arc,
 };
-use crate::utils::metrics::{EventRecorder, Event};
+use crate::component_b::actuator_fault::ActuatorFault;
+use crate::utils::{
+    bus::{OverflowPolicy, Subscription, Topic},
+    metrics::{EventRecorder, Event},
+};
 
 /// REQUIREMENT 1: Feedback message types (ack, state, error).
 #[derive(Debug, Clone)]
@@ -18,6 +27,10 @@ pub enum FeedbackKind {
     Ack,                          // Acknowledgement of successful actuation
     ActuatorState(f64),           // Current actuator state (for recalibration)
     Error(&'static str),          // Error indicator (threshold adjustment trigger)
+    Saturated(f64),               // Command outside the actuator's limits (requested command)
+    RateLimited(f64),             // Command changing faster than the slew-rate limit (requested command)
+    CommandedState(f64),          // State a healthy actuator reaches (precedes its ActuatorState)
+    ActuatorFault(ActuatorFault), // Detector verdict: commanded and observed response disagree
 }
 
 #[derive(Debug, Clone)]
@@ -29,19 +42,26 @@ pub struct Feedback {
     pub seq: u64,
 }
 
-/// Real-time feedback producer: non-blocking send to Component A.
+/// Real-time feedback producer: non-blocking publish to Component A.
 #[derive(Clone)]
 pub struct FeedbackLoop {
-    tx: Sender<Feedback>,
+    topic: Topic<Feedback>,
     event_recorder: Arc<EventRecorder>,
 }
 
 impl FeedbackLoop {
-    /// bounded feedback channel.
+    /// Standalone loop with a single bounded subscriber (benches, tests).
     /// REQUIREMENT 1: Feedback channel for Component A (sensor/processor).
-    pub fn new(capacity: usize, event_recorder: Arc<EventRecorder>) -> (Self, Receiver<Feedback>) {
-        let (tx, rx) = bounded(capacity);
-        (Self { tx, event_recorder }, rx)
+    #[allow(dead_code)]
+    pub fn new(capacity: usize, event_recorder: Arc<EventRecorder>) -> (Self, Subscription<Feedback>) {
+        let topic = Topic::new("feedback");
+        let rx = topic.subscribe("feedback", capacity, OverflowPolicy::DropNewest);
+        (Self::on_topic(topic, event_recorder), rx)
+    }
+
+    /// Publishes onto a shared bus topic; consumers subscribe to it directly.
+    pub fn on_topic(topic: Topic<Feedback>, event_recorder: Arc<EventRecorder>) -> Self {
+        Self { topic, event_recorder }
     }
 
     /// Emit feedback without blocking (non-blocking try_send).
@@ -90,7 +110,7 @@ impl FeedbackLoop {
             ts_ns: t5_ns,
         });
 
-        // Non-blocking send (real-time safety)
-        let _ = self.tx.try_send(feedback);
+        // Non-blocking publish (real-time safety; each subscriber applies its overflow policy)
+        self.topic.publish(feedback);
     }
 }
\ No newline at end of file
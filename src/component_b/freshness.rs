@@ -0,0 +1,162 @@
+//! freshness.rs
+//! End-to-end freshness deadlines for actuator commands.
+//!
+//! - Age is measured from sensor release (`ProcessedPacket::sensor_timestamp`) to actuation.
+//! - Packets older than the actuator's `max_age` are stale; stale rejections are counted
+//!   separately from deadline misses (a stale packet may still be actuated on time).
+//! - Configurable fallback per actuator: discard, hold last command, ramp to a safe value,
+//!   or extrapolate the measurement from the last two fresh packets.
+//! - Limits and fallbacks come from `[freshness.<actuator>]` in the run configuration.
+
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::component_a::{processor::ProcessedPacket, sensor::SensorType};
+use crate::component_b::routing::ActuatorRole;
+
+/// Action taken by an actuator when it dequeues a stale packet, tagged by `action`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum StaleFallback {
+    Discard,                                        // Drop packet; no actuation cycle at all
+    HoldLast,                                       // Re-issue last command (state unchanged)
+    RampToSafe { safe_value: f64, max_step: f64 },  // Move state toward `safe_value` by ≤ `max_step` per stale packet
+    Extrapolate,                                    // Control on linearly extrapolated measurement
+}
+
+/// Per-actuator freshness deadline and fallback.
+#[derive(Debug, Clone, Copy)]
+pub struct FreshnessPolicy {
+    pub max_age: Duration,
+    pub fallback: StaleFallback,
+}
+
+/// `[freshness.<actuator>]`: staleness limit and fallback of one actuator.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct FreshnessSpec {
+    pub limit_ms: u64,              // Sensor-to-actuation age above which a packet is stale
+    pub fallback: StaleFallback,
+}
+
+impl Default for FreshnessSpec {
+    fn default() -> Self {
+        Self { limit_ms: 20, fallback: StaleFallback::HoldLast }
+    }
+}
+
+impl FreshnessSpec {
+    pub fn policy(&self) -> FreshnessPolicy {
+        FreshnessPolicy {
+            max_age: Duration::from_millis(self.limit_ms),
+            fallback: self.fallback,
+        }
+    }
+}
+
+/// `[freshness]`: freshness limit and fallback per actuator role.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct FreshnessConfig {
+    pub gripper: FreshnessSpec,
+    pub motor: FreshnessSpec,
+    pub stabiliser: FreshnessSpec,
+}
+
+impl Default for FreshnessConfig {
+    /// Gripper holds its grip, motor extrapolates position, stabiliser ramps to neutral.
+    /// Limits are a few sensor periods (5 ms) so ordinary queueing never trips them.
+    fn default() -> Self {
+        Self {
+            gripper: FreshnessSpec::default(),
+            motor: FreshnessSpec { limit_ms: 15, fallback: StaleFallback::Extrapolate },
+            stabiliser: FreshnessSpec {
+                limit_ms: 50,
+                fallback: StaleFallback::RampToSafe { safe_value: 0.0, max_step: 1.0 },
+            },
+        }
+    }
+}
+
+impl FreshnessConfig {
+    /// Policy of an actuator with `role`.
+    pub fn policy(&self, role: ActuatorRole) -> FreshnessPolicy {
+        match role {
+            ActuatorRole::Gripper => self.gripper.policy(),
+            ActuatorRole::Motor => self.motor.policy(),
+            ActuatorRole::Stabiliser => self.stabiliser.policy(),
+        }
+    }
+}
+
+/// Result of a freshness check on a dequeued packet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Freshness {
+    Fresh,
+    Stale { age: Duration },
+}
+
+/// Per-actuator freshness state: applies the policy and remembers recent fresh measurements.
+pub struct FreshnessGuard {
+    policy: FreshnessPolicy,
+    last: Option<(Instant, f64)>,   // Most recent fresh (sensor release, filtered value)
+    prev: Option<(Instant, f64)>,   // The one before (extrapolation slope)
+    sensor: Option<SensorType>,     // Sensor whose packets are references; None = any
+}
+
+impl FreshnessGuard {
+    pub fn new(policy: FreshnessPolicy) -> Self {
+        Self {
+            policy,
+            last: None,
+            prev: None,
+            sensor: None,
+        }
+    }
+
+    /// Extrapolates `sensor` only (the actuator's primary when several sensors feed it).
+    pub fn with_sensor(mut self, sensor: SensorType) -> Self {
+        self.sensor = Some(sensor);
+        self
+    }
+
+    #[inline]
+    pub fn policy(&self) -> FreshnessPolicy {
+        self.policy
+    }
+
+    /// Checks packet age at `now`; fresh packets (of the tracked sensor) become the extrapolation
+    /// reference.
+    pub fn check(&mut self, pkt: &ProcessedPacket, now: Instant) -> Freshness {
+        let age = now.saturating_duration_since(pkt.sensor_timestamp);
+        if age > self.policy.max_age {
+            return Freshness::Stale { age };
+        }
+
+        if self.sensor.is_none_or(|sensor| sensor == pkt.sensor_type) {
+            self.prev = self.last;
+            self.last = Some((pkt.sensor_timestamp, pkt.filtered));
+        }
+        Freshness::Fresh
+    }
+
+    /// Measurement extrapolated to `now` from the last two fresh packets
+    /// (last value if only one is known, None before any fresh packet).
+    /// The horizon is capped at `max_age` so long outages do not run away.
+    pub fn extrapolate(&self, now: Instant) -> Option<f64> {
+        let (t1, v1) = self.last?;
+        let (t0, v0) = match self.prev {
+            Some(p) => p,
+            None => return Some(v1),
+        };
+
+        let span = t1.saturating_duration_since(t0).as_secs_f64();
+        if span <= 0.0 {
+            return Some(v1);
+        }
+        let slope = (v1 - v0) / span;
+        let horizon = now.saturating_duration_since(t1).min(self.policy.max_age);
+        Some(v1 + slope * horizon.as_secs_f64())
+    }
+}
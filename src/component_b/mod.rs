@@ -4,3 +4,13 @@ pub mod receiver;
 pub mod controller;
 pub mod multi_actuator;
 pub mod feedback;
+pub mod sequence;
+pub mod freshness;
+pub mod plant;
+pub mod control_law;
+pub mod autotune;
+pub mod setpoint;
+pub mod actuator_model;
+pub mod failsafe;
+pub mod actuator_fault;
+pub mod routing;
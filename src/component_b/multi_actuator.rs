@@ -1,147 +1,288 @@
 //! multi_actuator.rs
-//! Manages multiple actuators (gripper, motor, stabiliser) with concurrent dispatch.
+//! Manages multiple actuators (gripper, motor, stabiliser by default) with concurrent dispatch.
 //!
 //! REQUIREMENT 1: Multiple actuators concurrently (bounded channels, dedicated threads).
-//! REQUIREMENT 2: Per-actuator deadline enforcement (2 ms, ThreadPriority::Max).
+//! REQUIREMENT 2: Per-actuator deadline enforcement (2 ms, ThreadPriority::Max by default).
+//! Routing (`routing.rs`): the table decides the actuator threads, the sensors routed to each
+//! (fan-out and fan-in), and each one's channel capacity and thread priority; `dispatch` counts
+//! deliveries and drops per route.
+//! Freshness: packets older than the actuator's age limit (sensor release → actuation)
+//! are rejected and handled by the configured fallback (see `freshness.rs`).
+//! Each actuator loop runs inside an RT section (`rt_alloc`): no allocation per packet.
+//! Plants (optional): each actuator drives the plant measured by its primary sensor.
+//! Control law per actuator (`ActuatorSetup::law`): PID unless configured otherwise, run once
+//! per `sample_s`; with `commands` the controller takes setpoint / tuning commands by its name.
+//! Auto-tune (`ActuatorSetup::autotune`): relay experiment at start when enabled, or on command.
+//! Setpoint (`ActuatorSetup::setpoint`): profile tracked on the primary sensor; tracking quality
+//! is analysed per actuator (`control_quality.rs`) and finalised when the channel closes.
+//! Dynamics (`ActuatorSetup::dynamics`): saturation, rate limit, dead band, backlash, lag and
+//! delay between command and state (`actuator_model.rs`); limit onsets are fed back.
+//! Faults (`ActuatorSetup::faults`): injected after the dynamics (`actuator_fault.rs`); with
+//! `report_expected` each `ActuatorState` feedback is preceded by the healthy `CommandedState`.
+//! Fail-safe (`ActuatorSetup::watchdogs`): fresh packets are heartbeats for the supervisor; outside
+//! `nominal` the actuator applies its safe action for the system mode (`failsafe.rs`).
 
 use crossbeam::channel::{Sender, Receiver, bounded};
 use std::{sync::Arc, thread::{self, JoinHandle}, time::{Instant}};
-use thread_priority::{ThreadPriority, ThreadBuilderExt};
+use thread_priority::ThreadBuilderExt;
 use crate::{component_a::{
     processor::ProcessedPacket,
-    sensor::SensorType,
     sync_manager::SyncManager,
 }, };
 
 use crate::component_b::{
-    controller::Controller,
+    actuator_fault::FaultInjector,
+    actuator_model::ActuatorModel,
+    autotune::AutotuneSetup,
+    control_law::ControlLaw,
+    controller::{ControlCommand, Controller},
+    failsafe::{SafeAction, SafeActions, Watchdogs},
     feedback::{FeedbackLoop, FeedbackKind},
+    freshness::{Freshness, FreshnessConfig, FreshnessGuard, FreshnessPolicy, StaleFallback},
+    plant::PlantInput,
+    routing::{ActuatorRoute, RoutingConfig, RoutingTable},
+    setpoint::SetpointGenerator,
 };
-use crate::utils::metrics::{SharedMetrics, push_capped, EventRecorder,DeadlineComponent};
+use crate::utils::bus::Topic;
+use crate::utils::metrics::{SharedMetrics, EventRecorder, Event, DeadlineComponent};
+use crate::utils::rt_alloc;
 
 const ACTUATOR_DEADLINE_US: u64 = 2_000;     // 2 ms deadline per actuator
-const CHANNEL_CAPACITY: usize = 8;            // Bounded queue per actuator
+const STALE_REJECT_CODE: u16 = 700;           // SyncManager custom code for stale rejections
+
+/// Per-actuator setup (routing table order): freshness policy, driven plant, control law and
+/// its command channel.
+pub struct ActuatorSetup {
+    pub freshness: FreshnessPolicy,
+    pub plant: Option<PlantInput>,              // None = open loop
+    pub law: Option<Box<dyn ControlLaw>>,       // None = default PID
+    pub sample_s: f64,                          // Control period; 0 = every packet
+    pub commands: Option<Topic<ControlCommand>>, // Remote setpoint / tuning (bus `commands`)
+    pub autotune: AutotuneSetup,                // Relay auto-tune settings (off by default)
+    pub setpoint: Option<SetpointGenerator>,    // None = nominal constant target
+    pub dynamics: Option<ActuatorModel>,        // None = ideal actuator
+    pub faults: Option<FaultInjector>,          // None = healthy actuator
+    pub report_expected: bool,                  // CommandedState feedback (fault detector)
+    pub watchdogs: Option<Arc<Watchdogs>>,      // None = no fail-safe supervisor
+    pub safe: SafeActions,                      // Action per system mode
+}
+
+impl ActuatorSetup {
+    pub fn new(freshness: FreshnessPolicy) -> Self {
+        Self {
+            freshness,
+            plant: None,
+            law: None,
+            sample_s: 0.0,
+            commands: None,
+            autotune: AutotuneSetup::default(),
+            setpoint: None,
+            dynamics: None,
+            faults: None,
+            report_expected: false,
+            watchdogs: None,
+            safe: SafeActions::default(),
+        }
+    }
+}
 
 /// Routes packets to multiple actuators; each runs in independent priority thread.
 pub struct MultiActuator {
-    tx_gripper: Sender<ProcessedPacket>,
-    tx_motor: Sender<ProcessedPacket>,
-    tx_stabiliser: Sender<ProcessedPacket>,
+    senders: Vec<Sender<ProcessedPacket>>,  // By actuator (routing table order)
+    routing: RoutingTable,
     _handles: Vec<JoinHandle<()>>,
     _feedback: FeedbackLoop,
 }
 
 impl MultiActuator {
-    /// Create and start all actuator threads with max priority.
-    /// REQUIREMENT 1: Three independent channels (gripper, motor, stabiliser).
+    /// Create and start the default actuators (gripper, motor, stabiliser) with max priority.
+    /// REQUIREMENT 1: Independent channels, one per actuator.
     /// REQUIREMENT 2: Each thread spawned with ThreadPriority::Max for deadline adherence.
+    #[allow(dead_code)]
     pub fn new(sync: Arc<SyncManager>, feedback: FeedbackLoop, metrics: SharedMetrics, event_recorder: Arc<EventRecorder>) -> Self {
+        Self::with_freshness(sync, feedback, metrics, event_recorder, FreshnessConfig::default())
+    }
+
+    /// Same as `new`, with explicit per-actuator freshness limits and stale fallbacks.
+    #[allow(dead_code)]
+    pub fn with_freshness(
+        sync: Arc<SyncManager>,
+        feedback: FeedbackLoop,
+        metrics: SharedMetrics,
+        event_recorder: Arc<EventRecorder>,
+        freshness: FreshnessConfig,
+    ) -> Self {
+        let routing = RoutingConfig::default().build();
+        let actuators = routing.actuators().iter().map(|a| ActuatorSetup::new(freshness.policy(a.role))).collect();
+        Self::with_actuators(sync, feedback, metrics, event_recorder, routing, actuators)
+    }
+
+    /// Full per-actuator setup, one per actuator of `routing` (in table order).
+    pub fn with_actuators(
+        sync: Arc<SyncManager>,
+        feedback: FeedbackLoop,
+        metrics: SharedMetrics,
+        event_recorder: Arc<EventRecorder>,
+        routing: RoutingTable,
+        actuators: Vec<ActuatorSetup>,
+    ) -> Self {
+        assert_eq!(actuators.len(), routing.actuators().len(), "one ActuatorSetup per routed actuator");
+
         // ====================================================================
         // REQUIREMENT 1: Create bounded channels for concurrent packet dispatch
+        // Capacity and thread priority per actuator come from the routing table
         // ====================================================================
-        let (tx_g, rx_g) = bounded(CHANNEL_CAPACITY);
-        let (tx_m, rx_m) = bounded(CHANNEL_CAPACITY);
-        let (tx_s, rx_s) = bounded(CHANNEL_CAPACITY);
-
-        let mut handles = Vec::new();
-
-        // Spawn independent actuator threads (gripper, motor, stabiliser)
-        handles.push(spawn_actuator_thread(
-            "Gripper",
-            rx_g,
-            sync.clone(),
-            feedback.clone(),
-            metrics.clone(),
-            ActuatorType::Gripper,
-            event_recorder.clone(),
-        ));
-
-        handles.push(spawn_actuator_thread(
-            "Motor",
-            rx_m,
-            sync.clone(),
-            feedback.clone(),
-            metrics.clone(),
-            ActuatorType::Motor,
-            event_recorder.clone(),
-        ));
-
-        handles.push(spawn_actuator_thread(
-            "Stabiliser",
-            rx_s,
-            sync.clone(),
-            feedback.clone(),
-            metrics.clone(),
-            ActuatorType::Stabiliser,
-            event_recorder,
-        ));
+        let mut senders = Vec::with_capacity(actuators.len());
+        let mut handles = Vec::with_capacity(actuators.len());
+        for (index, (route, setup)) in routing.actuators().iter().zip(actuators).enumerate() {
+            let (tx, rx) = bounded(route.capacity);
+            senders.push(tx);
+            handles.push(spawn_actuator_thread(
+                index,
+                route,
+                rx,
+                sync.clone(),
+                feedback.clone(),
+                metrics.clone(),
+                setup,
+                event_recorder.clone(),
+            ));
+        }
 
         Self {
-            tx_gripper: tx_g,
-            tx_motor: tx_m,
-            tx_stabiliser: tx_s,
+            senders,
+            routing,
             _handles: handles,
             _feedback: feedback,
         }
     }
 
-    /// Dispatch processed packet to correct actuator (non-blocking).
-    /// REQUIREMENT 1: Route by sensor type (Force→Gripper, Position→Motor, Temperature→Stabiliser).
+    /// Dispatch processed packet to every actuator it is routed to (non-blocking).
+    /// REQUIREMENT 1: Route by sensor type through the routing table (fan-out: one copy per route).
     pub fn dispatch(&self, pkt: ProcessedPacket, sync: Arc<SyncManager>) {
-        let result = match pkt.sensor_type {
-            SensorType::Force => self.tx_gripper.try_send(pkt),
-            SensorType::Position => self.tx_motor.try_send(pkt),
-            SensorType::Temperature => self.tx_stabiliser.try_send(pkt),
-        };
-
-        if result.is_err() {
-            sync.record_tx_drop();
+        let stats = self.routing.stats();
+        stats.packet(pkt.sensor_type);
+
+        for index in self.routing.routes_from(pkt.sensor_type) {
+            let actuator = self.routing.route(index).actuator;
+            let tx = &self.senders[actuator];
+            if tx.try_send(pkt.clone()).is_ok() {
+                stats.delivered(index, actuator, tx.len());
+            } else {
+                stats.dropped(index);
+                sync.record_tx_drop();
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum ActuatorType {
-    Gripper,
-    Motor,
-    Stabiliser,
-}
-
-/// Spawn independent actuator thread with max OS priority.
+/// Spawn independent actuator thread with the routed OS priority (max by default).
 /// REQUIREMENT 2: Enforce 2 ms deadline; track deadline misses per actuator.
-
+#[allow(clippy::too_many_arguments)]
 fn spawn_actuator_thread(
-    name: &'static str,
+    index: usize,
+    route: &ActuatorRoute,
     rx: Receiver<ProcessedPacket>,
     sync: Arc<SyncManager>,
     feedback: FeedbackLoop,
     metrics: SharedMetrics,
-    actuator_type: ActuatorType,
+    setup: ActuatorSetup,
     event_recorder: Arc<EventRecorder>,
 ) -> JoinHandle<()> {
+    let (name, role, primary, series) = (route.name, route.role, route.primary(), route.series);
     thread::Builder::new()
         .name(name.to_string())
-        .spawn_with_priority(ThreadPriority::Max, move |_| {
-            let mut controller = Controller::new(sync.clone(), feedback.clone(), metrics.clone(), event_recorder.clone());
+        .spawn_with_priority(route.priority.thread_priority(), move |_| {
+            let mut controller = Controller::new(sync.clone(), feedback.clone(), metrics.clone(), event_recorder.clone())
+                .with_name(name)
+                .with_primary(primary)
+                .with_sample_time(setup.sample_s)
+                .with_autotune(setup.autotune)
+                .with_quality_tracking();
+            if let Some(plant) = setup.plant {
+                controller = controller.with_plant(plant);
+            }
+            if let Some(setpoint) = setup.setpoint {
+                controller = controller.with_setpoint(primary, setpoint);
+            }
+            if let Some(dynamics) = setup.dynamics {
+                controller = controller.with_dynamics(dynamics);
+            }
+            if let Some(faults) = setup.faults {
+                controller = controller.with_faults(faults);
+            }
+            if let Some(law) = setup.law {
+                controller = controller.with_law(law);
+            }
+            if let Some(commands) = &setup.commands {
+                controller.subscribe_commands(commands);
+            }
+            let mut freshness_guard = FreshnessGuard::new(setup.freshness).with_sensor(primary);
+            let (watchdogs, safe) = (setup.watchdogs, setup.safe);
+
+            metrics.live().register_actuator(name);
+            event_recorder.attach_thread();
+            sync.attach_thread();
 
-            while let Ok(pkt) = rx.recv() {
+            // The first blocking receive allocates crossbeam's per-thread wait context and the
+            // channel's waiter list: wait for the first packet before entering the RT section
+            let mut first = rx.recv().ok();
+            let _rt = rt_alloc::enter(name);
+
+            while let Some(pkt) = first.take().or_else(|| rx.recv().ok()) {
                 let cycle_start = Instant::now();
-                controller.handle_packet(&pkt);
+
+                // Safe action for the system mode (one atomic load; always `Continue` in nominal)
+                let action = watchdogs.as_ref().map_or(SafeAction::Continue, |w| safe.for_mode(w.mode()));
+
+                // ====================================================================
+                // Freshness: reject commands older than the end-to-end age limit
+                // Counted separately from deadline misses; fallback decides the action
+                // ====================================================================
+                let fresh = match freshness_guard.check(&pkt, cycle_start) {
+                    Freshness::Fresh => {
+                        if let Some(watchdogs) = &watchdogs {
+                            watchdogs.actuator_beat(index);
+                        }
+                        if action == SafeAction::Continue {
+                            controller.handle_packet(&pkt);
+                        }
+                        true
+                    }
+                    Freshness::Stale { age } => {
+                        record_stale(name, &pkt, age.as_micros() as u64, &sync, &metrics, &event_recorder);
+                        feedback.emit(name, FeedbackKind::Error("stale_command"), cycle_start);
+
+                        if action == SafeAction::Continue {
+                            match freshness_guard.policy().fallback {
+                                StaleFallback::Discard => continue,
+                                StaleFallback::HoldLast => {}
+                                StaleFallback::RampToSafe { safe_value, max_step } => {
+                                    controller.ramp_toward(safe_value, max_step);
+                                }
+                                StaleFallback::Extrapolate if pkt.sensor_type == primary => {
+                                    if let Some(estimate) = freshness_guard.extrapolate(cycle_start) {
+                                        controller.handle_packet(&ProcessedPacket { filtered: estimate, ..pkt });
+                                    }
+                                }
+                                StaleFallback::Extrapolate => {} // Secondary sensor: hold the last command
+                            }
+                        }
+                        false
+                    }
+                };
+
+                // Fail-safe: hold the last command or ramp toward the safe value, fresh or not
+                match action {
+                    SafeAction::Continue | SafeAction::Hold => {}
+                    SafeAction::Ramp { value, max_step } => controller.ramp_toward(value, max_step),
+                }
 
                 let state = controller.current_state();
                 
-                {
-                    let mut m = match metrics.lock() {
-                        Ok(guard) => guard,
-                        Err(poisoned) => poisoned.into_inner(),
-                    };
-                     
-                    
-                    match actuator_type {
-                        ActuatorType::Gripper => push_capped(&mut m.gripper, state),
-                        ActuatorType::Motor => push_capped(&mut m.motor, state),
-                        ActuatorType::Stabiliser => push_capped(&mut m.stabiliser, state),
-                    }
+                let live = metrics.live();
+                if series {
+                    live.actuator_series(role).push(state);
                 }
 
                 // ====================================================================
@@ -153,18 +294,19 @@ fn spawn_actuator_thread(
                 if elapsed_us > ACTUATOR_DEADLINE_US {
                     sync.record_proc_miss();
 
-                    {
-                        let mut m = match metrics.lock() {
-                            Ok(guard) => guard,
-                            Err(poisoned) => poisoned.into_inner(),
-                        };
-                        m.record_deadline_miss(DeadlineComponent::Actuator);
-                    }
+                    live.record_deadline_miss(DeadlineComponent::Actuator);
                 }
 
                 // Emit feedback: ack on success, error on deadline miss
+                // (stale packets already reported; no ack for a fallback or safe action)
+                if !fresh || action != SafeAction::Continue {
+                    continue;
+                }
                 if elapsed_us <= 500 {
                     feedback.emit(name, FeedbackKind::Ack, cycle_start);
+                    if setup.report_expected {
+                        feedback.emit(name, FeedbackKind::CommandedState(controller.expected_state()), cycle_start);
+                    }
                     feedback.emit(
                         name,
                         FeedbackKind::ActuatorState(controller.current_state()),
@@ -174,6 +316,28 @@ fn spawn_actuator_thread(
                     feedback.emit(name, FeedbackKind::Error("deadline_miss"), cycle_start);
                 }
             }
+
+            // Channel closed (end of run): the last setpoint segment ends here
+            controller.finish_quality();
         })
         .expect("Failed to spawn actuator thread")
-}
\ No newline at end of file
+}
+
+/// Stale rejection bookkeeping: SyncManager log, per-actuator metrics counter, event trace.
+fn record_stale(
+    actuator: &'static str,
+    pkt: &ProcessedPacket,
+    age_us: u64,
+    sync: &SyncManager,
+    metrics: &SharedMetrics,
+    event_recorder: &EventRecorder,
+) {
+    sync.record_custom(STALE_REJECT_CODE);
+    metrics.live().record_stale(actuator);
+    event_recorder.record(Event::StaleRejected {
+        seq: pkt.seq,
+        ts_ns: event_recorder.now_ns(),
+        actuator,
+        age_us,
+    });
+}
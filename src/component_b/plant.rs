@@ -0,0 +1,423 @@
+//! plant.rs
+//! Physical plant models that close the loop between actuators and sensors.
+//!
+//! - `PlantModel`: continuous-time model advanced by `step(input, dt)`; `output()` is the
+//!   quantity the paired sensor measures.
+//! - Models: first-order lag, mass-spring-damper, thermal RC, DC motor (semi-implicit Euler,
+//!   sub-stepped at `MAX_SUBSTEP_S` so stiff parameters stay stable at the 5 ms sample period).
+//! - `Plant` is owned by the sensor thread: each sample integrates the model from the previous
+//!   sample to now, holding the last actuator command (zero-order hold), so the plant keeps
+//!   evolving even when its actuator stalls.
+//! - `PlantInput` is the actuator side: the controller publishes its command with one atomic
+//!   store; no lock and no allocation on either RT thread.
+//!
+//! - `PlantSpec::linear` gives the same models in state-space form for model-based control
+//!   laws (LQR, MPC in `control_law.rs`); `LinearModel::discretize` samples them exactly.
+//!
+//! Default pairing (`[plant]` in config.toml): Force ← Gripper (first-order lag),
+//! Position ← Motor (DC motor, shaft angle), Temperature ← Stabiliser (thermal RC).
+
+use std::{
+    sync::{Arc, atomic::{AtomicU64, Ordering}},
+    time::Instant,
+};
+
+use serde::Deserialize;
+
+use crate::component_a::sensor::SensorType;
+
+const MAX_SUBSTEP_S: f64 = 0.0005;  // Integration step limit (0.5 ms)
+const MAX_GAP_S: f64 = 0.1;         // Longer gaps (stalled sensor) are integrated as 100 ms
+const EXPM_TERMS: usize = 24;       // Taylor terms of the matrix exponential (‖A·dt‖ stays below ~1)
+
+/// Largest state dimension of the plant models (DC motor: current, speed, angle).
+pub const MAX_STATES: usize = 3;
+
+pub type Mat = [[f64; MAX_STATES]; MAX_STATES];
+pub type Vector = [f64; MAX_STATES];
+
+/// Continuous-time plant driven by one actuator input.
+pub trait PlantModel: Send {
+    /// Advances the state by `dt` seconds with `input` held constant.
+    fn step(&mut self, input: f64, dt: f64);
+
+    /// Measured output (what the paired sensor samples, before noise).
+    fn output(&self) -> f64;
+
+    fn name(&self) -> &'static str;
+}
+
+/// y' = (gain·u − y) / tau
+#[derive(Debug, Clone)]
+pub struct FirstOrderLag {
+    pub gain: f64,
+    pub tau_s: f64,
+    y: f64,
+}
+
+impl FirstOrderLag {
+    pub fn new(gain: f64, tau_s: f64) -> Self {
+        Self { gain, tau_s: tau_s.max(1e-6), y: 0.0 }
+    }
+}
+
+impl PlantModel for FirstOrderLag {
+    fn step(&mut self, input: f64, dt: f64) {
+        self.y += (self.gain * input - self.y) * dt / self.tau_s;
+    }
+
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    fn name(&self) -> &'static str {
+        "first_order_lag"
+    }
+}
+
+/// m·x'' = u − c·x' − k·x (input force, output position)
+#[derive(Debug, Clone)]
+pub struct MassSpringDamper {
+    pub mass: f64,
+    pub stiffness: f64,
+    pub damping: f64,
+    x: f64,
+    v: f64,
+}
+
+impl MassSpringDamper {
+    pub fn new(mass: f64, stiffness: f64, damping: f64) -> Self {
+        Self { mass: mass.max(1e-9), stiffness, damping, x: 0.0, v: 0.0 }
+    }
+}
+
+impl PlantModel for MassSpringDamper {
+    fn step(&mut self, input: f64, dt: f64) {
+        let accel = (input - self.damping * self.v - self.stiffness * self.x) / self.mass;
+        self.v += accel * dt;
+        self.x += self.v * dt;
+    }
+
+    fn output(&self) -> f64 {
+        self.x
+    }
+
+    fn name(&self) -> &'static str {
+        "mass_spring_damper"
+    }
+}
+
+/// C·T' = u − (T − ambient) / R (input heating power, output temperature)
+#[derive(Debug, Clone)]
+pub struct ThermalRc {
+    pub resistance: f64,
+    pub capacitance: f64,
+    pub ambient: f64,
+    temp: f64,
+}
+
+impl ThermalRc {
+    /// Starts at ambient temperature.
+    pub fn new(resistance: f64, capacitance: f64, ambient: f64) -> Self {
+        Self {
+            resistance: resistance.max(1e-9),
+            capacitance: capacitance.max(1e-9),
+            ambient,
+            temp: ambient,
+        }
+    }
+}
+
+impl PlantModel for ThermalRc {
+    fn step(&mut self, input: f64, dt: f64) {
+        let loss = (self.temp - self.ambient) / self.resistance;
+        self.temp += (input - loss) * dt / self.capacitance;
+    }
+
+    fn output(&self) -> f64 {
+        self.temp
+    }
+
+    fn name(&self) -> &'static str {
+        "thermal_rc"
+    }
+}
+
+/// Armature-controlled DC motor (input voltage, output shaft angle):
+/// L·i' = V − R·i − ke·ω,  J·ω' = kt·i − b·ω,  θ' = ω
+#[derive(Debug, Clone)]
+pub struct DcMotor {
+    pub resistance: f64,
+    pub inductance: f64,
+    pub kt: f64,
+    pub ke: f64,
+    pub inertia: f64,
+    pub friction: f64,
+    current: f64,
+    speed: f64,
+    angle: f64,
+}
+
+impl DcMotor {
+    pub fn new(resistance: f64, inductance: f64, kt: f64, ke: f64, inertia: f64, friction: f64) -> Self {
+        Self {
+            resistance,
+            inductance: inductance.max(1e-9),
+            kt,
+            ke,
+            inertia: inertia.max(1e-12),
+            friction,
+            current: 0.0,
+            speed: 0.0,
+            angle: 0.0,
+        }
+    }
+}
+
+impl PlantModel for DcMotor {
+    fn step(&mut self, input: f64, dt: f64) {
+        self.current += (input - self.resistance * self.current - self.ke * self.speed) * dt / self.inductance;
+        self.speed += (self.kt * self.current - self.friction * self.speed) * dt / self.inertia;
+        self.angle += self.speed * dt;
+    }
+
+    fn output(&self) -> f64 {
+        self.angle
+    }
+
+    fn name(&self) -> &'static str {
+        "dc_motor"
+    }
+}
+
+/// Plant model and parameters as written in `[plant]` (tagged by `model`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum PlantSpec {
+    FirstOrderLag { gain: f64, tau_s: f64 },
+    MassSpringDamper { mass: f64, stiffness: f64, damping: f64 },
+    ThermalRc { resistance: f64, capacitance: f64, ambient: f64 },
+    DcMotor { resistance: f64, inductance: f64, kt: f64, ke: f64, inertia: f64, friction: f64 },
+}
+
+impl PlantSpec {
+    /// Default plant measured by `sensor`; each settles inside the ±50 controller output range.
+    pub fn default_for(sensor: SensorType) -> Self {
+        match sensor {
+            // Grip force: 4 N per unit command, 200 ms lag (100 N at a command of 25)
+            SensorType::Force => PlantSpec::FirstOrderLag { gain: 4.0, tau_s: 0.2 },
+            // Joint motor: 10 ms electrical, ~0.4 s mechanical time constant
+            SensorType::Position => PlantSpec::DcMotor {
+                resistance: 1.0,
+                inductance: 0.01,
+                kt: 0.05,
+                ke: 0.05,
+                inertia: 0.001,
+                friction: 0.0001,
+            },
+            // Stabiliser heater: 2 s time constant, +5 °C above 20 °C ambient at 10 W
+            SensorType::Temperature => PlantSpec::ThermalRc { resistance: 0.5, capacitance: 4.0, ambient: 20.0 },
+        }
+    }
+
+    /// Continuous state-space form (states beyond `n` are zero padding).
+    pub fn linear(&self) -> LinearModel {
+        let mut m = LinearModel::default();
+        match *self {
+            PlantSpec::FirstOrderLag { gain, tau_s } => {
+                let tau = tau_s.max(1e-6);
+                m.n = 1;
+                m.a[0][0] = -1.0 / tau;
+                m.b[0] = gain / tau;
+                m.c[0] = 1.0;
+            }
+            PlantSpec::MassSpringDamper { mass, stiffness, damping } => {
+                // x = [position, velocity]
+                let mass = mass.max(1e-9);
+                m.n = 2;
+                m.a[0][1] = 1.0;
+                m.a[1][0] = -stiffness / mass;
+                m.a[1][1] = -damping / mass;
+                m.b[1] = 1.0 / mass;
+                m.c[0] = 1.0;
+            }
+            PlantSpec::ThermalRc { resistance, capacitance, ambient } => {
+                // x = temperature above ambient
+                let (r, c) = (resistance.max(1e-9), capacitance.max(1e-9));
+                m.n = 1;
+                m.a[0][0] = -1.0 / (r * c);
+                m.b[0] = 1.0 / c;
+                m.c[0] = 1.0;
+                m.y0 = ambient;
+            }
+            PlantSpec::DcMotor { resistance, inductance, kt, ke, inertia, friction } => {
+                // x = [current, speed, angle]
+                let (l, j) = (inductance.max(1e-9), inertia.max(1e-12));
+                m.n = 3;
+                m.a[0][0] = -resistance / l;
+                m.a[0][1] = -ke / l;
+                m.a[1][0] = kt / j;
+                m.a[1][1] = -friction / j;
+                m.a[2][1] = 1.0;
+                m.b[0] = 1.0 / l;
+                m.c[2] = 1.0;
+            }
+        }
+        m
+    }
+
+    pub fn build(&self) -> Box<dyn PlantModel> {
+        match *self {
+            PlantSpec::FirstOrderLag { gain, tau_s } => Box::new(FirstOrderLag::new(gain, tau_s)),
+            PlantSpec::MassSpringDamper { mass, stiffness, damping } => {
+                Box::new(MassSpringDamper::new(mass, stiffness, damping))
+            }
+            PlantSpec::ThermalRc { resistance, capacitance, ambient } => {
+                Box::new(ThermalRc::new(resistance, capacitance, ambient))
+            }
+            PlantSpec::DcMotor { resistance, inductance, kt, ke, inertia, friction } => {
+                Box::new(DcMotor::new(resistance, inductance, kt, ke, inertia, friction))
+            }
+        }
+    }
+}
+
+/// Single-input single-output linear model: x' = A·x + B·u, y = C·x + y0
+/// (or x⁺ = A·x + B·u once discretized).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinearModel {
+    pub n: usize,       // Active states
+    pub a: Mat,
+    pub b: Vector,
+    pub c: Vector,
+    pub y0: f64,        // Output offset (thermal ambient)
+}
+
+impl LinearModel {
+    /// Zero-order-hold discretization at `dt` seconds: A_d = e^(A·dt), B_d = ∫ e^(A·s) ds · B.
+    pub fn discretize(&self, dt: f64) -> LinearModel {
+        // Scale down until the Taylor series converges quickly, then square back up
+        let norm = self.a.iter().flatten().fold(0.0_f64, |acc, v| acc.max(v.abs())) * dt * MAX_STATES as f64;
+        let squarings = norm.max(1.0).log2().ceil() as u32;
+        let h = dt / f64::from(1u32 << squarings);
+
+        // Φ = Σ (A·h)^k / k!,  Γ = Σ A^k h^(k+1) / (k+1)!
+        let mut phi = identity();
+        let mut gamma = scale(&identity(), h);
+        let mut term = identity();
+        for k in 1..EXPM_TERMS {
+            term = scale(&mat_mul(&term, &self.a), h / k as f64);
+            phi = mat_add(&phi, &term);
+            gamma = mat_add(&gamma, &scale(&term, h / (k + 1) as f64));
+        }
+        // e^(A·2h) = Φ², ∫₀^2h = Γ + Φ·Γ
+        for _ in 0..squarings {
+            gamma = mat_add(&gamma, &mat_mul(&phi, &gamma));
+            phi = mat_mul(&phi, &phi);
+        }
+
+        // Padding states stay zero (e^0 would make them integrators)
+        for (i, row) in phi.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                if i >= self.n || j >= self.n {
+                    *v = 0.0;
+                }
+            }
+        }
+
+        LinearModel {
+            n: self.n,
+            a: phi,
+            b: mat_vec(&gamma, &self.b),
+            c: self.c,
+            y0: self.y0,
+        }
+    }
+}
+
+pub fn identity() -> Mat {
+    std::array::from_fn(|i| std::array::from_fn(|j| if i == j { 1.0 } else { 0.0 }))
+}
+
+pub fn mat_mul(x: &Mat, y: &Mat) -> Mat {
+    std::array::from_fn(|i| std::array::from_fn(|j| (0..MAX_STATES).map(|k| x[i][k] * y[k][j]).sum()))
+}
+
+pub fn mat_add(x: &Mat, y: &Mat) -> Mat {
+    std::array::from_fn(|i| std::array::from_fn(|j| x[i][j] + y[i][j]))
+}
+
+pub fn scale(x: &Mat, s: f64) -> Mat {
+    std::array::from_fn(|i| std::array::from_fn(|j| x[i][j] * s))
+}
+
+pub fn transpose(x: &Mat) -> Mat {
+    std::array::from_fn(|i| std::array::from_fn(|j| x[j][i]))
+}
+
+#[inline]
+pub fn mat_vec(x: &Mat, v: &Vector) -> Vector {
+    std::array::from_fn(|i| dot(&x[i], v))
+}
+
+#[inline]
+pub fn dot(x: &Vector, y: &Vector) -> f64 {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+/// Actuator side of a plant: the latest command, read by the sensor thread at each sample.
+#[derive(Clone, Default)]
+pub struct PlantInput(Arc<AtomicU64>);
+
+impl PlantInput {
+    #[inline]
+    pub fn set(&self, command: f64) {
+        self.0.store(command.to_bits(), Ordering::Release);
+    }
+
+    #[inline]
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Acquire))
+    }
+}
+
+/// Plant state owned by the sensor that measures it.
+pub struct Plant {
+    model: Box<dyn PlantModel>,
+    input: PlantInput,
+    last_sample: Option<Instant>,
+}
+
+impl Plant {
+    pub fn new(model: Box<dyn PlantModel>) -> Self {
+        Self {
+            model,
+            input: PlantInput::default(),
+            last_sample: None,
+        }
+    }
+
+    /// Handle for the controller that drives this plant.
+    pub fn input(&self) -> PlantInput {
+        self.input.clone()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.model.name()
+    }
+
+    /// Integrates up to `now` under the current command and returns the output.
+    pub fn sample(&mut self, now: Instant) -> f64 {
+        if let Some(last) = self.last_sample {
+            let input = self.input.get();
+            let mut remaining = now.saturating_duration_since(last).as_secs_f64().min(MAX_GAP_S);
+            while remaining > 0.0 {
+                let dt = remaining.min(MAX_SUBSTEP_S);
+                self.model.step(input, dt);
+                remaining -= dt;
+            }
+        }
+        self.last_sample = Some(now);
+        self.model.output()
+    }
+}
@@ -1,30 +1,43 @@
-
 //! receiver.rs
 //! Receives processed sensor packets from Component A and dispatches them to the Controller and actuators.
 //! - uses high-performance crossbeam channels for efficient input handling
 //! - minimizes delay by immediately timestamping and dispatching packets
-//! - decouples receiving logic from actuation work (motors or grippers) to ensure minimal delay 
+//! - decouples receiving logic from actuation work (motors or grippers) to ensure minimal delay
+//! - tracks per-sensor sequence numbers (gaps, duplicates, reordering) with an optional reorder window
+//! - optionally publishes every arrival on the bus `processed` topic
+//! - runs the controller inside an RT section: no allocation per packet after startup
 
 
-use crossbeam::channel::Receiver;
+use crossbeam::channel::{Receiver, RecvTimeoutError, Select};
 use std::{
+    mem,
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use crate::component_a::{
     processor::ProcessedPacket,
+    sensor::{SensorType, SENSOR_COUNT},
     sync_manager::SyncManager,
 };
 
-use crate::utils::metrics::{SharedMetrics, push_capped_u64, EventRecorder, Event};
+use crate::utils::metrics::{SharedMetrics, EventRecorder, Event};
+use crate::utils::bus::{Bus, Topic};
+use crate::utils::rt_alloc;
 
 use crate::component_b::{
+    autotune::AutotuneSetup,
+    control_law::{ControlLaw, LawConfig},
     controller::Controller,
     multi_actuator::MultiActuator,
     feedback::FeedbackLoop,
+    sequence::{ReorderConfig, SequenceMonitor},
+    setpoint::SetpointGenerator,
 };
 
+/// Name of the receiver's controller (tuning target, feedback and parameter-change label).
+pub const RECEIVER: &str = "Receiver";
+
 /// Receiving stage: bridges Processor → Controller → Actuators.
 /// Minimizes latency via non-blocking IPC and immediate hand-off.
 pub struct Receiving {
@@ -33,6 +46,9 @@ pub struct Receiving {
     multi_actuator: MultiActuator,
     metrics: SharedMetrics,
     event_recorder: Arc<EventRecorder>,
+    sequence: SequenceMonitor,
+    released: Vec<ProcessedPacket>,     // Reused buffer for packets leaving the sequence monitor
+    processed: Option<Topic<ProcessedPacket>>,
 }
 
 impl Receiving {
@@ -46,40 +62,148 @@ impl Receiving {
     ) -> Self {
         Self {
             rx,
-            controller: Controller::new(sync, feedback_loop, metrics.clone(), event_recorder.clone()),
+            controller: Controller::new(sync, feedback_loop, metrics.clone(), event_recorder.clone())
+                .with_name(RECEIVER),
             multi_actuator,
             metrics,
             event_recorder,
+            sequence: SequenceMonitor::new(None),
+            released: Vec::with_capacity(1),
+            processed: None,
         }
     }
 
+    /// Publishes every arrival on the bus `processed` topic; the controller
+    /// subscribes to remote `commands`.
+    pub fn with_bus(mut self, bus: &Bus) -> Self {
+        self.processed = Some(bus.processed.clone());
+        self.controller.subscribe_commands(&bus.commands);
+        self
+    }
+
+    /// Replaces the receiver controller's default PID with `law`, run at most once per
+    /// `config.sample_s`, with `config`'s auto-tune settings.
+    pub fn with_law(mut self, law: Box<dyn ControlLaw>, config: &LawConfig) -> Self {
+        self.controller = self
+            .controller
+            .with_law(law)
+            .with_sample_time(config.sample_s)
+            .with_autotune(AutotuneSetup::new(config));
+        self
+    }
+
+    /// Setpoint profiles the receiver controller tracks, one per sensor (by `SensorType::index`).
+    pub fn with_setpoints(mut self, setpoints: [SetpointGenerator; SENSOR_COUNT]) -> Self {
+        for (sensor, setpoint) in SensorType::ALL.into_iter().zip(setpoints) {
+            self.controller = self.controller.with_setpoint(sensor, setpoint);
+        }
+        self
+    }
+
+    /// Enables the reorder window: packets up to `window` seqs past a missing one are held per
+    /// sensor (max `timeout`) so a late packet can be actuated in sequence. `window == 0` keeps
+    /// detection only. The reorder rings and the release buffer are allocated here.
+    pub fn with_reorder(mut self, window: usize, timeout: Duration) -> Self {
+        self.sequence = SequenceMonitor::new(Some(ReorderConfig { window, timeout }));
+        self.released = Vec::with_capacity(SENSOR_COUNT * (window + 1));   // A timeout sweep releases every stream
+        self
+    }
+
     /// Receive and dispatch packets to actuators.
     /// IPC: Crossbeam lock-free channel (non-blocking, bounded queue).
     /// Latency: Immediate timestamp, zero processing, decoupled actuation threads.
+    /// Sequence: gaps/duplicates/reordering detected per sensor before actuation.
     pub fn run(&mut self) {
-        while let Ok(packet) = self.rx.recv() {
-            // T3: ActuatorReceive event (timestamp on dequeue)
-            let t3_ns = self.event_recorder.now_ns();
-            self.event_recorder.record(Event::ActuatorReceive {
-                seq: packet.seq,
-                ts_ns: t3_ns,
-            });
-
-            // Measure end-to-end latency (Processor → Receiver)
-            let now = Instant::now();
-            let latency_us = now.duration_since(packet.timestamp).as_micros() as u64;
-            {
-                let mut m = match self.metrics.lock() {
-                    Ok(guard) => guard,
-                    Err(poisoned) => poisoned.into_inner(),
-                };
-                push_capped_u64(&mut m.latency_us, latency_us);
+        self.event_recorder.attach_thread();
+        self.controller.get_sync().attach_thread();
+
+        // Crossbeam allocates its per-thread wait context and the channel's waiter list the first
+        // time a receive blocks, and a contended `recv` may spin-and-yield past packets for a long
+        // time first. A non-blocking select on the empty channel always registers (then withdraws)
+        // a waiter; packets already queued are handled here, outside the RT section.
+        let mut first = loop {
+            let queued = {
+                let mut select = Select::new();
+                select.recv(&self.rx);
+                select.try_select().ok().map(|operation| operation.recv(&self.rx))
+            };
+            match queued {
+                Some(Ok(packet)) => self.dispatch(Some(packet)),
+                Some(Err(_)) => break None,
+                None => break self.rx.recv().ok(),
             }
+        };
+        let rt = rt_alloc::enter("receiver");
+
+        loop {
+            // Wake up for reorder timeouts while packets are held
+            let received = match (first.take(), self.sequence.next_deadline()) {
+                (Some(packet), _) => Some(packet),
+                (None, Some(deadline)) => match self.rx.recv_deadline(deadline) {
+                    Ok(packet) => Some(packet),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                },
+                (None, None) => match self.rx.recv() {
+                    Ok(packet) => Some(packet),
+                    Err(_) => break,
+                },
+            };
+
+            self.dispatch(received);
+        }
 
-            // Fast hand-off: controller + actuators process independently
+        drop(rt);
+
+        // Channel closed: actuate whatever the reorder window still holds
+        let mut released = mem::take(&mut self.released);
+        self.sequence.drain(&mut released);
+        self.actuate(&mut released);
+    }
+
+    /// Handles one arrival (if any) and actuates every packet the sequence monitor releases.
+    fn dispatch(&mut self, received: Option<ProcessedPacket>) {
+        let mut released = mem::take(&mut self.released);
+        if let Some(packet) = received {
+            self.on_arrival(packet, &mut released);
+        }
+        self.sequence.release_expired(Instant::now(), &mut released);
+        self.actuate(&mut released);
+        self.released = released;
+    }
+
+    /// Timestamps the arrival, measures latency and classifies the packet's sequence number.
+    fn on_arrival(&mut self, packet: ProcessedPacket, released: &mut Vec<ProcessedPacket>) {
+        // T3: ActuatorReceive event (timestamp on dequeue)
+        let t3_ns = self.event_recorder.now_ns();
+        self.event_recorder.record(Event::ActuatorReceive {
+            seq: packet.seq,
+            ts_ns: t3_ns,
+        });
+
+        // Measure end-to-end latency (Processor → Receiver)
+        let now = Instant::now();
+        let latency_us = now.duration_since(packet.timestamp).as_micros() as u64;
+        self.controller.record_rx_latency(latency_us);
+
+        if let Some(topic) = &self.processed {
+            topic.publish(packet.clone());
+        }
+
+        let sensor_type = packet.sensor_type;
+        self.sequence.accept(packet, now, released);
+        let seq_stats = self.sequence.stats(sensor_type);
+
+        let live = self.metrics.live();
+        live.latency_us.push(latency_us);
+        live.set_seq_stats(sensor_type, seq_stats);
+    }
+
+    /// Fast hand-off: controller + actuators process independently
+    fn actuate(&mut self, released: &mut Vec<ProcessedPacket>) {
+        for packet in released.drain(..) {
             self.controller.handle_packet(&packet);
-            self.controller.record_rx_latency(latency_us);
             self.multi_actuator.dispatch(packet, self.controller.get_sync().clone());
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,453 @@
+//! routing.rs
+//! Sensor → actuator routing table (`[[routing.actuator]]`) and its delivery statistics.
+//!
+//! - Each entry is one actuator thread: its name (thread, feedback and metrics key), the role
+//!   whose per-actuator tables it uses (`[control.<role>]`, `[actuator.<role>]`,
+//!   `[failsafe.<role>]`, freshness), the sensors routed to it, its channel capacity and its
+//!   thread priority. Without entries the table is the fixed Force → Gripper,
+//!   Position → Motor, Temperature → Stabiliser routing (capacity 8, max priority).
+//! - Fan-out: a sensor listed by several actuators is copied to each of their channels. Fan-in:
+//!   an actuator listing several sensors runs its controller on the packets of each.
+//! - The first sensor of an actuator is its primary: the setpoint profile it tracks, and the plant
+//!   that sensor measures when it is the first actuator (in table order) with that primary.
+//! - Delivery statistics: `RouteStats` atomics updated by `MultiActuator::dispatch` on the
+//!   receiver thread (no allocation); the run loop publishes `RoutingStats` snapshots
+//!   (dashboard, run summary).
+
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex, atomic::{AtomicU64, AtomicUsize, Ordering}},
+};
+
+use log::{error, warn};
+use serde::Deserialize;
+use thread_priority::{ThreadPriority, ThreadPriorityValue};
+
+use crate::component_a::sensor::{SensorType, SENSOR_COUNT};
+
+pub const MAX_ACTUATORS: usize = 8;     // Actuator threads; keeps the named live counters in bounds
+const DEFAULT_CAPACITY: usize = 8;      // Bounded queue per actuator
+
+/// Names of configured actuators, leaked once per distinct name (sweep levels reuse them).
+static INTERNED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Per-actuator config tables an actuator uses; also its waveform series on the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActuatorRole {
+    Gripper,
+    Motor,
+    Stabiliser,
+}
+
+impl ActuatorRole {
+    pub const ALL: [ActuatorRole; 3] = [ActuatorRole::Gripper, ActuatorRole::Motor, ActuatorRole::Stabiliser];
+
+    /// Name of the role's actuator in the default table.
+    pub fn name(self) -> &'static str {
+        match self {
+            ActuatorRole::Gripper => "Gripper",
+            ActuatorRole::Motor => "Motor",
+            ActuatorRole::Stabiliser => "Stabiliser",
+        }
+    }
+
+    /// Sensor routed to the role's actuator in the default table.
+    pub fn sensor(self) -> SensorType {
+        match self {
+            ActuatorRole::Gripper => SensorType::Force,
+            ActuatorRole::Motor => SensorType::Position,
+            ActuatorRole::Stabiliser => SensorType::Temperature,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|role| role.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Named thread priority (`"max"`, `"min"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityName {
+    Max,
+    Min,
+}
+
+/// Actuator thread priority: `"max"`, `"min"` or a cross-platform level 0–99.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum PrioritySetting {
+    Level(u8),
+    Named(PriorityName),
+}
+
+impl Default for PrioritySetting {
+    fn default() -> Self {
+        PrioritySetting::Named(PriorityName::Max)
+    }
+}
+
+impl PrioritySetting {
+    /// Priority passed to `spawn_with_priority`; an out-of-range level falls back to max.
+    pub fn thread_priority(self) -> ThreadPriority {
+        match self {
+            PrioritySetting::Named(PriorityName::Max) => ThreadPriority::Max,
+            PrioritySetting::Named(PriorityName::Min) => ThreadPriority::Min,
+            PrioritySetting::Level(level) => ThreadPriorityValue::try_from(level)
+                .map_or(ThreadPriority::Max, ThreadPriority::Crossplatform),
+        }
+    }
+
+    /// As written in the config (`max`, `min` or the level).
+    pub fn label(self) -> String {
+        match self {
+            PrioritySetting::Named(PriorityName::Max) => "max".into(),
+            PrioritySetting::Named(PriorityName::Min) => "min".into(),
+            PrioritySetting::Level(level) => level.to_string(),
+        }
+    }
+}
+
+/// `[[routing.actuator]]`: one actuator thread and the sensors routed to it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActuatorRouteConfig {
+    pub name: String,
+    #[serde(default)]
+    pub role: Option<ActuatorRole>,     // None = the role named like the actuator
+    pub sensors: Vec<SensorType>,       // First = primary (setpoint, driven plant)
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,                // Bounded channel from the receiver
+    #[serde(default)]
+    pub priority: PrioritySetting,
+}
+
+fn default_capacity() -> usize {
+    DEFAULT_CAPACITY
+}
+
+/// `[routing]`: the actuator threads, in order.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RoutingConfig {
+    pub actuator: Vec<ActuatorRouteConfig>,
+}
+
+impl Default for RoutingConfig {
+    /// One actuator per role, fed by its sensor.
+    fn default() -> Self {
+        Self {
+            actuator: ActuatorRole::ALL
+                .iter()
+                .map(|&role| ActuatorRouteConfig {
+                    name: role.name().into(),
+                    role: Some(role),
+                    sensors: vec![role.sensor()],
+                    capacity: DEFAULT_CAPACITY,
+                    priority: PrioritySetting::default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RoutingConfig {
+    /// Validated table. Entries without a role or a sensor, and duplicate names, are skipped;
+    /// at most `MAX_ACTUATORS`. Falls back to the default table when nothing is left.
+    pub fn build(&self) -> RoutingTable {
+        let mut actuators: Vec<ActuatorRoute> = Vec::new();
+
+        for entry in &self.actuator {
+            let Some(role) = entry.role.or_else(|| ActuatorRole::from_name(&entry.name)) else {
+                error!("[Routing] Actuator {:?} has no role (gripper, motor or stabiliser); skipped", entry.name);
+                continue;
+            };
+            if entry.name.is_empty() || actuators.iter().any(|a| a.name == entry.name) {
+                error!("[Routing] Actuator name {:?} is empty or already used; skipped", entry.name);
+                continue;
+            }
+            let mut sensors: Vec<SensorType> = Vec::with_capacity(entry.sensors.len());
+            for &sensor in &entry.sensors {
+                if !sensors.contains(&sensor) {
+                    sensors.push(sensor);
+                }
+            }
+            if sensors.is_empty() {
+                error!("[Routing] Actuator {:?} has no sensor; skipped", entry.name);
+                continue;
+            }
+            if actuators.len() == MAX_ACTUATORS {
+                error!("[Routing] More than {} actuators; {:?} and later skipped", MAX_ACTUATORS, entry.name);
+                break;
+            }
+            if let PrioritySetting::Level(level) = entry.priority
+                && level > *ThreadPriorityValue::MAX
+            {
+                warn!("[Routing] {} priority {} above {}; using max", entry.name, level, *ThreadPriorityValue::MAX);
+            }
+
+            let primary = sensors[0];
+            actuators.push(ActuatorRoute {
+                name: intern(&entry.name),
+                role,
+                drives_plant: !actuators.iter().any(|a| a.sensors[0] == primary),
+                series: !actuators.iter().any(|a| a.role == role),
+                sensors,
+                capacity: entry.capacity.max(1),
+                priority: entry.priority,
+            });
+        }
+
+        if actuators.is_empty() {
+            error!("[Routing] No valid actuator; using the default routing");
+            return RoutingConfig::default().build();
+        }
+        RoutingTable::new(actuators)
+    }
+}
+
+/// `&'static` actuator name (thread, feedback and metrics key).
+fn intern(name: &str) -> &'static str {
+    if let Some(role) = ActuatorRole::ALL.into_iter().find(|role| role.name() == name) {
+        return role.name();
+    }
+    let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&known) = interned.iter().find(|&&known| known == name) {
+        return known;
+    }
+    let leaked: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    interned.push(leaked);
+    leaked
+}
+
+/// One actuator of the table.
+#[derive(Debug, Clone)]
+pub struct ActuatorRoute {
+    pub name: &'static str,
+    pub role: ActuatorRole,
+    pub sensors: Vec<SensorType>,       // Primary first
+    pub capacity: usize,
+    pub priority: PrioritySetting,
+    pub drives_plant: bool,             // First actuator with this primary sensor
+    pub series: bool,                   // First actuator with this role (dashboard waveform)
+}
+
+impl ActuatorRoute {
+    pub fn primary(&self) -> SensorType {
+        self.sensors[0]
+    }
+}
+
+/// One sensor → actuator delivery path.
+#[derive(Debug, Clone, Copy)]
+pub struct Route {
+    pub sensor: SensorType,
+    pub actuator: usize,                // Index in the table
+}
+
+/// Actuators, routes grouped by sensor, and the shared delivery counters.
+#[derive(Debug, Clone)]
+pub struct RoutingTable {
+    actuators: Vec<ActuatorRoute>,
+    routes: Vec<Route>,                             // Sorted by sensor, then actuator
+    by_sensor: [Range<usize>; SENSOR_COUNT],        // Routes of each sensor (by `SensorType::index`)
+    stats: Arc<RouteStats>,
+}
+
+impl RoutingTable {
+    fn new(actuators: Vec<ActuatorRoute>) -> Self {
+        let mut routes = Vec::new();
+        let by_sensor = SensorType::ALL.map(|sensor| {
+            let start = routes.len();
+            for (actuator, route) in actuators.iter().enumerate() {
+                if route.sensors.contains(&sensor) {
+                    routes.push(Route { sensor, actuator });
+                }
+            }
+            start..routes.len()
+        });
+        for sensor in SensorType::ALL {
+            if by_sensor[sensor.index()].is_empty() {
+                warn!("[Routing] {} is routed to no actuator; its packets are dropped", sensor.name());
+            }
+        }
+        let stats = Arc::new(RouteStats::new(routes.len(), actuators.len()));
+        Self { actuators, routes, by_sensor, stats }
+    }
+
+    pub fn actuators(&self) -> &[ActuatorRoute] {
+        &self.actuators
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.actuators.iter().map(|a| a.name).collect()
+    }
+
+    /// Route indices of `sensor` (index into `route`).
+    #[inline]
+    pub fn routes_from(&self, sensor: SensorType) -> Range<usize> {
+        self.by_sensor[sensor.index()].clone()
+    }
+
+    #[inline]
+    pub fn route(&self, index: usize) -> Route {
+        self.routes[index]
+    }
+
+    #[inline]
+    pub fn stats(&self) -> &RouteStats {
+        &self.stats
+    }
+
+    /// `Force → Gripper, Motor; Position → Motor; ...` (startup log).
+    pub fn describe(&self) -> String {
+        SensorType::ALL
+            .iter()
+            .map(|&sensor| {
+                let targets: Vec<_> = self.routes[self.routes_from(sensor)]
+                    .iter()
+                    .map(|r| self.actuators[r.actuator].name)
+                    .collect();
+                let targets = if targets.is_empty() { "-".to_string() } else { targets.join(", ") };
+                format!("{} → {}", sensor.name(), targets)
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Current delivery statistics per route, per sensor (fan-out) and per actuator (fan-in).
+    pub fn snapshot(&self) -> RoutingStats {
+        let s = &self.stats;
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        let routes: Vec<RouteDelivery> = self
+            .routes
+            .iter()
+            .enumerate()
+            .map(|(i, r)| RouteDelivery {
+                sensor: r.sensor.name(),
+                actuator: self.actuators[r.actuator].name,
+                delivered: load(&s.delivered[i]),
+                dropped: load(&s.dropped[i]),
+            })
+            .collect();
+
+        let fan_out = SensorType::ALL
+            .iter()
+            .map(|&sensor| {
+                let range = self.routes_from(sensor);
+                FanOut {
+                    sensor: sensor.name(),
+                    actuators: range.len(),
+                    packets: load(&s.packets[sensor.index()]),
+                    delivered: routes[range.clone()].iter().map(|r| r.delivered).sum(),
+                    dropped: routes[range].iter().map(|r| r.dropped).sum(),
+                }
+            })
+            .collect();
+
+        let fan_in = self
+            .actuators
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let inbound = || self.routes.iter().zip(&routes).filter(move |(r, _)| r.actuator == i);
+                FanIn {
+                    actuator: a.name,
+                    role: a.role,
+                    sensors: a.sensors.len(),
+                    capacity: a.capacity,
+                    priority: a.priority,
+                    received: inbound().map(|(_, d)| d.delivered).sum(),
+                    dropped: inbound().map(|(_, d)| d.dropped).sum(),
+                    max_depth: s.max_depth[i].load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+
+        RoutingStats { routes, fan_out, fan_in }
+    }
+}
+
+/// Delivery counters, written by the receiver thread (`MultiActuator::dispatch`).
+#[derive(Debug)]
+pub struct RouteStats {
+    packets: [AtomicU64; SENSOR_COUNT],     // Packets dispatched per sensor
+    delivered: Box<[AtomicU64]>,            // Per route
+    dropped: Box<[AtomicU64]>,              // Per route: actuator channel full
+    max_depth: Box<[AtomicUsize]>,          // Per actuator: deepest channel after a delivery
+}
+
+impl RouteStats {
+    fn new(routes: usize, actuators: usize) -> Self {
+        Self {
+            packets: std::array::from_fn(|_| AtomicU64::new(0)),
+            delivered: (0..routes).map(|_| AtomicU64::new(0)).collect(),
+            dropped: (0..routes).map(|_| AtomicU64::new(0)).collect(),
+            max_depth: (0..actuators).map(|_| AtomicUsize::new(0)).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn packet(&self, sensor: SensorType) {
+        self.packets[sensor.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Packet queued on route `route`; `depth` is the actuator's channel length after it.
+    #[inline]
+    pub fn delivered(&self, route: usize, actuator: usize, depth: usize) {
+        self.delivered[route].fetch_add(1, Ordering::Relaxed);
+        self.max_depth[actuator].fetch_max(depth, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn dropped(&self, route: usize) {
+        self.dropped[route].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Deliveries on one sensor → actuator route (snapshot).
+#[derive(Debug, Clone)]
+pub struct RouteDelivery {
+    pub sensor: &'static str,
+    pub actuator: &'static str,
+    pub delivered: u64,
+    pub dropped: u64,               // Actuator channel full
+}
+
+/// Fan-out of one sensor (snapshot).
+#[derive(Debug, Clone)]
+pub struct FanOut {
+    pub sensor: &'static str,
+    pub actuators: usize,           // Routes from this sensor; 0 = packets dropped unrouted
+    pub packets: u64,               // Dispatched by the receiver
+    pub delivered: u64,             // Copies queued, over all routes
+    pub dropped: u64,
+}
+
+/// Fan-in of one actuator (snapshot).
+#[derive(Debug, Clone)]
+pub struct FanIn {
+    pub actuator: &'static str,
+    pub role: ActuatorRole,
+    pub sensors: usize,             // Routes into this actuator
+    pub capacity: usize,
+    pub priority: PrioritySetting,
+    pub received: u64,              // Over all its routes
+    pub dropped: u64,
+    pub max_depth: usize,
+}
+
+/// Delivery ratio of `delivered` against `dropped` (1 when nothing was offered).
+pub fn delivery_ratio(delivered: u64, dropped: u64) -> f64 {
+    let offered = delivered + dropped;
+    if offered == 0 { 1.0 } else { delivered as f64 / offered as f64 }
+}
+
+/// Routing delivery statistics (run loop → metrics; dashboard, run summary).
+#[derive(Debug, Clone, Default)]
+pub struct RoutingStats {
+    pub routes: Vec<RouteDelivery>,
+    pub fan_out: Vec<FanOut>,
+    pub fan_in: Vec<FanIn>,
+}
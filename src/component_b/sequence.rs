@@ -0,0 +1,475 @@
+//! sequence.rs
+//! Per-sensor sequence tracking at the receiver: gap, duplicate and out-of-order detection.
+//!
+//! - Every sensor numbers its samples from 1; the receiver tracks the highest `seq` seen per sensor.
+//! - A 64-slot history bitmap separates late arrivals (recovered loss) from duplicates.
+//! - Slots that leave the history window unreceived are finalised as lost (burst length tracked).
+//! - Optional reorder window: holds packets until the missing `seq` arrives, a packet more than
+//!   `window` past it arrives, or the oldest held packet exceeds the timeout; skipped sequence
+//!   numbers are released as lost. Held packets live in a fixed ring (no allocation per packet).
+
+use std::time::{Duration, Instant};
+
+use crate::component_a::{
+    processor::ProcessedPacket,
+    sensor::{SensorType, SENSOR_COUNT},
+};
+use crate::utils::metrics::SequenceStats;
+
+const HISTORY_SLOTS: u64 = 64;           // Late arrivals older than this are no longer classified
+
+/// Classification of one arrival relative to the sensor's sequence history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqOutcome {
+    First,                  // First packet seen from this sensor
+    InOrder,                // seq == highest + 1
+    Gap { missing: u64 },   // seq jumped ahead; `missing` packets not (yet) received
+    OutOfOrder,             // Late arrival of a previously missing seq
+    Duplicate,              // seq already received; discarded
+}
+
+/// Reorder window: packets up to `window` sequence numbers past the next one due are held per
+/// sensor, each for at most `timeout`.
+#[derive(Debug, Clone, Copy)]
+pub struct ReorderConfig {
+    pub window: usize,
+    pub timeout: Duration,
+}
+
+/// Loss accounting for one sensor stream.
+#[derive(Debug, Default)]
+struct SeqTracker {
+    base: Option<u64>,      // First seq observed
+    highest: u64,
+    history: u64,           // Bit k set => seq (highest - k) received
+    loss_run: u64,          // Current run of finalised losses
+    stats: SequenceStats,   // `lost` / `max_burst_loss` hold finalised values only
+}
+
+impl SeqTracker {
+    fn observe(&mut self, seq: u64) -> SeqOutcome {
+        let base = match self.base {
+            Some(b) => b,
+            None => {
+                self.base = Some(seq);
+                self.highest = seq;
+                self.history = 1;
+                self.stats.received += 1;
+                return SeqOutcome::First;
+            }
+        };
+
+        if seq > self.highest {
+            let shift = seq - self.highest;
+            self.retire(shift, base);
+            self.history = if shift >= HISTORY_SLOTS {
+                1
+            } else {
+                (self.history << shift) | 1
+            };
+            self.highest = seq;
+            self.stats.received += 1;
+
+            if shift > 1 {
+                self.stats.gaps += 1;
+                SeqOutcome::Gap { missing: shift - 1 }
+            } else {
+                SeqOutcome::InOrder
+            }
+        } else {
+            let age = self.highest - seq;
+            if seq < base || age >= HISTORY_SLOTS {
+                // Already finalised as lost; counted as reordering only
+                self.stats.out_of_order += 1;
+                return SeqOutcome::OutOfOrder;
+            }
+
+            let bit = 1u64 << age;
+            if self.history & bit != 0 {
+                self.stats.duplicates += 1;
+                SeqOutcome::Duplicate
+            } else {
+                self.history |= bit;
+                self.stats.received += 1;
+                self.stats.out_of_order += 1;
+                SeqOutcome::OutOfOrder
+            }
+        }
+    }
+
+    /// Finalises history slots pushed out of the window by a jump of `shift`, oldest first.
+    fn retire(&mut self, shift: u64, base: u64) {
+        let valid_slots = self.highest - base + 1;
+        let first_retired = HISTORY_SLOTS.saturating_sub(shift);
+
+        for k in (first_retired..HISTORY_SLOTS).rev() {
+            if k < valid_slots {
+                self.finalise(self.history & (1u64 << k) != 0, 1);
+            }
+        }
+
+        // Gap longer than the window: those sequence numbers never entered the history
+        if shift > HISTORY_SLOTS {
+            self.finalise(false, shift - HISTORY_SLOTS);
+        }
+    }
+
+    fn finalise(&mut self, received: bool, count: u64) {
+        if received {
+            self.loss_run = 0;
+        } else {
+            self.stats.lost += count;
+            self.loss_run += count;
+            self.stats.max_burst_loss = self.stats.max_burst_loss.max(self.loss_run);
+        }
+    }
+
+    /// Finalised counters plus losses still pending inside the history window.
+    fn snapshot(&self) -> SequenceStats {
+        let mut s = self.stats;
+        let base = match self.base {
+            Some(b) => b,
+            None => return s,
+        };
+
+        let mut run = self.loss_run;
+        for k in (0..(self.highest - base + 1).min(HISTORY_SLOTS)).rev() {
+            if self.history & (1u64 << k) == 0 {
+                s.lost += 1;
+                run += 1;
+                s.max_burst_loss = s.max_burst_loss.max(run);
+            } else {
+                run = 0;
+            }
+        }
+
+        s.expected = self.highest - base + 1;
+        s
+    }
+}
+
+#[derive(Default)]
+struct Stream {
+    tracker: SeqTracker,
+    held: Box<[Option<(Instant, ProcessedPacket)>]>,   // Reorder ring of `window + 1` slots, by seq % len
+    held_count: usize,                                  // Occupied ring slots
+    next_release: Option<u64>,
+}
+
+impl Stream {
+    fn with_window(window: usize) -> Self {
+        Self {
+            held: (0..=window).map(|_| None).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Held seqs always lie in `next_release ..= next_release + window`, so ring slots never collide.
+    fn slot(&self, seq: u64) -> usize {
+        (seq % self.held.len() as u64) as usize
+    }
+
+    fn take(&mut self, seq: u64) -> Option<ProcessedPacket> {
+        let slot = self.slot(seq);
+        if self.held[slot].as_ref().is_some_and(|(_, pkt)| pkt.seq == seq) {
+            self.held_count -= 1;
+            self.held[slot].take().map(|(_, pkt)| pkt)
+        } else {
+            None
+        }
+    }
+
+    fn hold(&mut self, pkt: ProcessedPacket, now: Instant, out: &mut Vec<ProcessedPacket>) {
+        let next = *self.next_release.get_or_insert(pkt.seq);
+        if pkt.seq < next {
+            // Its slot was already released past: too late to reorder
+            self.tracker.stats.late_discarded += 1;
+            return;
+        }
+
+        // Past the window: give up on the missing seqs it would leave behind
+        let window = self.held.len() as u64 - 1;
+        if pkt.seq - next > window {
+            self.skip_to(pkt.seq - window, out);
+        }
+
+        let slot = self.slot(pkt.seq);
+        if self.held[slot].replace((now, pkt)).is_none() {
+            self.held_count += 1;
+        }
+        self.release_contiguous(out);
+    }
+
+    fn release_contiguous(&mut self, out: &mut Vec<ProcessedPacket>) {
+        while let Some(next) = self.next_release {
+            match self.take(next) {
+                Some(pkt) => {
+                    out.push(pkt);
+                    self.next_release = Some(next + 1);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Releases every held packet below `target` in seq order and resumes release there.
+    fn skip_to(&mut self, target: u64, out: &mut Vec<ProcessedPacket>) {
+        let Some(next) = self.next_release else { return };
+        for seq in next..target.min(next + self.held.len() as u64) {
+            if let Some(pkt) = self.take(seq) {
+                out.push(pkt);
+            }
+        }
+        self.next_release = Some(target.max(next));
+        self.release_contiguous(out);
+    }
+
+    /// Gives up on the missing seq: resumes release at the lowest held packet.
+    fn skip_to_lowest(&mut self, out: &mut Vec<ProcessedPacket>) {
+        let Some(next) = self.next_release else { return };
+        let lowest = (next..next + self.held.len() as u64)
+            .find(|&seq| self.held[self.slot(seq)].as_ref().is_some_and(|(_, pkt)| pkt.seq == seq));
+        if let Some(lowest) = lowest {
+            self.next_release = Some(lowest);
+            self.release_contiguous(out);
+        }
+    }
+
+    fn release_expired(&mut self, now: Instant, timeout: Duration, out: &mut Vec<ProcessedPacket>) {
+        while self
+            .held
+            .iter()
+            .flatten()
+            .any(|(arrived, _)| now.duration_since(*arrived) >= timeout)
+        {
+            self.skip_to_lowest(out);
+        }
+    }
+
+    fn oldest_arrival(&self) -> Option<Instant> {
+        self.held.iter().flatten().map(|(arrived, _)| *arrived).min()
+    }
+}
+
+/// Receiver-side sequence monitor: one stream per sensor, optional reorder window.
+///
+/// Packets released for actuation are appended to the caller's `out` buffer in seq order
+/// (when reordering) or arrival order (detection only). Duplicates are never released.
+#[derive(Default)]
+pub struct SequenceMonitor {
+    reorder: Option<ReorderConfig>,
+    streams: [Stream; SENSOR_COUNT],     // By `SensorType::index`
+}
+
+impl SequenceMonitor {
+    /// `None` = detection only; packets pass straight through. With a window every stream's
+    /// reorder ring is allocated here.
+    pub fn new(reorder: Option<ReorderConfig>) -> Self {
+        let reorder = reorder.filter(|c| c.window > 0);
+        Self {
+            reorder,
+            streams: std::array::from_fn(|_| match reorder {
+                Some(cfg) => Stream::with_window(cfg.window),
+                None => Stream::default(),
+            }),
+        }
+    }
+
+    /// Classifies an arrival and appends any packets now ready for actuation to `out`.
+    pub fn accept(&mut self, pkt: ProcessedPacket, now: Instant, out: &mut Vec<ProcessedPacket>) -> SeqOutcome {
+        let reorder = self.reorder;
+        let stream = &mut self.streams[pkt.sensor_type.index()];
+        let outcome = stream.tracker.observe(pkt.seq);
+
+        if outcome == SeqOutcome::Duplicate {
+            return outcome;
+        }
+
+        match reorder {
+            Some(_) => stream.hold(pkt, now, out),
+            None => out.push(pkt),
+        }
+        outcome
+    }
+
+    /// Releases held packets whose wait exceeded the reorder timeout.
+    pub fn release_expired(&mut self, now: Instant, out: &mut Vec<ProcessedPacket>) {
+        if let Some(cfg) = self.reorder {
+            for stream in &mut self.streams {
+                stream.release_expired(now, cfg.timeout, out);
+            }
+        }
+    }
+
+    /// Earliest instant at which a held packet times out (None if nothing is held).
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let cfg = self.reorder?;
+        self.streams
+            .iter()
+            .filter_map(Stream::oldest_arrival)
+            .min()
+            .map(|t| t + cfg.timeout)
+    }
+
+    /// Shutdown: releases every held packet in seq order.
+    pub fn drain(&mut self, out: &mut Vec<ProcessedPacket>) {
+        for stream in &mut self.streams {
+            while stream.held_count > 0 {
+                stream.skip_to_lowest(out);
+            }
+        }
+    }
+
+    pub fn stats(&self, sensor: SensorType) -> SequenceStats {
+        self.streams[sensor.index()].tracker.snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(seq: u64) -> ProcessedPacket {
+        let now = Instant::now();
+        ProcessedPacket {
+            sensor_type: SensorType::Force,
+            filtered: 0.0,
+            raw: 0.0,
+            timestamp: now,
+            sensor_timestamp: now,
+            seq,
+        }
+    }
+
+    fn reorder(window: usize) -> SequenceMonitor {
+        SequenceMonitor::new(Some(ReorderConfig { window, timeout: Duration::from_millis(10) }))
+    }
+
+    /// Seqs released by accepting `seqs` in order at `now`.
+    fn accept_all(monitor: &mut SequenceMonitor, seqs: &[u64], now: Instant) -> Vec<u64> {
+        let mut out = Vec::new();
+        for &seq in seqs {
+            monitor.accept(packet(seq), now, &mut out);
+        }
+        out.iter().map(|p| p.seq).collect()
+    }
+
+    #[test]
+    fn gaps_and_duplicates_are_classified() {
+        let mut t = SeqTracker::default();
+        assert_eq!(t.observe(1), SeqOutcome::First);
+        assert_eq!(t.observe(2), SeqOutcome::InOrder);
+        assert_eq!(t.observe(5), SeqOutcome::Gap { missing: 2 });
+        assert_eq!(t.observe(5), SeqOutcome::Duplicate);
+        assert_eq!(t.observe(2), SeqOutcome::Duplicate);
+
+        let s = t.snapshot();
+        assert_eq!((s.expected, s.received, s.lost), (5, 3, 2));
+        assert_eq!((s.gaps, s.duplicates, s.max_burst_loss), (1, 2, 2));
+    }
+
+    #[test]
+    fn late_arrival_inside_the_history_is_recovered() {
+        let mut t = SeqTracker::default();
+        for seq in [1, 4, 3] {
+            t.observe(seq);
+        }
+        assert_eq!(t.snapshot().lost, 1);
+        assert_eq!(t.observe(2), SeqOutcome::OutOfOrder);
+
+        let s = t.snapshot();
+        assert_eq!((s.received, s.lost, s.out_of_order), (4, 0, 2));
+    }
+
+    #[test]
+    fn late_arrival_outside_the_history_stays_lost() {
+        let mut t = SeqTracker::default();
+        t.observe(1);
+        for seq in 3..=70 {
+            t.observe(seq);
+        }
+        // seq 2 left the 64-slot history when seq 66 arrived: finalised as lost
+        assert_eq!(t.stats.lost, 1);
+        assert_eq!(t.observe(2), SeqOutcome::OutOfOrder);
+
+        let s = t.snapshot();
+        assert_eq!((s.expected, s.received, s.lost, s.out_of_order), (70, 69, 1, 1));
+    }
+
+    #[test]
+    fn burst_longer_than_the_history_is_counted_once() {
+        let mut t = SeqTracker::default();
+        t.observe(1);
+        assert_eq!(t.observe(200), SeqOutcome::Gap { missing: 198 });
+        // 135 seqs never entered the history, 63 are still pending inside it
+        assert_eq!(t.stats.lost, 135);
+        assert_eq!((t.snapshot().lost, t.snapshot().max_burst_loss), (198, 198));
+
+        for seq in 201..=300 {
+            t.observe(seq);
+        }
+        t.observe(290);                 // Duplicate: no effect on loss
+        let s = t.snapshot();
+        assert_eq!((t.stats.lost, t.stats.max_burst_loss), (198, 198));
+        assert_eq!((s.expected, s.received, s.lost, s.gaps, s.duplicates), (300, 102, 198, 1, 1));
+    }
+
+    #[test]
+    fn reorder_window_releases_a_late_packet_in_sequence() {
+        let mut monitor = reorder(4);
+        let now = Instant::now();
+        assert_eq!(accept_all(&mut monitor, &[1, 3, 4], now), [1]);
+        assert_eq!(accept_all(&mut monitor, &[2], now), [2, 3, 4]);
+        assert_eq!(accept_all(&mut monitor, &[5, 5], now), [5]);
+
+        let s = monitor.stats(SensorType::Force);
+        assert_eq!((s.lost, s.out_of_order, s.duplicates, s.late_discarded), (0, 1, 1, 0));
+        assert_eq!(monitor.next_deadline(), None);
+    }
+
+    #[test]
+    fn packet_past_the_window_skips_the_missing_seq() {
+        let mut monitor = reorder(2);
+        let now = Instant::now();
+        assert_eq!(accept_all(&mut monitor, &[1, 3, 4], now), [1]);
+        // 5 is 3 past the missing 2: give up on it (the ring wraps past its 3 slots)
+        assert_eq!(accept_all(&mut monitor, &[5], now), [3, 4, 5]);
+        assert_eq!(accept_all(&mut monitor, &[2], now), Vec::<u64>::new());
+        assert_eq!(monitor.stats(SensorType::Force).late_discarded, 1);
+
+        // Far ahead of anything held: 7 goes first, 20 still waits for the 2 seqs before it
+        assert_eq!(accept_all(&mut monitor, &[7, 20], now), [7]);
+        assert_eq!(accept_all(&mut monitor, &[18, 19], now), [18, 19, 20]);
+    }
+
+    #[test]
+    fn reorder_timeout_releases_held_packets() {
+        let mut monitor = reorder(4);
+        let t0 = Instant::now();
+        assert_eq!(accept_all(&mut monitor, &[1, 3, 5], t0), [1]);
+        assert_eq!(monitor.next_deadline(), Some(t0 + Duration::from_millis(10)));
+
+        let mut out = Vec::new();
+        monitor.release_expired(t0 + Duration::from_millis(5), &mut out);
+        assert!(out.is_empty());
+        monitor.release_expired(t0 + Duration::from_millis(10), &mut out);
+        assert_eq!(out.iter().map(|p| p.seq).collect::<Vec<_>>(), [3, 5]);
+        assert_eq!(monitor.next_deadline(), None);
+
+        // Both skipped seqs are now too late; the tracker still counts them as recovered
+        assert_eq!(accept_all(&mut monitor, &[2, 4], t0), Vec::<u64>::new());
+        let s = monitor.stats(SensorType::Force);
+        assert_eq!((s.lost, s.late_discarded), (0, 2));
+    }
+
+    #[test]
+    fn drain_releases_every_held_packet_in_order() {
+        let mut monitor = reorder(4);
+        let now = Instant::now();
+        assert_eq!(accept_all(&mut monitor, &[1, 5, 3], now), [1]);
+
+        let mut out = Vec::new();
+        monitor.drain(&mut out);
+        assert_eq!(out.iter().map(|p| p.seq).collect::<Vec<_>>(), [3, 5]);
+    }
+}
@@ -0,0 +1,266 @@
+//! setpoint.rs
+//! Setpoint generators: the target each controller tracks, as a function of run time.
+//!
+//! - Profiles (`[setpoint]`, tagged by `profile`): constant, step (optionally a square wave),
+//!   ramp, sinusoid, piecewise schedule from a CSV file, external (injected only).
+//! - Injection: a `Setpoint` command on the bus `commands` topic overrides any profile until it
+//!   is resumed; with the `external` profile injected values are the profile itself.
+//! - Schedules are loaded when the generator is built (startup); evaluation is allocation-free
+//!   and runs on the RT threads.
+
+use std::{f64::consts::TAU, fs};
+
+use log::warn;
+use serde::Deserialize;
+
+use crate::component_a::sensor::SensorType;
+
+/// Setpoint profile and parameters as written in `[setpoint]` (tagged by `profile`).
+/// Times are seconds since the controller started (every sweep level restarts at 0).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "profile", rename_all = "snake_case")]
+pub enum SetpointSpec {
+    Constant { value: f64 },
+    Step {
+        value: f64,                 // Before `at_s`
+        amplitude: f64,             // Added at `at_s`
+        at_s: f64,
+        #[serde(default)]
+        period_s: f64,              // > 0: square wave, high for the first half of each period
+    },
+    Ramp {
+        value: f64,                 // Start value
+        target: f64,                // Held once reached
+        rate: f64,                  // Units per second
+        #[serde(default)]
+        start_s: f64,
+    },
+    Sine {
+        value: f64,                 // Mean
+        amplitude: f64,
+        period_s: f64,
+        #[serde(default)]
+        start_s: f64,
+    },
+    Schedule {
+        file: String,               // CSV rows `time_s,value` (header and `#` lines skipped)
+        #[serde(default)]
+        interpolate: bool,          // Linear between points; false = hold each value
+        #[serde(default)]
+        repeat: bool,               // Restart after the last point
+    },
+    External { value: f64 },        // Initial value; then the last injected one
+}
+
+impl SetpointSpec {
+    /// Nominal target of the actuator `sensor` is routed to (the former fixed setpoints).
+    pub fn default_for(sensor: SensorType) -> Self {
+        let value = match sensor {
+            SensorType::Force => 100.0,
+            SensorType::Position => 0.0,
+            SensorType::Temperature => 25.0,
+        };
+        SetpointSpec::Constant { value }
+    }
+
+    /// Builds the generator, loading a schedule file; an unreadable schedule falls back to the
+    /// nominal setpoint of `sensor`.
+    pub fn build(&self, sensor: SensorType) -> SetpointGenerator {
+        let points = match self {
+            SetpointSpec::Schedule { file, .. } => match load_schedule(file) {
+                Ok(points) => points,
+                Err(e) => {
+                    warn!("[Setpoint] {} schedule {:?}: {}; using the nominal setpoint", sensor.name(), file, e);
+                    return SetpointSpec::default_for(sensor).build(sensor);
+                }
+            },
+            _ => Vec::new(),
+        };
+        SetpointGenerator { spec: self.clone(), points, injected: None }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SetpointSpec::Constant { .. } => "constant",
+            SetpointSpec::Step { .. } => "step",
+            SetpointSpec::Ramp { .. } => "ramp",
+            SetpointSpec::Sine { .. } => "sine",
+            SetpointSpec::Schedule { .. } => "schedule",
+            SetpointSpec::External { .. } => "external",
+        }
+    }
+}
+
+/// Evaluates one setpoint profile; owned by the controller that tracks it.
+#[derive(Debug, Clone)]
+pub struct SetpointGenerator {
+    spec: SetpointSpec,
+    points: Vec<(f64, f64)>,        // Schedule `(time_s, value)`, ascending in time
+    injected: Option<f64>,          // Remote override
+}
+
+impl SetpointGenerator {
+    /// Target at `t_s` seconds since start (the injected value while one is active).
+    pub fn value(&self, t_s: f64) -> f64 {
+        if let Some(value) = self.injected {
+            return value;
+        }
+
+        match self.spec {
+            SetpointSpec::Constant { value } | SetpointSpec::External { value } => value,
+            SetpointSpec::Step { value, amplitude, at_s, period_s } => {
+                let since = t_s - at_s;
+                let high = since >= 0.0 && (period_s <= 0.0 || since % period_s < period_s / 2.0);
+                if high { value + amplitude } else { value }
+            }
+            SetpointSpec::Ramp { value, target, rate, start_s } => {
+                let travelled = rate.abs() * (t_s - start_s).max(0.0);
+                value + (target - value).clamp(-travelled, travelled)
+            }
+            SetpointSpec::Sine { value, amplitude, period_s, start_s } => {
+                if period_s <= 0.0 || t_s < start_s {
+                    return value;
+                }
+                value + amplitude * (TAU * (t_s - start_s) / period_s).sin()
+            }
+            SetpointSpec::Schedule { interpolate, repeat, .. } => self.scheduled(t_s, interpolate, repeat),
+        }
+    }
+
+    /// Remote setpoint: `Some` overrides the profile (and becomes an `external` profile's value),
+    /// `None` resumes the profile.
+    pub fn inject(&mut self, value: Option<f64>) {
+        match (&mut self.spec, value) {
+            (SetpointSpec::External { value: held }, Some(value)) => *held = value,
+            (_, value) => self.injected = value,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.spec.name()
+    }
+
+    /// Whether the target varies continuously (ramp, sine, interpolated schedule) rather than
+    /// in steps; an injected value is a step.
+    pub fn continuous(&self) -> bool {
+        self.injected.is_none()
+            && matches!(
+                self.spec,
+                SetpointSpec::Ramp { .. } | SetpointSpec::Sine { .. } | SetpointSpec::Schedule { interpolate: true, .. }
+            )
+    }
+
+    fn scheduled(&self, t_s: f64, interpolate: bool, repeat: bool) -> f64 {
+        let (Some(&first), Some(&last)) = (self.points.first(), self.points.last()) else {
+            return 0.0;
+        };
+        let t_s = if repeat && last.0 > 0.0 { t_s.rem_euclid(last.0) } else { t_s };
+
+        let next = self.points.partition_point(|&(t, _)| t <= t_s);
+        if next == 0 {
+            return first.1;
+        }
+        let (t0, v0) = self.points[next - 1];
+        match self.points.get(next) {
+            Some(&(t1, v1)) if interpolate && t1 > t0 => v0 + (v1 - v0) * (t_s - t0) / (t1 - t0),
+            _ => v0,
+        }
+    }
+}
+
+/// Reads `time_s,value` rows; lines that are not two numbers (header, comments) are skipped.
+fn load_schedule(path: &str) -> Result<Vec<(f64, f64)>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let points: Vec<(f64, f64)> = text
+        .lines()
+        .filter_map(|line| {
+            let (t, v) = line.split_once(',')?;
+            let point = (t.trim().parse::<f64>().ok()?, v.trim().parse::<f64>().ok()?);
+            (point.0.is_finite() && point.1.is_finite()).then_some(point)
+        })
+        .collect();
+
+    if points.is_empty() {
+        return Err("no `time_s,value` rows".into());
+    }
+    if points.windows(2).any(|w| w[1].0 < w[0].0) {
+        return Err("times must be ascending".into());
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generator(spec: SetpointSpec, points: Vec<(f64, f64)>) -> SetpointGenerator {
+        SetpointGenerator { spec, points, injected: None }
+    }
+
+    fn schedule(interpolate: bool, repeat: bool) -> SetpointGenerator {
+        let spec = SetpointSpec::Schedule { file: String::new(), interpolate, repeat };
+        generator(spec, vec![(0.0, 10.0), (2.0, 20.0), (4.0, 0.0)])
+    }
+
+    #[test]
+    fn ramp_clamps_at_target_in_both_directions() {
+        let up = generator(SetpointSpec::Ramp { value: 20.0, target: 30.0, rate: 0.5, start_s: 2.0 }, Vec::new());
+        assert_eq!(up.value(0.0), 20.0);
+        assert_eq!(up.value(6.0), 22.0);
+        assert_eq!(up.value(22.0), 30.0);
+        assert_eq!(up.value(1e6), 30.0);
+
+        // A negative rate still moves toward the target
+        let down = generator(SetpointSpec::Ramp { value: 30.0, target: 20.0, rate: -2.0, start_s: 0.0 }, Vec::new());
+        assert_eq!(down.value(1.0), 28.0);
+        assert_eq!(down.value(100.0), 20.0);
+    }
+
+    #[test]
+    fn square_wave_is_high_for_first_half_of_each_period() {
+        let step = generator(SetpointSpec::Step { value: 80.0, amplitude: 20.0, at_s: 5.0, period_s: 10.0 }, Vec::new());
+        assert_eq!(step.value(4.9), 80.0);      // Before the first edge
+        assert_eq!(step.value(5.0), 100.0);
+        assert_eq!(step.value(9.9), 100.0);
+        assert_eq!(step.value(10.0), 80.0);     // Second half
+        assert_eq!(step.value(15.0), 100.0);    // Next period
+
+        // Without a period the step stays high
+        let single = generator(SetpointSpec::Step { value: 0.0, amplitude: 1.0, at_s: 1.0, period_s: 0.0 }, Vec::new());
+        assert_eq!(single.value(0.5), 0.0);
+        assert_eq!(single.value(1e6), 1.0);
+    }
+
+    #[test]
+    fn schedule_holds_or_interpolates_between_points() {
+        let held = schedule(false, false);
+        assert_eq!(held.value(-1.0), 10.0);     // Before the first point: first value
+        assert_eq!(held.value(1.0), 10.0);
+        assert_eq!(held.value(2.0), 20.0);
+        assert_eq!(held.value(100.0), 0.0);     // After the last point: last value
+
+        let interpolated = schedule(true, false);
+        assert_eq!(interpolated.value(1.0), 15.0);
+        assert_eq!(interpolated.value(3.0), 10.0);
+        assert_eq!(interpolated.value(100.0), 0.0);
+        assert!(interpolated.continuous());
+        assert!(!held.continuous());
+    }
+
+    #[test]
+    fn schedule_repeats_after_last_point() {
+        let repeated = schedule(true, true);
+        assert_eq!(repeated.value(5.0), repeated.value(1.0));
+        assert_eq!(repeated.value(8.0), 10.0);
+        assert_eq!(repeated.value(11.0), 10.0);
+    }
+
+    #[test]
+    fn injection_overrides_and_resumes_profile() {
+        let mut g = generator(SetpointSpec::Constant { value: 25.0 }, Vec::new());
+        g.inject(Some(30.0));
+        assert_eq!(g.value(0.0), 30.0);
+        g.inject(None);
+        assert_eq!(g.value(0.0), 25.0);
+    }
+}
@@ -12,12 +12,16 @@
 //! ## Modes
 //! - **Single Run:** 30-second simulation with fixed CPU load (0 or user-specified threads).
 //! - **Sweep:** Iterates through load levels [0,2,4,8,12] measuring performance envelope.
+//! - **Priority inversion:** L/M/H SCHED_FIFO scenario, plain vs. priority-inheritance mutex.
 //!
 //! ## Key Architecture
 //! - **Sensors (3x):** Force, Position, Temperature at 5ms intervals → bounded channel (2048).
 //! - **Processor:** Anomaly detection (200µs deadline) with dynamic feedback-driven thresholds.
+//! - **Receiver:** Per-sensor sequence tracking (gaps, duplicates, reordering) before actuation.
 //! - **Actuators:** Execute commands, send feedback to processor (loop-close).
 //! - **CPU Load:** Background threads on `shared_core` create contention.
+//! - **Bus:** Typed pub/sub topics (sensor data, processed packets, feedback, anomalies);
+//!   consumers subscribe with their own bounded queue and overflow policy.
 //!
 //! ## Concurrency
 //! - Bounded channels with backpressure (sizes: 2048→1024→64).
@@ -26,8 +30,39 @@
 //!
 //! ## Outputs
 //! - `data/events_load_X.csv` — Sensor/actuator events (microsecond precision).
-//! - `data/logs/sync_events_load_X.csv` — Lock-free sync log (nanosecond precision).
+//! - `data/logs/sync_events_load_X.csv` — Lock-free sync log (nanosecond precision);
+//!   `.bin` instead with `[sync] log_format = "binary"` (decode with the `synclog` binary).
+//!
+//! ## Configuration
+//! - `config/config.toml` (optional): `[sync] mode` selects the SyncManager diagnostics sink;
+//!   `[logging]` sets rotation, compression and fsync of the CSV logs.
 //! - Dashboard: `http://127.0.0.1:8080`.
+//! - MQTT (optional): `rts/processed/*`, `rts/feedback/*`, `rts/metrics` on `127.0.0.1:1883`.
+//! - `[plant]` closes the loop: each actuator drives a plant model (first-order lag, DC motor,
+//!   thermal RC, mass-spring-damper) and the paired sensor samples its output plus noise.
+//! - `[control.<controller>]` selects the control law (PID, bang-bang, LQR, MPC) and its gains,
+//!   limits, anti-windup, derivative filter and period for the receiver and each actuator;
+//!   compute time per law is measured against the 2 ms actuator deadline. Gains can be retuned
+//!   live through the bus `commands` topic (MQTT `rts/cmd/tuning[/<controller>]`).
+//! - `[control.<controller>.autotune]` runs a relay experiment per controller at each load level
+//!   (or on `rts/cmd/autotune[/<controller>]`) and applies Ziegler–Nichols-style PID gains.
+//! - `[setpoint]` gives each actuator a setpoint profile (constant, step, ramp, sine, schedule file
+//!   or external); MQTT `rts/cmd/setpoint/<sensor>` injects a value or resumes the profile.
+//! - `[actuator.<actuator>]` models each actuator between command and state: saturation, slew
+//!   rate, dead band, backlash, lag and transport delay; limit onsets are reported as feedback.
+//! - Each actuator controller measures its step responses (rise, overshoot, settling, IAE/ISE/
+//!   ITAE) live; `control_steps_load_X.csv` lists every step (`--bin control_quality` per load).
+//! - `[failsafe]` runs a supervisor over sensor heartbeats, processor overruns, actuator staleness
+//!   and feedback errors: init → nominal → degraded / safe_stop → recovering, with a safe action
+//!   per actuator (continue, hold, ramp) and every transition logged and shown on the dashboard.
+//! - `[[faults.scenario]]` injects actuator faults (stuck, reduced gain, lag, intermittent,
+//!   inversion) for a time window; the detector classifies commanded vs observed responses into
+//!   `ActuatorFault` feedback, scored per scenario (latency, false-positive rate).
+//! - `[[routing.actuator]]` sets the actuator threads: the sensors routed to each (fan-out to
+//!   several actuators, fan-in from several sensors), the role whose tables it uses, its channel
+//!   capacity and thread priority; deliveries and drops are reported per route.
+//! - Debug builds install `RtAllocator`: any heap allocation on a sensor, processor, receiver or
+//!   actuator thread after startup is reported on stderr (no allocation expected).
 
 
 
@@ -37,36 +72,49 @@ mod utils;
 mod advanced;
 
 use component_a::{
-    sensor::{Sensor, SensorType, SensorData},
+    sensor::{Sensor, SensorType, SensorData, SENSOR_COUNT},
     processor::Processor,
     sync_manager::{SyncManager, SyncMode},
+    synclog::SyncLogFormat,
     transmitter::Transmitter,
 };
 
 use component_b::{
-    receiver::Receiving,
-    multi_actuator::MultiActuator,
+    receiver::{Receiving, RECEIVER},
+    autotune::AutotuneSetup,
+    multi_actuator::{ActuatorSetup, MultiActuator},
+    actuator_fault::spawn_fault_detector,
+    failsafe::{Watchdogs, spawn_supervisor},
     feedback::{FeedbackLoop},
+    plant::{Plant, PlantInput},
+    routing::RoutingTable,
 };
 
 use utils::{
     metrics::{
-    SharedMetrics, Metrics, EventRecorder},
-    export::{run_exports, spawn_feedback_handler},
+    SharedMetrics, EventRecorder},
+    export::{export_control_steps, run_exports, spawn_feedback_handler, spawn_anomaly_logger},
+    bus::{Bus, OverflowPolicy},
+    config::{load_config, DEFAULT_CONFIG_PATH},
+    logwriter::install_crash_flush,
+    rt_alloc,
 };
 
 use advanced::{
     dashboard::start_dashboard_system,
     cpu_load::spawn_cpu_load,
+    mqtt::{MqttBroker, start_mqtt_bridge},
+    priority_inversion::{InversionConfig, run_priority_inversion},
 };
 
 use crossbeam::channel::bounded;
 use std::{
     io::{ Write},
+    iter,
     path::Path,
-    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
     io::stdout,
     io::stdin,
     fs::{create_dir_all},
@@ -77,6 +125,13 @@ use log::{info, error};
 const DEFAULT_SIMULATION_DURATION_SECS: u64 = 30;
 const CPU_LOAD_SWEEP: &[usize] = &[0, 2, 4, 8, 12, 16, 18, 20];
 const DEFAULT_SHARED_CORE: usize = 0;
+// How often the run loop copies bus subscriber stats into the dashboard metrics.
+const BUS_STATS_INTERVAL_MS: u64 = 1000;
+
+// Debug builds: report heap allocations made inside RT sections (see rt_alloc.rs)
+#[cfg(debug_assertions)]
+#[global_allocator]
+static ALLOCATOR: rt_alloc::RtAllocator = rt_alloc::RtAllocator;
 
 //Maps sensor IDs to their respective names.
 fn sensor_name_map() -> HashMap<u16, String> {
@@ -90,6 +145,8 @@ fn sensor_name_map() -> HashMap<u16, String> {
 // Main entry point for the RTS simulation project.
 fn main() {
     env_logger::init();
+    // Flush open CSV logs on panic or Ctrl-C so an interrupted run keeps its tail
+    install_crash_flush();
     info!("=== RTS SIMULATION START ===");
     println!("check Dashboard live at: http://127.0.0.1:8080 ");
 
@@ -116,6 +173,14 @@ fn main() {
                 thread::sleep(Duration::from_secs(2));
             }
             "4" => {
+                let config = InversionConfig {
+                    core: prompt_core_id(),
+                    ..InversionConfig::default()
+                };
+                run_priority_inversion(&config);
+                println!("\n Priority-inversion demo completed. Returning to menu...\n");
+            }
+            "5" => {
                 println!("Exiting. Goodbye!");
                 info!("=== RTS SIMULATION FINISHED ===");
                 return;
@@ -135,9 +200,10 @@ fn prompt_menu() -> String {
     println!("│  1) WITH CPU load (single run)         │");
     println!("│  2) NO CPU load (single run)           │");
     println!("│  3) AUTO SWEEP [0,2,4,8,20]           │");
-    println!("│  4) Exit                               │");
+    println!("│  4) Priority-inversion demo (PI mutex) │");
+    println!("│  5) Exit                               │");
     println!("└─────────────────────────────────────────────┘");
-    print!("Select [1/2/3/4] (default: 2): ");
+    print!("Select [1/2/3/4/5] (default: 2): ");
     let _ = stdout().flush();
 
     let mut input = String::new();
@@ -162,9 +228,9 @@ fn prompt_core_id() -> usize {
 }
 
 fn run_simulation_with_dashboard(cpu_load_threads: usize, shared_core: usize) {
-    let metrics: SharedMetrics = Arc::new(Mutex::new(Metrics::default()));
+    let metrics: SharedMetrics = SharedMetrics::default();
     {
-        let mut m = metrics.lock().unwrap_or_else(|e| e.into_inner());
+        let mut m = metrics.lock_run();
         m.cpu_load_threads = cpu_load_threads;
     }
 
@@ -180,23 +246,19 @@ fn run_simulation_with_dashboard(cpu_load_threads: usize, shared_core: usize) {
 /// This function takes a list of CPU load thread counts and runs a simulation for each level.
 /// It starts a shared dashboard and reuses the same dashboard for all simulations in the sweep.
 /// stopped after all simulations are complete.
-
 fn run_sweep_series(sweep_levels: &[usize]) {
     println!("Starting automatic sweep with shared dashboard...");
     println!("Levels: {:?}", sweep_levels);
     println!("Core pinning: {}", DEFAULT_SHARED_CORE);
     
-    let dashboard_metrics: SharedMetrics = Arc::new(Mutex::new(Metrics::default()));
+    let dashboard_metrics: SharedMetrics = SharedMetrics::default();
     let (render_handle, web_handle, dashboard_running) = start_dashboard_system(dashboard_metrics.clone());
     info!("Dashboard: http://127.0.0.1:8080 (shared for entire sweep)");
     thread::sleep(Duration::from_millis(1500));
 
     for &level in sweep_levels {
-        {
-            let mut m = dashboard_metrics.lock().unwrap_or_else(|e| e.into_inner());
-            *m = Metrics::default();
-            m.cpu_load_threads = level;
-        }
+        dashboard_metrics.reset();
+        dashboard_metrics.lock_run().cpu_load_threads = level;
 
         info!("\n[SWEEP] Running level: cpu_load_threads={} on core {}", level, DEFAULT_SHARED_CORE);
         
@@ -246,14 +308,22 @@ fn run_simulation_internal(
     // ========================================================================
     // Event Recording System
     // ========================================================================
-    let event_recorder = Arc::new(EventRecorder::new());
+    let config = load_config(DEFAULT_CONFIG_PATH);
+    let log_policy = config.logging.policy();
+
+    let event_recorder = Arc::new(EventRecorder::new().with_log_policy(log_policy.clone()));
     
     let csv_path = format!("data/logs/events_load_{}.csv", cpu_load_threads);
     create_dir_all("data").ok();
-    let _exporter_handle = event_recorder.start_exporter(csv_path.clone(), cpu_load_threads);
+    let exporter_handle = event_recorder.start_exporter(csv_path.clone(), cpu_load_threads);
 
     let running = Arc::new(AtomicBool::new(true));
-    let sync = Arc::new(SyncManager::new(SyncMode::LockFree));
+    let sync = Arc::new(if config.sync.instrument {
+        SyncManager::instrumented(config.sync.mode)
+    } else {
+        SyncManager::new(config.sync.mode)
+    }.with_log_policy(log_policy.clone()));
+    info!("[Experiment] SyncManager mode: {:?} (instrumented: {})", sync.mode, sync.is_instrumented());
 
     if sync.mode == SyncMode::LockFree {
         let log_dir = Path::new("data/logs");
@@ -262,15 +332,27 @@ fn run_simulation_internal(
             return;
         }
 
-        let log_file = format!("data/logs/sync_events_load_{}.csv", cpu_load_threads);
-        sync.start_log_consumer(
-            log_file.into(),
-            Some(sensor_name_map()),
-        ).expect("Failed to start log consumer");
+        match config.sync.log_format {
+            SyncLogFormat::Csv => {
+                let log_file = format!("data/logs/sync_events_load_{}.csv", cpu_load_threads);
+                sync.start_log_consumer(
+                    log_file.into(),
+                    Some(sensor_name_map()),
+                ).expect("Failed to start log consumer");
+            }
+            SyncLogFormat::Binary => {
+                let log_file = format!("data/logs/sync_events_load_{}.bin", cpu_load_threads);
+                sync.start_binary_log_consumer(
+                    log_file.into(),
+                    Some(sensor_name_map()),
+                    &format!("load_{}", cpu_load_threads),
+                ).expect("Failed to start log consumer");
+            }
+        }
     }
 
     {
-        let mut m = metrics.lock().unwrap_or_else(|e| e.into_inner());
+        let mut m = metrics.lock_run();
         m.cpu_load_threads = cpu_load_threads;
     }
     
@@ -280,39 +362,110 @@ fn run_simulation_internal(
     let (tx_sensors, rx_proc) = bounded::<SensorData>(2048);
     let (tx_proc, rx_act) = bounded::<component_a::processor::ProcessedPacket>(1024);
 
+    // Pub/sub bus: every consumer registers its own bounded queue + overflow policy,
+    // so a slow logger can never hold back the processor (or the publishing actuator).
+    let bus = Bus::new();
+
     // Feedback loop enables dynamic threshold adjustment: actuators inform processor of state.
-    let (feedback_loop, feedback_rx_raw) = FeedbackLoop::new(64, event_recorder.clone());
-
-   
-    // Duplicate feedback: non-blocking sends to logger (CSV) and processor (recalibration).
-    // Prevents feedback thread blocking on either channel
-    let (tx_log, rx_log) = bounded(64);
-    let (tx_proc_feedback, rx_proc_feedback) = bounded(64);
-
-    thread::spawn(move || {
-        while let Ok(msg) = feedback_rx_raw.recv() {
-            // Non-blocking send to logger
-            let _ = tx_log.try_send(msg.clone());
-            // Non-blocking send to processor (for threshold adjustment)
-            let _ = tx_proc_feedback.try_send(msg);
+    let feedback_loop = FeedbackLoop::on_topic(bus.feedback.clone(), event_recorder.clone());
+    // Processor only needs the most recent feedback: evict the oldest when behind.
+    let rx_proc_feedback = bus.feedback.subscribe("processor", 64, OverflowPolicy::DropOldest);
+
+    // Spawn feedback handler thread (logs feedback to CSV)
+    let feedback_handler = spawn_feedback_handler(
+        bus.feedback.subscribe("csv_logger", 64, OverflowPolicy::DropNewest),
+        log_policy.clone(),
+    );
+
+    // Routing table: the actuator threads, the sensors routed to each, channel capacity and
+    // priority. Delivery statistics are published with the bus statistics.
+    let routing = config.routing.build();
+    let actuator_names = routing.names();
+    info!("[Routing] {}", routing.describe());
+    info!(
+        "[Routing] {}",
+        routing
+            .actuators()
+            .iter()
+            .map(|a| format!("{}: capacity {}, priority {}", a.name, a.capacity, a.priority.label()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    // Optional MQTT bridge: telemetry out, setpoint/tuning commands in (bus `commands`)
+    let mqtt = &config.mqtt;
+    let mqtt_broker = if mqtt.enabled && mqtt.embedded_broker {
+        match MqttBroker::start(&mqtt.address) {
+            Ok(broker) => Some(broker),
+            Err(e) => {
+                error!("Failed to start embedded MQTT broker on {}: {}", mqtt.address, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let mqtt_bridge = if mqtt.enabled {
+        let broker = mqtt_broker
+            .as_ref()
+            .map(|b| b.addr().to_string())
+            .unwrap_or_else(|| mqtt.address.clone());
+        // Tuning/autotune targets: the receiver's controller and every routed actuator
+        let targets = iter::once(RECEIVER).chain(actuator_names.iter().copied()).collect();
+        match start_mqtt_bridge(mqtt.bridge(broker, targets), &bus, metrics.clone()) {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                error!("MQTT bridge disabled (broker unreachable): {}", e);
+                None
+            }
         }
+    } else {
+        None
+    };
+
+    // Fail-safe supervisor: watchdogs fed by sensors, processor and actuators; the mode it sets
+    // selects each actuator's safe action. Started before the pipeline so `init` covers startup.
+    let failsafe = config.failsafe.clone();
+    let watchdogs = Arc::new(Watchdogs::new(failsafe.enabled, &actuator_names));
+    let supervisor = failsafe.enabled.then(|| {
+        spawn_supervisor(
+            failsafe.clone(),
+            watchdogs.clone(),
+            bus.feedback.subscribe("failsafe", 256, OverflowPolicy::DropOldest),
+            metrics.clone(),
+            event_recorder.clone(),
+            running.clone(),
+        )
     });
 
-    // Spawn feedback handler thread (logs feedback to CSV)
-    let _feedback_handler = spawn_feedback_handler(rx_log);
+    // Actuator faults: scenarios injected after each actuator's dynamics, timed from here; the
+    // detector compares the commanded and observed responses the actuators feed back.
+    let faults = config.faults.clone();
+    let faults_origin = Instant::now();
+    let fault_detector = faults.detector.then(|| {
+        spawn_fault_detector(
+            faults.clone(),
+            faults_origin,
+            actuator_names.clone(),
+            bus.feedback.subscribe("fault_detector", 512, OverflowPolicy::DropOldest),
+            feedback_loop.clone(),
+            metrics.clone(),
+            event_recorder.clone(),
+            running.clone(),
+        )
+    });
+
+    // Spawn anomaly logger thread (processor anomalies to CSV)
+    let anomaly_logger = spawn_anomaly_logger(
+        bus.anomalies.subscribe("csv_logger", 256, OverflowPolicy::DropNewest),
+        cpu_load_threads,
+        log_policy,
+    );
 
     let transmitter = Arc::new(
         Transmitter::new(tx_proc.clone(), 1024, sync.clone())
     );
 
-    // Spawn three sensors pinned to shared_core.
-    // All contend for same core; CPU load threads amplify contention.
-    let sensors = vec![
-        spawn_sensor("Force", SensorType::Force, tx_sensors.clone(), running.clone(), sync.clone(), metrics.clone(), event_recorder.clone()),
-        spawn_sensor("Position", SensorType::Position, tx_sensors.clone(), running.clone(), sync.clone(), metrics.clone(), event_recorder.clone()),
-        spawn_sensor("Temperature", SensorType::Temperature, tx_sensors.clone(), running.clone(), sync.clone(), metrics.clone(), event_recorder.clone()),
-    ];
-
     // Processor: consumes SensorData → applies anomaly detection + thresholds → produces commands.
     // Pinned to shared_core. Deadline: 200µs. Feedback adjusts thresholds dynamically.
     let processor_handle = {
@@ -321,51 +474,147 @@ fn run_simulation_internal(
         let metrics_p = metrics.clone();
         let core = shared_core;
         let recorder = event_recorder.clone();
-
-        thread::spawn(move || {
-            // Pin processor to shared_core (contention point with CPU load)
-            let core_ids = core_affinity::get_core_ids().unwrap_or_default();
-            if let Some(core_id) = core_ids.get(core) {
-                if core_affinity::set_for_current(*core_id) {
-                    info!(" Processor pinned to core {}", core);
+        let bus_p = bus.clone();
+        let watchdogs_p = watchdogs.clone();
+        let processor_config = config.processor.clone();
+
+        thread::Builder::new()
+            .name("processor".into())
+            .spawn(move || {
+                // Pin processor to shared_core (contention point with CPU load)
+                let core_ids = core_affinity::get_core_ids().unwrap_or_default();
+                if let Some(core_id) = core_ids.get(core) {
+                    if core_affinity::set_for_current(*core_id) {
+                        info!(" Processor pinned to core {}", core);
+                    } else {
+                        error!("Failed to pin processor to core {}", core);
+                    }
                 } else {
-                    error!("Failed to pin processor to core {}", core);
+                    error!("Core {} not found available system cores", core);
                 }
-            } else {
-                error!("Core {} not found available system cores", core);
-            }
 
-            // Create processor with feedback channel for dynamic threshold adjustment
-            let mut proc = Processor::new(
-                rx_proc,              // Sensor data channel
-                rx_proc_feedback,     // Feedback channel (actuator → processor)
-                10,                   // window_size
-                3.0,                  // anomaly_threshold (initial value)
-                200,                  // deadline_us (200 µs)
-                5_000,                // expected_interval_us (5 ms)
-                sync_p,
-                tx_p,
-                metrics_p,
-                recorder,
-            );
-            proc.run();
-        })
+                // Create processor with feedback channel for dynamic threshold adjustment
+                let mut proc = Processor::new(
+                    rx_proc,              // Sensor data channel
+                    rx_proc_feedback,     // Feedback subscription (actuator → processor)
+                    10,                   // window_size
+                    processor_config.anomaly_threshold, // anomaly_threshold (initial value)
+                    200,                  // deadline_us (200 µs)
+                    5_000,                // expected_interval_us (5 ms)
+                    sync_p,
+                    tx_p,
+                    metrics_p,
+                    recorder,
+                )
+                .with_threshold_limits(processor_config.threshold_min, processor_config.threshold_max)
+                .with_bus(&bus_p)
+                .with_watchdogs(watchdogs_p);
+                proc.run();
+            })
+            .expect("Failed to spawn processor thread")
     };
 
+    // Plants: owned by the sensor that measures them, driven by the routed actuator.
+    let plants = config.plant.build();
+    let mut plant_inputs: [Option<PlantInput>; SENSOR_COUNT] =
+        std::array::from_fn(|i| plants[i].as_ref().map(Plant::input));
+    for (sensor, plant) in SensorType::ALL.iter().zip(&plants) {
+        if let Some(plant) = plant {
+            info!("[Plant] {} ← {}", sensor.name(), plant.name());
+        }
+    }
+
+    // Actuators (routing table order): freshness, control law and period, dynamics and safe
+    // actions by role; driven plant and setpoint profile by primary sensor. The receiver's
+    // controller has its own law.
+    let freshness = config.freshness;
+    let control = &config.control;
+    let law_receiver = control.build_receiver();
+    let setpoints = config.setpoint.build();
+    let receiver_setpoints = setpoints.clone();
+    let injectors = faults.build(faults_origin, &actuator_names);
+    let model_name = |model: &Option<_>| if model.is_some() { "modelled" } else { "ideal" };
+    let fault_name = |injector: &Option<_>| if injector.is_some() { "faults scheduled" } else { "healthy" };
+    let (mut laws, mut profiles, mut models, mut faulted) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let actuators: Vec<ActuatorSetup> = routing
+        .actuators()
+        .iter()
+        .zip(injectors)
+        .map(|(route, faults_injected)| {
+            let primary = route.primary().index();
+            let law_config = control.law(route.role);
+            let law = control.build(route.role, route.primary(), &config.plant);
+            let dynamics = config.actuator.build(route.role);
+            laws.push(format!("{}: {}", route.name, law.name()));
+            profiles.push(format!("{}: {}", route.name, setpoints[primary].name()));
+            models.push(format!("{}: {}", route.name, model_name(&dynamics)));
+            faulted.push(format!("{}: {}", route.name, fault_name(&faults_injected)));
+            ActuatorSetup {
+                freshness: freshness.policy(route.role),
+                plant: if route.drives_plant { plant_inputs[primary].take() } else { None },
+                law: Some(law),
+                sample_s: law_config.sample_s,
+                commands: Some(bus.commands.clone()),
+                autotune: AutotuneSetup::new(law_config),
+                setpoint: Some(setpoints[primary].clone()),
+                dynamics,
+                faults: faults_injected,
+                report_expected: faults.detector,
+                watchdogs: failsafe.enabled.then(|| watchdogs.clone()),
+                safe: failsafe.actions(route.role),
+            }
+        })
+        .collect();
+    info!("[Control] Receiver: {}, {}", law_receiver.name(), laws.join(", "));
+    info!("[Setpoint] {}", profiles.join(", "));
+    info!("[Actuator] {}", models.join(", "));
+    info!(
+        "[Faults] {} ({} scenarios, detector {})",
+        faulted.join(", "), faults.scenario.len(), if faults.detector { "on" } else { "off" }
+    );
+    let receiver_config = control.receiver.clone();
+
     // Receiver: consumes processor commands → drives actuators → sends feedback.
     let receiver_handle = {
         let sync_r = sync.clone();
         let metrics_r = metrics.clone();
         let feedback_r = feedback_loop.clone();
         let recorder = event_recorder.clone();
-
-        thread::spawn(move || {
-            let multi = MultiActuator::new(sync_r.clone(), feedback_r.clone(), metrics_r.clone(), recorder.clone());
-            let mut receiver = Receiving::new(rx_act, sync_r, multi, feedback_r, metrics_r, recorder);
-            receiver.run();
-        })
+        let bus_r = bus.clone();
+        let routing_r = routing.clone();
+        let (reorder_window, reorder_timeout) = (config.receiver.reorder_window, config.receiver.reorder_timeout());
+
+        thread::Builder::new()
+            .name("receiver".into())
+            .spawn(move || {
+                let multi = MultiActuator::with_actuators(
+                    sync_r.clone(),
+                    feedback_r.clone(),
+                    metrics_r.clone(),
+                    recorder.clone(),
+                    routing_r,
+                    actuators,
+                );
+                let mut receiver = Receiving::new(rx_act, sync_r, multi, feedback_r, metrics_r, recorder)
+                    .with_reorder(reorder_window, reorder_timeout)
+                    .with_bus(&bus_r)
+                    .with_law(law_receiver, &receiver_config)
+                    .with_setpoints(receiver_setpoints);
+                receiver.run();
+            })
+            .expect("Failed to spawn receiver thread")
     };
 
+    // Spawn three sensors pinned to shared_core, after their consumers: the receiver and the
+    // actuators are already blocked on their channels when the first sample flows (rt_alloc.rs).
+    // All contend for same core; CPU load threads amplify contention.
+    let [plant_force, plant_position, plant_temperature] = plants;
+    let sensors = vec![
+        spawn_sensor("Force", SensorType::Force, tx_sensors.clone(), running.clone(), sync.clone(), metrics.clone(), event_recorder.clone(), bus.clone(), plant_force, watchdogs.clone()),
+        spawn_sensor("Position", SensorType::Position, tx_sensors.clone(), running.clone(), sync.clone(), metrics.clone(), event_recorder.clone(), bus.clone(), plant_position, watchdogs.clone()),
+        spawn_sensor("Temperature", SensorType::Temperature, tx_sensors.clone(), running.clone(), sync.clone(), metrics.clone(), event_recorder.clone(), bus.clone(), plant_temperature, watchdogs.clone()),
+    ];
+
     //CPU load threads: background CPU-bound work on shared_core.
     // Higher thread counts increase contention; measures real-time performance degradation.
     let cpu_load_handles = spawn_cpu_load(
@@ -380,7 +629,13 @@ fn run_simulation_internal(
     );
 
     info!("[Main] Running simulation for {} seconds...", DEFAULT_SIMULATION_DURATION_SECS);
-    thread::sleep(Duration::from_secs(DEFAULT_SIMULATION_DURATION_SECS));
+    let run_until = Instant::now() + Duration::from_secs(DEFAULT_SIMULATION_DURATION_SECS);
+    while Instant::now() < run_until {
+        thread::sleep(Duration::from_millis(BUS_STATS_INTERVAL_MS).min(run_until - Instant::now()));
+        publish_bus_stats(&bus, &metrics);
+        publish_routing_stats(&routing, &metrics);
+        publish_sync_stats(&sync, &metrics);
+    }
     
     info!("[Main] Time's up! Setting running = false");
     running.store(false, Ordering::Relaxed);
@@ -403,6 +658,12 @@ fn run_simulation_internal(
     for s in sensors {
         let _ = s.join();
     }
+    if let Some(handle) = supervisor {
+        let _ = handle.join();
+    }
+    if let Some(handle) = fault_detector {
+        let _ = handle.join();
+    }
 
     for h in cpu_load_handles {
         let _ = h.join();
@@ -411,6 +672,19 @@ fn run_simulation_internal(
     let _ = processor_handle.join();
     let _ = receiver_handle.join();
 
+    // All publishers have stopped: close the bus so subscribers drain and exit
+    bus.close();
+    let _ = feedback_handler.join();
+    let _ = anomaly_logger.join();
+    if let Some(handle) = mqtt_bridge {
+        let _ = handle.join();
+    }
+    if let Some(broker) = mqtt_broker {
+        broker.stop();
+    }
+    publish_bus_stats(&bus, &metrics);
+    publish_routing_stats(&routing, &metrics);
+
     if let Some(handle) = render_handle {
         match handle.join() {
             Ok(_) => info!("Render thread joined"),
@@ -427,9 +701,48 @@ fn run_simulation_internal(
         let _ = sync.stop_consumer();
     }
 
+    // Every producer has exited: exporter drains all per-thread buffers, then exits
+    event_recorder.shutdown();
+    let _ = exporter_handle.join();
+
+    {
+        let mut m = metrics.lock_run();
+        m.event_producers = event_recorder.producer_stats();
+        m.events_exported = event_recorder.exported_count();
+        let dropped: u64 = m.event_producers.iter().map(|p| p.dropped).sum();
+        info!(
+            "[EventRecorder] {} events exported from {} producers, {} dropped",
+            m.events_exported, m.event_producers.len(), dropped
+        );
+        m.sync_mode = Some(sync.mode);
+        m.sync_diagnostics = sync.snapshot();
+        m.sync_contention = sync.contention();
+        if let Some(d) = sync.snapshot_lock_free() {
+            info!(
+                "[SyncManager] LockFree: rx latency p50 {} µs / p99 {} µs / max {} µs ({} events), dropped logs {}",
+                d.rx_latency_hist.percentile_us(0.50),
+                d.rx_latency_hist.percentile_us(0.99),
+                d.rx_latency_hist.max_us,
+                d.rx_latency_hist.count,
+                sync.dropped_log_count().unwrap_or(0)
+            );
+        }
+        if cfg!(debug_assertions) {
+            info!("[RtAlloc] {} allocations on RT threads so far", rt_alloc::violations());
+        }
+        if let Some(c) = &m.sync_contention {
+            let t = c.total();
+            info!(
+                "[SyncManager] {:?}: {} records, wait avg {:.0} ns / max {} ns, hold avg {:.0} ns / max {} ns, CAS retries {}, push fails {}",
+                sync.mode, t.ops, t.wait_avg_ns(), t.wait_max_ns, t.hold_avg_ns(), t.hold_max_ns, t.cas_retries, t.push_fails
+            );
+        }
+    }
+
     thread::sleep(Duration::from_millis(500));
 
     run_exports(metrics, cpu_load_threads);
+    export_control_steps(&csv_path, cpu_load_threads);
 
     info!("[Experiment] Completed: cpu_load_threads={}", cpu_load_threads);
     info!("[Experiment] Events exported to: {}", csv_path);
@@ -438,6 +751,28 @@ fn run_simulation_internal(
 
 
 
+/// Copies per-subscriber bus statistics into the shared metrics (dashboard + export).
+fn publish_bus_stats(bus: &Bus, metrics: &SharedMetrics) {
+    let stats = bus.stats();
+    let mut m = metrics.lock_run();
+    m.bus_stats = stats;
+}
+
+/// Copies the sensor → actuator delivery statistics (fan-out, fan-in) into the shared metrics.
+fn publish_routing_stats(routing: &RoutingTable, metrics: &SharedMetrics) {
+    let stats = routing.snapshot();
+    let mut m = metrics.lock_run();
+    m.routing = stats;
+}
+
+/// Copies the SyncManager running aggregates (LockFree: drained so far) into the dashboard metrics.
+fn publish_sync_stats(sync: &SyncManager, metrics: &SharedMetrics) {
+    let diagnostics = sync.snapshot();
+    let mut m = metrics.lock_run();
+    m.sync_mode = Some(sync.mode);
+    m.sync_diagnostics = diagnostics;
+}
+
 /// Spawns a sensor thread pinned to shared_core.
 ///
 /// # Arguments
@@ -448,8 +783,10 @@ fn run_simulation_internal(
 /// * `sync` — Synchronization manager (lock-free or mutex-based logging).
 /// * `metrics` — Shared metrics; sensor updates latency histograms.
 /// * `event_recorder` — Event recorder; logs all sample timestamps.
-/// 
-
+/// * `bus` — Pub/sub bus; every sample is published on `sensor_data`.
+/// * `plant` — Plant sampled instead of the constant base value (None = open loop).
+/// * `watchdogs` — Fail-safe watchdogs; every release is a sensor heartbeat.
+#[allow(clippy::too_many_arguments)]
 fn spawn_sensor(
     name: &'static str,
     sensor_type: SensorType,
@@ -458,18 +795,29 @@ fn spawn_sensor(
     sync: Arc<SyncManager>,
     metrics: SharedMetrics,
     event_recorder: Arc<EventRecorder>,
+    bus: Bus,
+    plant: Option<Plant>,
+    watchdogs: Arc<Watchdogs>,
 ) -> thread::JoinHandle<()> {
-    thread::spawn(move || {
-        let sensor = Sensor::new(
-            name,
-            5, //sample interval
-            tx,
-            running,
-            sensor_type,
-            sync,
-            metrics,
-            event_recorder,
-        );
-        sensor.run();
-    })
+    thread::Builder::new()
+        .name(format!("sensor_{}", name))
+        .spawn(move || {
+            let mut sensor = Sensor::new(
+                name,
+                5, //sample interval
+                tx,
+                running,
+                sensor_type,
+                sync,
+                metrics,
+                event_recorder,
+            )
+            .with_bus(&bus)
+            .with_watchdogs(watchdogs);
+            if let Some(plant) = plant {
+                sensor = sensor.with_plant(plant);
+            }
+            sensor.run();
+        })
+        .expect("Failed to spawn sensor thread")
 }
\ No newline at end of file
@@ -0,0 +1,350 @@
+//! bus.rs
+//! In-process, typed publish/subscribe bus.
+//!
+//! - One `Topic<T>` per message type: sensor data, processed packets, feedback, anomalies,
+//!   plus remote control commands.
+//! - Every subscriber owns a bounded queue with its own overflow policy, so a slow consumer
+//!   (CSV logger, dashboard) can never stall the publisher or the other subscribers.
+//! - Subscriber lag (publish → receive) and queue depth are measured per subscriber.
+//! - Dropping a subscription removes its queue from the topic; its final statistics are kept.
+//! - Components register with the bus instead of being handed raw channel ends.
+
+use crossbeam::channel::{bounded, Receiver, RecvError, RecvTimeoutError, Sender, TryRecvError, TrySendError};
+use parking_lot::RwLock;
+use std::{
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::component_a::{
+    processor::{Anomaly, ProcessedPacket},
+    sensor::SensorData,
+};
+use crate::component_b::{controller::ControlCommand, feedback::Feedback};
+
+/// What a publisher does when a subscriber's queue is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverflowPolicy {
+    DropNewest,                     // Discard the message being published (never blocks)
+    DropOldest,                     // Evict the oldest queued message to make room (never blocks)
+    /// Wait up to `timeout`, then discard; non-RT consumers only. The publisher waits while
+    /// holding the topic's subscriber list, so `subscribe`, `close` and dropping a subscription
+    /// on the same topic can stall for up to `timeout` as well.
+    #[allow(dead_code)]
+    Block { timeout: Duration },
+}
+
+impl OverflowPolicy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverflowPolicy::DropNewest => "drop_newest",
+            OverflowPolicy::DropOldest => "drop_oldest",
+            OverflowPolicy::Block { .. } => "block",
+        }
+    }
+}
+
+/// Per-subscriber delivery statistics (snapshot).
+#[derive(Debug, Clone)]
+pub struct SubscriberStats {
+    pub topic: &'static str,
+    pub subscriber: &'static str,
+    pub policy: OverflowPolicy,
+    pub capacity: usize,
+    pub published: u64,     // Messages offered to this subscriber
+    pub delivered: u64,     // Messages received by the subscriber
+    pub dropped: u64,       // Discarded by the overflow policy
+    pub depth: usize,       // Current queue depth
+    pub max_depth: usize,
+    pub lag_avg_us: f64,    // Publish → receive
+    pub lag_max_us: u64,
+}
+
+/// Counters shared between the publisher side and the subscription.
+#[derive(Default)]
+struct SubscriberCounters {
+    published: AtomicU64,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    max_depth: AtomicU64,
+    lag_total_ns: AtomicU64,
+    lag_max_ns: AtomicU64,
+}
+
+type Envelope<T> = (Instant, T); // (publish time, message)
+
+struct Slot<T> {
+    name: &'static str,
+    policy: OverflowPolicy,
+    capacity: usize,
+    tx: Option<Sender<Envelope<T>>>, // None once the topic is closed
+    evict: Receiver<Envelope<T>>,   // Publisher-side handle for DropOldest eviction and depth
+    counters: Arc<SubscriberCounters>,
+}
+
+impl<T> Slot<T> {
+    fn stats(&self, topic: &'static str) -> SubscriberStats {
+        let c = &self.counters;
+        let delivered = c.delivered.load(Ordering::Relaxed);
+        let lag_total_ns = c.lag_total_ns.load(Ordering::Relaxed);
+        SubscriberStats {
+            topic,
+            subscriber: self.name,
+            policy: self.policy,
+            capacity: self.capacity,
+            published: c.published.load(Ordering::Relaxed),
+            delivered,
+            dropped: c.dropped.load(Ordering::Relaxed),
+            depth: self.evict.len(),
+            max_depth: c.max_depth.load(Ordering::Relaxed) as usize,
+            lag_avg_us: if delivered > 0 {
+                lag_total_ns as f64 / delivered as f64 / 1_000.0
+            } else {
+                0.0
+            },
+            lag_max_us: c.lag_max_ns.load(Ordering::Relaxed) / 1_000,
+        }
+    }
+}
+
+struct TopicInner<T> {
+    name: &'static str,
+    slots: RwLock<Vec<Slot<T>>>,
+    retired: RwLock<Vec<SubscriberStats>>, // Final statistics of dropped subscriptions
+}
+
+/// A typed topic. Cloning is cheap; all clones publish to the same subscribers.
+pub struct Topic<T> {
+    inner: Arc<TopicInner<T>>,
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<T: Clone> Topic<T> {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            inner: Arc::new(TopicInner {
+                name,
+                slots: RwLock::new(Vec::new()),
+                retired: RwLock::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Registers a subscriber with its own bounded queue and overflow policy.
+    pub fn subscribe(&self, name: &'static str, capacity: usize, policy: OverflowPolicy) -> Subscription<T> {
+        let (tx, rx) = bounded(capacity.max(1));
+        let counters = Arc::new(SubscriberCounters::default());
+
+        self.inner.slots.write().push(Slot {
+            name,
+            policy,
+            capacity: capacity.max(1),
+            tx: Some(tx),
+            evict: rx.clone(),
+            counters: counters.clone(),
+        });
+
+        Subscription { rx, counters, topic: Arc::downgrade(&self.inner) }
+    }
+
+    /// Delivers `msg` to every subscriber according to its overflow policy.
+    /// Never blocks unless a subscriber registered with `OverflowPolicy::Block`
+    /// (see there for what else waits meanwhile).
+    pub fn publish(&self, msg: T) {
+        let slots = self.inner.slots.read();
+        let published = Instant::now();
+
+        for slot in slots.iter() {
+            let c = &slot.counters;
+            let Some(tx) = &slot.tx else { continue };
+            c.published.fetch_add(1, Ordering::Relaxed);
+
+            let delivered = match slot.policy {
+                OverflowPolicy::DropNewest => tx.try_send((published, msg.clone())).is_ok(),
+                OverflowPolicy::DropOldest => match tx.try_send((published, msg.clone())) {
+                    Ok(()) => true,
+                    Err(TrySendError::Full(envelope)) => {
+                        // Make room by evicting the oldest message (counted as the drop)
+                        if slot.evict.try_recv().is_ok() {
+                            c.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        if tx.try_send(envelope).is_err() {
+                            c.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        c.max_depth.fetch_max(tx.len() as u64, Ordering::Relaxed);
+                        continue;
+                    }
+                    Err(TrySendError::Disconnected(_)) => false,
+                },
+                OverflowPolicy::Block { timeout } => {
+                    tx.send_timeout((published, msg.clone()), timeout).is_ok()
+                }
+            };
+
+            if delivered {
+                c.max_depth.fetch_max(tx.len() as u64, Ordering::Relaxed);
+            } else {
+                c.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Drops every subscriber queue's sender: subscribers drain what is queued,
+    /// then `recv` returns an error (shutdown signal). Later publishes are ignored.
+    pub fn close(&self) {
+        for slot in self.inner.slots.write().iter_mut() {
+            slot.tx = None;
+        }
+    }
+
+    /// Live subscribers first, then the final statistics of dropped ones.
+    pub fn stats(&self) -> Vec<SubscriberStats> {
+        let mut all: Vec<SubscriberStats> =
+            self.inner.slots.read().iter().map(|slot| slot.stats(self.inner.name)).collect();
+        all.extend(self.inner.retired.read().iter().cloned());
+        all
+    }
+}
+
+/// Receiving end of one subscriber. Dropping it removes the subscriber's queue from the
+/// topic (queued messages are freed); its final statistics stay available through `Topic::stats`.
+pub struct Subscription<T> {
+    rx: Receiver<Envelope<T>>,
+    counters: Arc<SubscriberCounters>,
+    topic: Weak<TopicInner<T>>,
+}
+
+impl<T> Subscription<T> {
+    /// Blocks until a message arrives; errors once the topic is closed and drained.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.rx.recv().map(|envelope| self.deliver(envelope))
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.rx.try_recv().map(|envelope| self.deliver(envelope))
+    }
+
+    #[allow(dead_code)]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.rx.recv_timeout(timeout).map(|envelope| self.deliver(envelope))
+    }
+
+    #[inline]
+    fn deliver(&self, (published, msg): Envelope<T>) -> T {
+        let lag_ns = published.elapsed().as_nanos() as u64;
+        self.counters.delivered.fetch_add(1, Ordering::Relaxed);
+        self.counters.lag_total_ns.fetch_add(lag_ns, Ordering::Relaxed);
+        self.counters.lag_max_ns.fetch_max(lag_ns, Ordering::Relaxed);
+        msg
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let Some(topic) = self.topic.upgrade() else { return };
+        let mut slots = topic.slots.write();
+        if let Some(index) = slots.iter().position(|slot| Arc::ptr_eq(&slot.counters, &self.counters)) {
+            let slot = slots.swap_remove(index);
+            drop(slots);
+            // Queued messages are freed with the last receiver (this subscription's `rx`)
+            let mut stats = slot.stats(topic.name);
+            stats.depth = 0;
+            topic.retired.write().push(stats);
+        }
+    }
+}
+
+/// Topics shared by the pipeline. Cloning shares the same topics.
+#[derive(Clone)]
+pub struct Bus {
+    pub sensor_data: Topic<SensorData>,         // Sensor → (observers); published on release
+    pub processed: Topic<ProcessedPacket>,      // Receiver arrivals
+    pub feedback: Topic<Feedback>,              // Actuators → processor, CSV logger, ...
+    pub anomalies: Topic<Anomaly>,              // Processor anomaly detections
+    pub commands: Topic<ControlCommand>,        // Remote setpoint/tuning (MQTT bridge, ...) → controller
+}
+
+impl Bus {
+    pub fn new() -> Self {
+        Self {
+            sensor_data: Topic::new("sensor_data"),
+            processed: Topic::new("processed"),
+            feedback: Topic::new("feedback"),
+            anomalies: Topic::new("anomalies"),
+            commands: Topic::new("commands"),
+        }
+    }
+
+    /// Closes every topic (end of run).
+    pub fn close(&self) {
+        self.sensor_data.close();
+        self.processed.close();
+        self.feedback.close();
+        self.anomalies.close();
+        self.commands.close();
+    }
+
+    /// Statistics for every subscriber on every topic.
+    pub fn stats(&self) -> Vec<SubscriberStats> {
+        let mut all = self.sensor_data.stats();
+        all.extend(self.processed.stats());
+        all.extend(self.feedback.stats());
+        all.extend(self.anomalies.stats());
+        all.extend(self.commands.stats());
+        all
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_subscription_leaves_the_topic_and_keeps_its_final_stats() {
+        let topic = Topic::new("test");
+        let kept = topic.subscribe("kept", 4, OverflowPolicy::DropNewest);
+        let dropped = topic.subscribe("dropped", 4, OverflowPolicy::DropNewest);
+        topic.publish(1u32);
+        topic.publish(2u32);
+        assert_eq!(dropped.recv(), Ok(1));
+        drop(dropped);
+
+        // No queue is left for the dropped subscriber: later publishes skip it
+        assert_eq!(topic.inner.slots.read().len(), 1);
+        topic.publish(3u32);
+        assert_eq!((kept.recv(), kept.recv(), kept.recv()), (Ok(1), Ok(2), Ok(3)));
+
+        let stats = topic.stats();
+        assert_eq!(stats.len(), 2);
+        let retired = stats.iter().find(|s| s.subscriber == "dropped").unwrap();
+        assert_eq!((retired.published, retired.delivered, retired.depth), (2, 1, 0));
+        let live = stats.iter().find(|s| s.subscriber == "kept").unwrap();
+        assert_eq!((live.published, live.delivered), (3, 3));
+    }
+
+    #[test]
+    fn subscription_outliving_its_topic_drops_cleanly() {
+        let topic = Topic::new("test");
+        let sub = topic.subscribe("late", 1, OverflowPolicy::DropOldest);
+        topic.publish(1u8);
+        topic.publish(2u8);
+        drop(topic);
+        // The sender went with the topic: the queue drains, then reports the shutdown
+        assert_eq!(sub.recv(), Ok(2));
+        assert!(sub.recv().is_err());
+    }
+}
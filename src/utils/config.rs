@@ -0,0 +1,297 @@
+//! config.rs
+//! Run configuration loaded from `config/config.toml`.
+//!
+//! - Missing file, empty file or missing keys fall back to the defaults below.
+//! - A malformed file is reported and ignored (the run continues on defaults).
+
+use std::{fs, path::Path, time::Duration};
+
+use serde::Deserialize;
+use log::{info, error};
+
+use crate::advanced::mqtt::MqttConfig;
+use crate::component_a::{
+    sensor::{SensorType, SENSOR_COUNT},
+    sync_manager::SyncMode,
+    synclog::SyncLogFormat,
+};
+use crate::component_b::{
+    actuator_fault::FaultsConfig,
+    actuator_model::{ActuatorModel, ActuatorModelConfig},
+    control_law::{CONTROL_SAMPLE_S, ControlLaw, LawConfig},
+    failsafe::FailSafeConfig,
+    freshness::FreshnessConfig,
+    plant::{Plant, PlantSpec},
+    routing::{ActuatorRole, RoutingConfig},
+    setpoint::{SetpointGenerator, SetpointSpec},
+};
+use crate::utils::logwriter::LogPolicy;
+
+pub const DEFAULT_CONFIG_PATH: &str = "config/config.toml";
+
+/// Top-level configuration (one table per subsystem).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SimConfig {
+    pub sync: SyncConfig,
+    pub logging: LoggingConfig,
+    pub receiver: ReceiverConfig,
+    pub processor: ProcessorConfig,
+    pub plant: PlantConfig,
+    pub control: ControlConfig,
+    pub setpoint: SetpointConfig,
+    pub actuator: ActuatorConfig,
+    pub freshness: FreshnessConfig,
+    pub failsafe: FailSafeConfig,
+    pub faults: FaultsConfig,
+    pub routing: RoutingConfig,
+    pub mqtt: MqttConfig,
+}
+
+/// `[sync]`: SyncManager diagnostics strategy.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub mode: SyncMode,     // mutex | priority_inheritance | atomics | lock_free | sharded | rw_lock
+    pub instrument: bool,   // Lock wait/hold, CAS retries, push fails per thread (small overhead)
+    pub log_format: SyncLogFormat,  // LockFree log: csv | binary (decode with `synclog`)
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            mode: SyncMode::LockFree,
+            instrument: false,
+            log_format: SyncLogFormat::Csv,
+        }
+    }
+}
+
+/// `[logging]`: rotation and durability of the streaming CSV logs (logwriter.rs).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub rotate_mb: u64,         // Size rotation threshold; 0 = off
+    pub rotate_secs: u64,       // Time rotation interval; 0 = off
+    pub keep_segments: usize,   // Rotated segments kept per log; 0 = keep all
+    pub compress: bool,         // Gzip rotated segments
+    pub fsync_ms: u64,          // Flush + fsync interval; 0 = only on rotation and exit
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            rotate_mb: 64,
+            rotate_secs: 0,
+            keep_segments: 8,
+            compress: true,
+            fsync_ms: 1000,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn policy(&self) -> LogPolicy {
+        let nonzero = |v: u64| (v > 0).then_some(v);
+        LogPolicy {
+            max_bytes: nonzero(self.rotate_mb).map(|mb| mb * 1024 * 1024),
+            max_age: nonzero(self.rotate_secs).map(Duration::from_secs),
+            keep_segments: (self.keep_segments > 0).then_some(self.keep_segments),
+            compress: self.compress,
+            fsync_interval: nonzero(self.fsync_ms).map(Duration::from_millis),
+        }
+    }
+}
+
+/// `[processor]`: anomaly threshold (standard deviations from the moving average) and the
+/// bounds its feedback-driven adjustments stay within.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProcessorConfig {
+    pub anomaly_threshold: f64,     // Initial (nominal) threshold
+    pub threshold_min: f64,         // Lowest threshold tightening may reach
+    pub threshold_max: f64,         // Highest threshold relaxing may reach
+}
+
+impl Default for ProcessorConfig {
+    fn default() -> Self {
+        Self {
+            anomaly_threshold: 3.0,
+            threshold_min: 1.5,
+            threshold_max: 6.0,
+        }
+    }
+}
+
+/// `[receiver]`: per-sensor sequence tracking at the receiver (sequence.rs).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReceiverConfig {
+    pub reorder_window: usize,      // Seqs past a missing one held per sensor for reordering; 0 = gap detection only
+    pub reorder_timeout_ms: u64,    // Longest a held packet waits for the gap before it
+}
+
+impl Default for ReceiverConfig {
+    fn default() -> Self {
+        Self {
+            reorder_window: 0,
+            reorder_timeout_ms: 10,
+        }
+    }
+}
+
+impl ReceiverConfig {
+    pub fn reorder_timeout(&self) -> Duration {
+        Duration::from_millis(self.reorder_timeout_ms)
+    }
+}
+
+/// `[plant]`: plant model measured by each sensor and driven by its actuator (plant.rs).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PlantConfig {
+    pub enabled: bool,          // false = open loop (sensors read base value + noise)
+    pub force: PlantSpec,       // Driven by the gripper
+    pub position: PlantSpec,    // Driven by the motor
+    pub temperature: PlantSpec, // Driven by the stabiliser
+}
+
+impl Default for PlantConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            force: PlantSpec::default_for(SensorType::Force),
+            position: PlantSpec::default_for(SensorType::Position),
+            temperature: PlantSpec::default_for(SensorType::Temperature),
+        }
+    }
+}
+
+impl PlantConfig {
+    pub fn spec(&self, sensor: SensorType) -> &PlantSpec {
+        match sensor {
+            SensorType::Force => &self.force,
+            SensorType::Position => &self.position,
+            SensorType::Temperature => &self.temperature,
+        }
+    }
+
+    /// One fresh plant per sensor (by `SensorType::index`); all None when disabled.
+    pub fn build(&self) -> [Option<Plant>; SENSOR_COUNT] {
+        SensorType::ALL.map(|sensor| self.enabled.then(|| Plant::new(self.spec(sensor).build())))
+    }
+}
+
+/// `[control.<controller>]`: control law and its parameters per controller (control_law.rs).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ControlConfig {
+    pub receiver: LawConfig,    // Receiver-side controller (every sensor, drives no plant)
+    pub gripper: LawConfig,
+    pub motor: LawConfig,
+    pub stabiliser: LawConfig,
+}
+
+impl ControlConfig {
+    /// `[control.<role>]` of an actuator.
+    pub fn law(&self, role: ActuatorRole) -> &LawConfig {
+        match role {
+            ActuatorRole::Gripper => &self.gripper,
+            ActuatorRole::Motor => &self.motor,
+            ActuatorRole::Stabiliser => &self.stabiliser,
+        }
+    }
+
+    /// Law of an actuator with `role`; LQR / MPC are designed on the plant `sensor` measures.
+    pub fn build(&self, role: ActuatorRole, sensor: SensorType, plant: &PlantConfig) -> Box<dyn ControlLaw> {
+        let law = self.law(role);
+        law.build(plant.enabled.then(|| plant.spec(sensor)), design_sample_s(law))
+    }
+
+    /// The receiver's law (no plant: model-based laws fall back to PID).
+    pub fn build_receiver(&self) -> Box<dyn ControlLaw> {
+        self.receiver.build(None, design_sample_s(&self.receiver))
+    }
+}
+
+/// `[setpoint]`: setpoint profile tracked by each actuator (setpoint.rs).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SetpointConfig {
+    pub gripper: SetpointSpec,      // Grip force (Force sensor)
+    pub motor: SetpointSpec,        // Joint angle (Position sensor)
+    pub stabiliser: SetpointSpec,   // Temperature
+}
+
+impl Default for SetpointConfig {
+    fn default() -> Self {
+        Self {
+            gripper: SetpointSpec::default_for(SensorType::Force),
+            motor: SetpointSpec::default_for(SensorType::Position),
+            stabiliser: SetpointSpec::default_for(SensorType::Temperature),
+        }
+    }
+}
+
+impl SetpointConfig {
+    /// Profile tracked on `sensor` (by the actuators whose primary sensor it is).
+    pub fn spec(&self, sensor: SensorType) -> &SetpointSpec {
+        match sensor {
+            SensorType::Force => &self.gripper,
+            SensorType::Position => &self.motor,
+            SensorType::Temperature => &self.stabiliser,
+        }
+    }
+
+    /// One generator per sensor (by `SensorType::index`); schedule files are read here.
+    pub fn build(&self) -> [SetpointGenerator; SENSOR_COUNT] {
+        SensorType::ALL.map(|sensor| self.spec(sensor).build(sensor))
+    }
+}
+
+/// `[actuator.<actuator>]`: dynamics of each actuator between command and state
+/// (actuator_model.rs); every stage is off unless configured.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ActuatorConfig {
+    pub gripper: ActuatorModelConfig,       // Grip force command
+    pub motor: ActuatorModelConfig,         // Motor voltage
+    pub stabiliser: ActuatorModelConfig,    // Heater power
+}
+
+impl ActuatorConfig {
+    /// Model of an actuator with `role`; None when no stage is configured.
+    pub fn build(&self, role: ActuatorRole) -> Option<ActuatorModel> {
+        let config = match role {
+            ActuatorRole::Gripper => &self.gripper,
+            ActuatorRole::Motor => &self.motor,
+            ActuatorRole::Stabiliser => &self.stabiliser,
+        };
+        (!config.is_ideal()).then(|| config.build())
+    }
+}
+
+/// Model-based design period: the control period, never below the sensor period.
+fn design_sample_s(law: &LawConfig) -> f64 {
+    law.sample_s.max(CONTROL_SAMPLE_S)
+}
+
+/// Loads `path`, falling back to defaults when it is absent or invalid.
+pub fn load_config(path: impl AsRef<Path>) -> SimConfig {
+    let path = path.as_ref();
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => {
+            info!("No config at {:?}; using defaults", path);
+            return SimConfig::default();
+        }
+    };
+
+    match toml::from_str(&text) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Invalid config {:?} ({}); using defaults", path, e);
+            SimConfig::default()
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//! control_quality.rs
+//! Control-performance analysis: step-response figures and error integrals per actuator.
+//!
+//! - Segments: a `step` starts when an actuator's setpoint jumps (step, schedule without
+//!   interpolation, injection) and is measured from the measurement at the jump (the plant may
+//!   not have reached the previous setpoint). The transient after the controller starts is a
+//!   `start` segment, measured the same way and left out of the step averages. Continuously varying profiles (ramp, sine, interpolated schedule)
+//!   form `tracking` segments with integrals and steady-state error only.
+//! - Per step: rise time (10 → 90 % of the step), overshoot (% of the step), settling time
+//!   (inside ±`SETTLING_BAND` of the step for good), steady-state error (setpoint − measurement,
+//!   averaged over the last ~`STEADY_TAU_S`), and IAE / ISE / ITAE (time since the step).
+//! - Live: each actuator controller feeds its control instants to a `QualityTracker` and
+//!   publishes the running `ControlQuality` through the live metrics (allocation-free).
+//! - Offline: `analyze_events_csv` replays the `SegmentStart` / `ControlSample` rows of
+//!   `events_load_X.csv` through the same tracker (per-step rows; `control_quality` binary).
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+pub const SETTLING_BAND: f64 = 0.05;    // ± fraction of the step size
+pub const RISE_LOW: f64 = 0.1;          // Rise time measured from 10 % ...
+pub const RISE_HIGH: f64 = 0.9;         // ... to 90 % of the step
+pub const STEADY_TAU_S: f64 = 0.5;      // Averaging time constant of the steady-state error
+
+const MIN_STEP: f64 = 1e-9;             // Smaller steps have no step-response figures
+
+/// How the setpoint behaves inside a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SegmentKind {
+    #[default]
+    Start,          // From the controller start to the first jump
+    Step,           // Constant setpoint after a jump
+    Tracking,       // Continuously varying setpoint
+}
+
+impl SegmentKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SegmentKind::Start => "start",
+            SegmentKind::Step => "step",
+            SegmentKind::Tracking => "tracking",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "start" => Some(SegmentKind::Start),
+            "step" => Some(SegmentKind::Step),
+            "tracking" => Some(SegmentKind::Tracking),
+            _ => None,
+        }
+    }
+}
+
+/// Figures of one segment (so far, while it is running). Times in seconds.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StepMetrics {
+    pub kind: SegmentKind,
+    pub start_s: f64,                   // Event-recorder clock
+    pub duration_s: f64,
+    pub from: f64,                      // Measurement at the segment's first control instant
+    pub to: f64,                        // Setpoint (latest, for tracking segments)
+    pub rise_time_s: Option<f64>,       // None: 90 % not reached, or no step
+    pub overshoot_pct: Option<f64>,     // None: no step
+    pub settling_time_s: Option<f64>,   // None: outside the band at the end, or no step
+    pub steady_state_error: f64,
+    pub iae: f64,
+    pub ise: f64,
+    pub itae: f64,
+    pub samples: u64,
+}
+
+/// Accumulates one segment sample by sample.
+#[derive(Debug, Clone, Copy, Default)]
+struct SegmentAnalyzer {
+    m: StepMetrics,
+    last_s: f64,
+    peak: f64,                          // Largest progress (measurement − from) / step
+    rise_low_s: Option<f64>,            // First time at 10 %
+    settled_s: Option<f64>,             // Entered the band and stayed since
+}
+
+impl SegmentAnalyzer {
+    fn new(kind: SegmentKind, start_s: f64) -> Self {
+        Self {
+            m: StepMetrics { kind, start_s, ..StepMetrics::default() },
+            last_s: start_s,
+            ..Self::default()
+        }
+    }
+
+    fn sample(&mut self, t_s: f64, setpoint: f64, measurement: f64) {
+        let m = &mut self.m;
+        if m.samples == 0 {
+            m.from = measurement;
+            m.to = setpoint;
+            m.steady_state_error = setpoint - measurement;
+        }
+        if m.kind == SegmentKind::Tracking {
+            m.to = setpoint;
+        }
+
+        let error = setpoint - measurement;
+        let dt = (t_s - self.last_s).max(0.0);
+        let since = t_s - m.start_s;
+        m.iae += error.abs() * dt;
+        m.ise += error * error * dt;
+        m.itae += since * error.abs() * dt;
+        m.steady_state_error += (error - m.steady_state_error) * dt / (STEADY_TAU_S + dt);
+        m.duration_s = since;
+        m.samples += 1;
+        self.last_s = t_s;
+
+        let step = m.to - m.from;
+        if m.kind == SegmentKind::Tracking || step.abs() < MIN_STEP {
+            return;
+        }
+        let progress = (measurement - m.from) / step;
+        self.peak = self.peak.max(progress);
+        if self.rise_low_s.is_none() && progress >= RISE_LOW {
+            self.rise_low_s = Some(t_s);
+        }
+        if m.rise_time_s.is_none() && progress >= RISE_HIGH {
+            m.rise_time_s = self.rise_low_s.map(|low| t_s - low);
+        }
+        if (measurement - m.to).abs() > SETTLING_BAND * step.abs() {
+            self.settled_s = None;
+        } else if self.settled_s.is_none() {
+            self.settled_s = Some(t_s);
+        }
+    }
+
+    fn metrics(&self) -> StepMetrics {
+        let mut m = self.m;
+        if m.kind == SegmentKind::Step && (m.to - m.from).abs() >= MIN_STEP && m.samples > 0 {
+            m.overshoot_pct = Some((self.peak - 1.0).max(0.0) * 100.0);
+            m.settling_time_s = self.settled_s.map(|t| t - m.start_s);
+        }
+        m
+    }
+}
+
+/// Running figures of one actuator: totals over its segments plus the latest one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ControlQuality {
+    pub segments: u64,
+    pub steps: u64,                     // `step` segments with a non-zero step
+    pub risen: u64,                     // ... that reached 90 %
+    pub settled: u64,                   // ... that ended inside the band
+    pub rise_time_sum_s: f64,
+    pub settling_time_sum_s: f64,
+    pub overshoot_sum_pct: f64,
+    pub overshoot_max_pct: f64,
+    pub abs_ss_error_sum: f64,          // Over every segment
+    pub iae: f64,
+    pub ise: f64,
+    pub itae: f64,
+    pub last: StepMetrics,              // Running segment (or the last one, once finished)
+}
+
+impl ControlQuality {
+    fn add(&mut self, m: &StepMetrics) {
+        self.segments += 1;
+        self.abs_ss_error_sum += m.steady_state_error.abs();
+        self.iae += m.iae;
+        self.ise += m.ise;
+        self.itae += m.itae;
+        self.last = *m;
+        if m.kind != SegmentKind::Step {
+            return;
+        }
+        let Some(overshoot) = m.overshoot_pct else { return };
+        self.steps += 1;
+        self.overshoot_sum_pct += overshoot;
+        self.overshoot_max_pct = self.overshoot_max_pct.max(overshoot);
+        if let Some(rise) = m.rise_time_s {
+            self.risen += 1;
+            self.rise_time_sum_s += rise;
+        }
+        if let Some(settling) = m.settling_time_s {
+            self.settled += 1;
+            self.settling_time_sum_s += settling;
+        }
+    }
+
+    pub fn mean_rise_time_s(&self) -> Option<f64> {
+        (self.risen > 0).then(|| self.rise_time_sum_s / self.risen as f64)
+    }
+
+    pub fn mean_settling_time_s(&self) -> Option<f64> {
+        (self.settled > 0).then(|| self.settling_time_sum_s / self.settled as f64)
+    }
+
+    pub fn mean_overshoot_pct(&self) -> Option<f64> {
+        (self.steps > 0).then(|| self.overshoot_sum_pct / self.steps as f64)
+    }
+
+    pub fn mean_abs_ss_error(&self) -> Option<f64> {
+        (self.segments > 0).then(|| self.abs_ss_error_sum / self.segments as f64)
+    }
+}
+
+/// Segments of one actuator in order; owned by its controller (live) or by the offline replay.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QualityTracker {
+    current: Option<SegmentAnalyzer>,
+    totals: ControlQuality,
+}
+
+impl QualityTracker {
+    /// Starts a segment at `t_s`; returns the one it closes. The segment is measured from the
+    /// measurement of its first control instant.
+    pub fn start(&mut self, kind: SegmentKind, t_s: f64) -> Option<StepMetrics> {
+        let closed = self.finish();
+        self.current = Some(SegmentAnalyzer::new(kind, t_s));
+        closed
+    }
+
+    /// One control instant; ignored before the first segment.
+    pub fn sample(&mut self, t_s: f64, setpoint: f64, measurement: f64) {
+        if let Some(segment) = &mut self.current {
+            segment.sample(t_s, setpoint, measurement);
+        }
+    }
+
+    /// Kind of the segment a control instant at `setpoint` opens, if any: `start` on the first
+    /// instant, `tracking` when the profile turns continuous, `step` on a jump.
+    pub fn next_segment(&self, continuous: bool, setpoint: f64) -> Option<SegmentKind> {
+        let Some(segment) = &self.current else {
+            return Some(if continuous { SegmentKind::Tracking } else { SegmentKind::Start });
+        };
+        match (continuous, segment.m.kind) {
+            (true, SegmentKind::Tracking) => None,
+            (true, _) => Some(SegmentKind::Tracking),
+            (false, SegmentKind::Tracking) => Some(SegmentKind::Step),
+            (false, _) => (segment.m.samples > 0 && segment.m.to != setpoint).then_some(SegmentKind::Step),
+        }
+    }
+
+    /// Closes the running segment into the totals.
+    pub fn finish(&mut self) -> Option<StepMetrics> {
+        let metrics = self.current.take()?.metrics();
+        self.totals.add(&metrics);
+        Some(metrics)
+    }
+
+    /// Totals including the running segment as if it ended now.
+    pub fn snapshot(&self) -> ControlQuality {
+        let mut quality = self.totals;
+        if let Some(segment) = &self.current {
+            quality.add(&segment.metrics());
+        }
+        quality
+    }
+}
+
+/// Offline result for one actuator.
+#[derive(Debug, Clone, Default)]
+pub struct ControllerSegments {
+    pub segments: Vec<StepMetrics>,
+    #[allow(dead_code)]
+    pub totals: ControlQuality,         // Per-level tables (`control_quality` binary)
+}
+
+/// Replays the `SegmentStart` / `ControlSample` rows of an events CSV, per controller.
+pub fn analyze_events_csv(path: impl AsRef<Path>) -> io::Result<BTreeMap<String, ControllerSegments>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut trackers: BTreeMap<String, (QualityTracker, Vec<StepMetrics>)> = BTreeMap::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        // seq,pipeline,component,event,ts_ns,field1,field2,field3
+        let cols: Vec<&str> = line.split(',').collect();
+        if cols.len() < 8 {
+            continue;
+        }
+        let Ok(ts_ns) = cols[4].parse::<u64>() else { continue };
+        let t_s = ts_ns as f64 / 1e9;
+
+        match cols[3] {
+            "SegmentStart" => {
+                let Some(kind) = SegmentKind::parse(cols[6]) else { continue };
+                let (tracker, segments) = trackers.entry(cols[5].to_string()).or_default();
+                segments.extend(tracker.start(kind, t_s));
+            }
+            "ControlSample" => {
+                let (Ok(setpoint), Ok(measurement)) = (cols[6].parse::<f64>(), cols[7].parse::<f64>()) else {
+                    continue;
+                };
+                if let Some((tracker, _)) = trackers.get_mut(cols[5]) {
+                    tracker.sample(t_s, setpoint, measurement);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(trackers
+        .into_iter()
+        .map(|(name, (mut tracker, mut segments))| {
+            segments.extend(tracker.finish());
+            (name, ControllerSegments { segments, totals: tracker.snapshot() })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 0.01;
+
+    /// Opens a segment the way a controller does: only when `next_segment` asks for one.
+    fn control_instant(tracker: &mut QualityTracker, t_s: f64, continuous: bool, setpoint: f64, measurement: f64) -> Option<StepMetrics> {
+        let closed = tracker.next_segment(continuous, setpoint).and_then(|kind| tracker.start(kind, t_s));
+        tracker.sample(t_s, setpoint, measurement);
+        closed
+    }
+
+    /// 0 → 1 at t = 1 s: linear rise to 1.1 over 0.5 s, back to 1.0 over 0.1 s, then constant.
+    fn step_response(t_since: f64) -> f64 {
+        if t_since < 0.5 {
+            2.2 * t_since
+        } else if t_since < 0.6 {
+            1.1 - (t_since - 0.5)
+        } else {
+            1.0
+        }
+    }
+
+    #[test]
+    fn setpoint_jump_rolls_start_segment_into_step() {
+        let mut tracker = QualityTracker::default();
+        let mut closed = Vec::new();
+        for i in 0..300 {
+            let t = i as f64 * DT;
+            let (setpoint, measurement) = if t < 1.0 { (0.0, 0.0) } else { (1.0, step_response(t - 1.0)) };
+            closed.extend(control_instant(&mut tracker, t, false, setpoint, measurement));
+        }
+
+        // The start segment closes at the jump; the step is measured from the settled plant
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].kind, SegmentKind::Start);
+        assert_eq!(closed[0].samples, 100);
+
+        let step = tracker.finish().expect("step segment running");
+        assert_eq!(step.kind, SegmentKind::Step);
+        assert_eq!((step.from, step.to), (0.0, 1.0));
+        assert!((step.start_s - 1.0).abs() < 1e-9);
+        let rise = step.rise_time_s.expect("reached 90 %");
+        assert!((rise - 0.36).abs() < 2.0 * DT, "rise time {}", rise);
+        let overshoot = step.overshoot_pct.expect("step has overshoot");
+        assert!((overshoot - 10.0).abs() < 0.5, "overshoot {}", overshoot);
+        let settling = step.settling_time_s.expect("settled");
+        assert!((settling - 0.55).abs() < 2.0 * DT, "settling {}", settling);
+
+        let totals = tracker.snapshot();
+        assert_eq!((totals.segments, totals.steps, totals.risen, totals.settled), (2, 1, 1, 1));
+    }
+
+    #[test]
+    fn step_before_the_plant_settles_is_measured_from_the_measurement() {
+        let mut tracker = QualityTracker::default();
+        for i in 0..300 {
+            let t = i as f64 * DT;
+            // Setpoint 1.0 → 0.0 at t = 1 s while the plant is still at 0.5 (halfway up):
+            // from 0.5 it falls linearly to 0.0 over 0.5 s
+            let (setpoint, measurement) = if t < 1.0 { (1.0, 0.5) } else { (0.0, (0.5 - (t - 1.0)).max(0.0)) };
+            control_instant(&mut tracker, t, false, setpoint, measurement);
+        }
+
+        let step = tracker.finish().expect("step segment running");
+        assert_eq!((step.from, step.to), (0.5, 0.0));
+        // 10 → 90 % of the 0.5 step is 0.4 at 1 unit/s; not 0 as when measured from 1.0
+        let rise = step.rise_time_s.expect("reached 90 %");
+        assert!((rise - 0.4).abs() < 2.0 * DT, "rise time {}", rise);
+        let overshoot = step.overshoot_pct.expect("step figures");
+        assert!(overshoot.abs() < 1e-9, "overshoot {}", overshoot);
+        assert!(step.settling_time_s.is_some());
+    }
+
+    #[test]
+    fn unchanged_setpoint_keeps_the_running_segment() {
+        let mut tracker = QualityTracker::default();
+        assert_eq!(tracker.next_segment(false, 5.0), Some(SegmentKind::Start));
+        tracker.start(SegmentKind::Start, 0.0);
+        assert_eq!(tracker.next_segment(false, 6.0), None);     // No sample yet: nothing to jump from
+        tracker.sample(0.0, 5.0, 4.0);
+        assert_eq!(tracker.next_segment(false, 5.0), None);
+        assert_eq!(tracker.next_segment(false, 6.0), Some(SegmentKind::Step));
+    }
+
+    #[test]
+    fn continuous_profile_opens_one_tracking_segment() {
+        let mut tracker = QualityTracker::default();
+        let mut kinds = Vec::new();
+        for i in 0..200 {
+            let t = i as f64 * DT;
+            // Constant, then a ramp from t = 0.5 s, then constant again from t = 1.5 s
+            let (continuous, setpoint) = match t {
+                t if t < 0.5 => (false, 0.0),
+                t if t < 1.5 => (true, t - 0.5),
+                _ => (false, 1.0),
+            };
+            if let Some(kind) = tracker.next_segment(continuous, setpoint) {
+                kinds.push(kind);
+            }
+            control_instant(&mut tracker, t, continuous, setpoint, setpoint);
+        }
+        assert_eq!(kinds, [SegmentKind::Start, SegmentKind::Tracking, SegmentKind::Step]);
+        assert_eq!(tracker.snapshot().segments, 3);
+    }
+}
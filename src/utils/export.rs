@@ -5,21 +5,35 @@
 //! - `metrics_summary_load_X.csv` — Aggregated stats (min/max/avg) for sensors, actuators, latency.
 //! - `sensors_all.csv` — Appended rows: all sensor samples across sweep levels (for trending).
 //! - `actuators_all.csv` — Appended rows: all actuator commands across sweep levels.
-//! - `feedback_events.csv` — Feedback loop events (state, errors, acks) with microsecond timestamps.
+//! - `feedback_events.csv` — Feedback loop events (state, errors, acks, actuator limits) with microsecond timestamps.
+//! - `autotune_all.csv` — Appended rows: relay auto-tune results per controller across sweep levels;
+//!   `autotune_gains_load_X.toml` holds the identified gains as `[control.<controller>]` tables.
+//! - `control_quality_all.csv` — Appended rows: live control quality per actuator across sweep levels.
+//! - `control_steps_load_X.csv` — Every setpoint segment, re-analysed offline from the events CSV.
+//! - `fault_detection_all.csv` — Appended rows: detection of each injected actuator fault scenario
+//!   (latency, classification, false-positive rate) across sweep levels.
 
 use crate::utils::{
+    bus::Subscription,
+    logwriter::{LogPolicy, LogWriter},
+    control_quality::{ControlQuality, StepMetrics, analyze_events_csv},
     metrics::{SharedMetrics, calculate_stats, calculate_stats_u64},
     metrics_export::export_summary_csv,
 };
+use crate::component_a::processor::Anomaly;
 use crate::component_b::{
+    actuator_fault::{ActuatorFault, FaultScore},
+    autotune::AutotuneResult,
+    failsafe::SystemMode,
     feedback::{FeedbackKind,Feedback},
+    routing::delivery_ratio,
 };
 use std::{
     path::{Path,PathBuf},
     io::Write,
     thread,
     fs::{create_dir_all, OpenOptions,write},
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
 };
 
 use log::{info, error};
@@ -35,10 +49,7 @@ pub fn export_metrics_to_csv(metrics: SharedMetrics, cpu_load_threads: usize) {
         return;
     }
 
-    let m = match metrics.lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
+    let m = metrics.snapshot();
 
     // Build summary: deadline misses, latency/jitter stats, sensor/actuator stats
     let mut csv_content = String::from("metric,value,description\n");
@@ -86,6 +97,172 @@ pub fn export_metrics_to_csv(metrics: SharedMetrics, cpu_load_threads: usize) {
     csv_content.push_str(&format!("motor_commands,{},Total motor actuations\n", m.motor.len()));
     csv_content.push_str(&format!("stabiliser_commands,{},Total stabiliser adjustments\n", m.stabiliser.len()));
 
+    // Stale-command rejections per actuator (separate from deadline misses)
+    csv_content.push_str(&format!("stale_rejections,{},Commands rejected by freshness limit\n", m.stale_total()));
+    for (name, n) in &m.stale_rejections {
+        csv_content.push_str(&format!("{}_stale_rejections,{},Stale commands at {}\n", name.to_lowercase(), n, name));
+    }
+
+    // Actuator model: how often commands started to hit a limit
+    for (name, n) in &m.saturations {
+        csv_content.push_str(&format!("{}_saturations,{},Saturation onsets at {}\n", name.to_lowercase(), n, name));
+    }
+    for (name, n) in &m.rate_limits {
+        csv_content.push_str(&format!("{}_rate_limits,{},Rate-limit onsets at {}\n", name.to_lowercase(), n, name));
+    }
+
+    // Fail-safe supervisor: final mode, transitions, time per mode (when enabled)
+    if m.mode.enabled {
+        csv_content.push_str(&format!("failsafe_mode_final,{},System mode at the end of the run\n", m.mode.mode.name()));
+        csv_content.push_str(&format!("failsafe_transitions,{},Mode changes\n", m.mode.transitions));
+        for mode in SystemMode::ALL {
+            csv_content.push_str(&format!("failsafe_time_{}_s,{:.3},Time spent {}\n", mode.name(), m.mode.time_in_mode_s[mode as usize], mode.name()));
+        }
+    }
+
+    // Actuator fault detection per scenario (details in fault_detection_all.csv)
+    for s in &m.fault_detection {
+        let key = format!("fault_{}", s.scenario);
+        if let Some(injected) = s.injected {
+            csv_content.push_str(&format!("{}_detected,{},{} at {} detected\n", key, s.detected_at_s.is_some(), injected.name(), s.actuator));
+            csv_content.push_str(&format!("{}_latency_ms,{},Injection to first verdict\n", key, opt(s.latency_s().map(|l| l * 1e3), 1)));
+            csv_content.push_str(&format!("{}_classified_as,{},First verdict\n", key, s.detected_as.map_or("", ActuatorFault::name)));
+        }
+        csv_content.push_str(&format!("{}_false_positives,{},Verdicts on healthy actuators\n", key, s.false_positives));
+        csv_content.push_str(&format!("{}_false_positive_rate,{:.6},False verdicts per healthy check ({} checks)\n", key, s.false_positive_rate(), s.healthy_checks));
+    }
+
+    // Control-law compute time per law, against the 2 ms actuator deadline
+    for (name, t) in &m.control_laws {
+        csv_content.push_str(&format!("law_{}_computations,{},Control-law calls\n", name, t.count));
+        csv_content.push_str(&format!("law_{}_compute_mean_ns,{:.0},Mean compute time\n", name, t.mean_ns()));
+        csv_content.push_str(&format!("law_{}_compute_p99_ns,{},Compute time 99th percentile (log2 bucket bound)\n", name, t.percentile_ns(0.99)));
+        csv_content.push_str(&format!("law_{}_compute_max_ns,{},Maximum compute time\n", name, t.max_ns));
+        csv_content.push_str(&format!("law_{}_over_deadline,{},Computations longer than the 2 ms actuator deadline\n", name, t.over_deadline));
+    }
+
+    // Relay auto-tune per controller (details in autotune_all.csv)
+    for (name, r) in &m.autotune {
+        let key = format!("autotune_{}", name.to_lowercase());
+        csv_content.push_str(&format!("{}_converged,{},Relay experiment identified the loop ({})\n", key, r.converged, r.rule));
+        csv_content.push_str(&format!("{}_applied,{},Identified gains switched to\n", key, r.applied));
+        csv_content.push_str(&format!("{}_ultimate_gain,{:.4},Ku from the relay limit cycle\n", key, r.ultimate_gain));
+        csv_content.push_str(&format!("{}_ultimate_period_s,{:.4},Pu from the relay limit cycle\n", key, r.ultimate_period_s));
+    }
+
+    // Control quality per actuator (averages over its setpoint steps; details in control_steps_load_X.csv)
+    for (name, q) in &m.control_quality {
+        let key = format!("quality_{}", name.to_lowercase());
+        csv_content.push_str(&format!("{}_steps,{},Setpoint steps analysed ({} segments)\n", key, q.steps, q.segments));
+        csv_content.push_str(&format!("{}_rise_time_s,{},Mean 10-90 % rise time\n", key, opt(q.mean_rise_time_s(), 4)));
+        csv_content.push_str(&format!("{}_overshoot_pct,{},Mean overshoot (% of step)\n", key, opt(q.mean_overshoot_pct(), 2)));
+        csv_content.push_str(&format!("{}_overshoot_max_pct,{:.2},Worst overshoot (% of step)\n", key, q.overshoot_max_pct));
+        csv_content.push_str(&format!("{}_settling_time_s,{},Mean settling time (steps that settled: {})\n", key, opt(q.mean_settling_time_s(), 4), q.settled));
+        csv_content.push_str(&format!("{}_ss_error,{},Mean absolute steady-state error\n", key, opt(q.mean_abs_ss_error(), 4)));
+        csv_content.push_str(&format!("{}_iae,{:.4},Integral of absolute error\n", key, q.iae));
+        csv_content.push_str(&format!("{}_ise,{:.4},Integral of squared error\n", key, q.ise));
+        csv_content.push_str(&format!("{}_itae,{:.4},Integral of time-weighted absolute error\n", key, q.itae));
+    }
+
+    // Receiver-side sequence accounting (loss rate, worst burst, reordering)
+    for (name, s) in &m.seq_stats {
+        let key = name.to_lowercase();
+        csv_content.push_str(&format!("{}_seq_expected,{},Packets expected from sequence numbers\n", key, s.expected));
+        csv_content.push_str(&format!("{}_seq_lost,{},Sequence numbers never received\n", key, s.lost));
+        csv_content.push_str(&format!("{}_seq_loss_rate,{:.6},Fraction of expected packets lost\n", key, s.loss_rate()));
+        csv_content.push_str(&format!("{}_seq_max_burst_loss,{},Longest run of consecutive losses\n", key, s.max_burst_loss));
+        csv_content.push_str(&format!("{}_seq_duplicates,{},Duplicate packets discarded\n", key, s.duplicates));
+        csv_content.push_str(&format!("{}_seq_out_of_order,{},Late arrivals\n", key, s.out_of_order));
+        csv_content.push_str(&format!("{}_seq_late_discarded,{},Arrivals too late for the reorder window\n", key, s.late_discarded));
+    }
+
+    // SyncManager diagnostics (uniform snapshot for every sync mode)
+    if let Some(mode) = m.sync_mode {
+        let d = &m.sync_diagnostics;
+        csv_content.push_str(&format!("sync_mode,{:?},SyncManager diagnostics strategy\n", mode));
+        let mut ids: Vec<_> = d.sample_count.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            csv_content.push_str(&format!("sync_samples_sensor_{},{},Samples recorded by SyncManager\n", id, d.sample_count[&id]));
+            csv_content.push_str(&format!("sync_jitter_sum_us_sensor_{},{},Jitter sum recorded by SyncManager\n", id, d.jitter_sum.get(&id).copied().unwrap_or(0)));
+        }
+        csv_content.push_str(&format!("sync_proc_misses,{},Processing misses recorded by SyncManager\n", d.proc_miss_count));
+        csv_content.push_str(&format!("sync_tx_drops,{},Transmit drops recorded by SyncManager\n", d.tx_drop_count));
+        csv_content.push_str(&format!("sync_rx_latency_events,{},Receiver latency events (LockFree only)\n", d.rx_latency_count));
+        csv_content.push_str(&format!("sync_dropped_logs,{},Events dropped on full log queue (LockFree only)\n", d.dropped_logs));
+        // Histogram percentiles are bucket upper bounds (log2 buckets, LockFree only)
+        let h = &d.rx_latency_hist;
+        if h.count > 0 {
+            csv_content.push_str(&format!("sync_rx_latency_p50_us,{},Receiver latency median (LockFree only)\n", h.percentile_us(0.50)));
+            csv_content.push_str(&format!("sync_rx_latency_p99_us,{},Receiver latency 99th percentile (LockFree only)\n", h.percentile_us(0.99)));
+            csv_content.push_str(&format!("sync_rx_latency_max_us,{},Receiver latency maximum (LockFree only)\n", h.max_us));
+        }
+        let mut ids: Vec<_> = d.jitter_hist.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let h = &d.jitter_hist[&id];
+            csv_content.push_str(&format!("sync_jitter_p99_us_sensor_{},{},Jitter 99th percentile (LockFree only)\n", id, h.percentile_us(0.99)));
+            csv_content.push_str(&format!("sync_jitter_max_us_sensor_{},{},Jitter maximum (LockFree only)\n", id, h.max_us));
+        }
+    }
+
+    // SyncManager lock contention per producer thread, then totals (instrumented runs only)
+    if let Some(c) = &m.sync_contention {
+        let total = c.total();
+        for t in c.threads.iter().chain(std::iter::once(&total)) {
+            let key = format!("sync_contention_{}", t.thread);
+            csv_content.push_str(&format!("{}_ops,{},Instrumented SyncManager records\n", key, t.ops));
+            csv_content.push_str(&format!("{}_wait_avg_ns,{:.1},Average lock acquisition time\n", key, t.wait_avg_ns()));
+            csv_content.push_str(&format!("{}_wait_max_ns,{},Worst lock acquisition time\n", key, t.wait_max_ns));
+            csv_content.push_str(&format!("{}_hold_avg_ns,{:.1},Average critical-section time\n", key, t.hold_avg_ns()));
+            csv_content.push_str(&format!("{}_hold_max_ns,{},Worst critical-section time\n", key, t.hold_max_ns));
+            csv_content.push_str(&format!("{}_cas_retries,{},Failed CAS attempts (Atomics/Sharded)\n", key, t.cas_retries));
+            csv_content.push_str(&format!("{}_push_fails,{},Events rejected by full queue (LockFree)\n", key, t.push_fails));
+        }
+    }
+
+    // EventRecorder per-thread buffers (recorded / dropped on a full buffer), exporter total
+    if !m.event_producers.is_empty() {
+        csv_content.push_str(&format!("events_exported,{},Events written to the event CSV\n", m.events_exported));
+        for p in &m.event_producers {
+            let key = format!("events_{}", p.thread);
+            csv_content.push_str(&format!("{}_recorded,{},Events buffered by this producer\n", key, p.recorded));
+            csv_content.push_str(&format!("{}_dropped,{},Events dropped on a full producer buffer\n", key, p.dropped));
+        }
+    }
+
+    // Pub/sub bus delivery per subscriber (drops by overflow policy, publish → receive lag)
+    for s in &m.bus_stats {
+        let key = format!("bus_{}_{}", s.topic, s.subscriber);
+        csv_content.push_str(&format!("{}_delivered,{},Messages received ({} policy)\n", key, s.delivered, s.policy.name()));
+        csv_content.push_str(&format!("{}_dropped,{},Messages discarded by overflow policy\n", key, s.dropped));
+        csv_content.push_str(&format!("{}_max_depth,{},Deepest queue observed (capacity {})\n", key, s.max_depth, s.capacity));
+        csv_content.push_str(&format!("{}_lag_avg_us,{:.2},Average publish to receive lag\n", key, s.lag_avg_us));
+        csv_content.push_str(&format!("{}_lag_max_us,{},Maximum publish to receive lag\n", key, s.lag_max_us));
+    }
+
+    // Sensor → actuator routing: fan-out per sensor, fan-in per actuator, deliveries per route
+    for f in &m.routing.fan_out {
+        let key = format!("fanout_{}", f.sensor.to_lowercase());
+        csv_content.push_str(&format!("{}_actuators,{},Actuators routed from {}\n", key, f.actuators, f.sensor));
+        csv_content.push_str(&format!("{}_packets,{},Packets dispatched\n", key, f.packets));
+        csv_content.push_str(&format!("{}_delivered,{},Copies queued over all routes\n", key, f.delivered));
+        csv_content.push_str(&format!("{}_dropped,{},Copies dropped on a full actuator channel\n", key, f.dropped));
+    }
+    for f in &m.routing.fan_in {
+        let key = format!("fanin_{}", f.actuator.to_lowercase());
+        csv_content.push_str(&format!("{}_sensors,{},Sensors routed to {} ({} role)\n", key, f.sensors, f.actuator, f.role.name().to_lowercase()));
+        csv_content.push_str(&format!("{}_received,{},Packets queued over all routes\n", key, f.received));
+        csv_content.push_str(&format!("{}_dropped,{},Packets dropped on a full channel\n", key, f.dropped));
+        csv_content.push_str(&format!("{}_delivery_ratio,{:.6},Received per packet offered\n", key, delivery_ratio(f.received, f.dropped)));
+        csv_content.push_str(&format!("{}_max_depth,{},Deepest channel observed (capacity {}, priority {})\n", key, f.max_depth, f.capacity, f.priority.label()));
+    }
+    for r in &m.routing.routes {
+        let key = format!("route_{}_{}", r.sensor.to_lowercase(), r.actuator.to_lowercase());
+        csv_content.push_str(&format!("{}_delivered,{},Packets queued on this route\n", key, r.delivered));
+        csv_content.push_str(&format!("{}_dropped,{},Packets dropped on this route\n", key, r.dropped));
+    }
+
     let summary_path = export_dir.join(format!("metrics_summary_load_{}.csv", cpu_load_threads));
     match write(&summary_path, csv_content) {
         Ok(_) => info!("Summary metrics exported to: {:?}", summary_path),
@@ -110,9 +287,34 @@ pub fn export_metrics_to_csv(metrics: SharedMetrics, cpu_load_threads: usize) {
         &m.stabiliser,
     );
 
+    // Auto-tune results: sweep-wide CSV plus a config snippet per load level
+    if !m.autotune.is_empty() {
+        append_autotune_csv(export_dir.join("autotune_all.csv"), cpu_load_threads, &m.autotune);
+        export_autotune_gains(
+            export_dir.join(format!("autotune_gains_load_{}.toml", cpu_load_threads)),
+            cpu_load_threads,
+            &m.autotune,
+        );
+    }
+
+    // Control quality: sweep-wide CSV (one row per actuator and load level)
+    if !m.control_quality.is_empty() {
+        append_control_quality_csv(export_dir.join("control_quality_all.csv"), cpu_load_threads, &m.control_quality);
+    }
+
+    // Fault detection: sweep-wide CSV (one row per scenario and load level)
+    if !m.fault_detection.is_empty() {
+        append_fault_detection_csv(export_dir.join("fault_detection_all.csv"), cpu_load_threads, &m.fault_detection);
+    }
+
     info!("Consolidated metrics exported to data/export/");
 }
 
+/// Optional figure as CSV text (empty when absent).
+fn opt(value: Option<f64>, decimals: usize) -> String {
+    value.map(|v| format!("{:.*}", decimals, v)).unwrap_or_default()
+}
+
 /// Appends sensor readings to sweep-wide CSV: load_level,sample_index,force,position,temperature.
 /// Creates header on first write; enables trending across multiple CPU load experiments.
 fn append_to_consolidated_csv(
@@ -137,11 +339,11 @@ fn append_to_consolidated_csv(
     };
 
     // Write header on first experiment
-    if !file_exists {
-        if let Err(e) = writeln!(file, "load_level,sample_index,force,position,temperature") {
-            error!("Failed to write sensor CSV header: {}", e);
-            return;
-        }
+    if !file_exists
+        && let Err(e) = writeln!(file, "load_level,sample_index,force,position,temperature")
+    {
+        error!("Failed to write sensor CSV header: {}", e);
+        return;
     }
 
     let max_len = force.len().max(position.len()).max(temperature.len());
@@ -185,11 +387,11 @@ fn append_to_consolidated_csv_actuators(
     };
 
     // Write header on first experiment
-    if !file_exists {
-        if let Err(e) = writeln!(file, "load_level,sample_index,gripper,motor,stabiliser") {
-            error!("Failed to write actuator CSV header: {}", e);
-            return;
-        }
+    if !file_exists
+        && let Err(e) = writeln!(file, "load_level,sample_index,gripper,motor,stabiliser")
+    {
+        error!("Failed to write actuator CSV header: {}", e);
+        return;
     }
 
     let max_len = gripper.len().max(motor.len()).max(stabiliser.len());
@@ -209,6 +411,169 @@ fn append_to_consolidated_csv_actuators(
     info!("Appended {} actuator samples to actuators_all.csv", max_len);
 }
 
+/// Appends auto-tune results to the sweep-wide CSV (one row per controller and load level).
+fn append_autotune_csv(path: PathBuf, load_level: usize, results: &BTreeMap<&'static str, AutotuneResult>) {
+    let file_exists = path.exists();
+
+    let mut file = match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("Failed to open auto-tune CSV: {}", e);
+            return;
+        }
+    };
+
+    if !file_exists {
+        let header = "load_level,controller,rule,converged,applied,ultimate_gain,ultimate_period_s,oscillation,relay_amplitude,bias,kp,ki,kd,cycles,duration_s";
+        if let Err(e) = writeln!(file, "{}", header) {
+            error!("Failed to write auto-tune CSV header: {}", e);
+            return;
+        }
+    }
+
+    for (name, r) in results {
+        let [kp, ki, kd] = r.gains;
+        if let Err(e) = writeln!(
+            file,
+            "{},{},{},{},{},{:.6},{:.6},{:.6},{:.4},{:.4},{:.6},{:.6},{:.6},{},{:.3}",
+            load_level, name, r.rule, r.converged, r.applied, r.ultimate_gain, r.ultimate_period_s,
+            r.oscillation, r.relay_amplitude, r.bias, kp, ki, kd, r.cycles, r.duration_s
+        ) {
+            error!("Failed to write auto-tune CSV row: {}", e);
+            return;
+        }
+    }
+
+    info!("Appended {} auto-tune results to autotune_all.csv", results.len());
+}
+
+/// Appends the live control quality to the sweep-wide CSV (one row per actuator and load level).
+fn append_control_quality_csv(path: PathBuf, load_level: usize, quality: &BTreeMap<&'static str, ControlQuality>) {
+    let file_exists = path.exists();
+
+    let mut file = match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("Failed to open control quality CSV: {}", e);
+            return;
+        }
+    };
+
+    if !file_exists {
+        let header = "load_level,actuator,segments,steps,risen,settled,mean_rise_time_s,mean_overshoot_pct,max_overshoot_pct,mean_settling_time_s,mean_abs_ss_error,iae,ise,itae";
+        if let Err(e) = writeln!(file, "{}", header) {
+            error!("Failed to write control quality CSV header: {}", e);
+            return;
+        }
+    }
+
+    for (name, q) in quality {
+        if let Err(e) = writeln!(
+            file,
+            "{},{},{},{},{},{},{},{},{:.2},{},{},{:.6},{:.6},{:.6}",
+            load_level, name, q.segments, q.steps, q.risen, q.settled,
+            opt(q.mean_rise_time_s(), 4), opt(q.mean_overshoot_pct(), 2), q.overshoot_max_pct,
+            opt(q.mean_settling_time_s(), 4), opt(q.mean_abs_ss_error(), 6), q.iae, q.ise, q.itae
+        ) {
+            error!("Failed to write control quality CSV row: {}", e);
+            return;
+        }
+    }
+
+    info!("Appended {} actuators to control_quality_all.csv", quality.len());
+}
+
+/// Re-analyses the run's events CSV offline and writes one row per setpoint segment
+/// to `control_steps_load_X.csv` (same analyzer as the live figures).
+pub fn export_control_steps(events_csv: impl AsRef<Path>, cpu_load_threads: usize) {
+    let export_dir = Path::new("data/dash_live_results");
+    let analysed = match analyze_events_csv(&events_csv) {
+        Ok(analysed) => analysed,
+        Err(e) => {
+            error!("Failed to analyse control quality from {:?}: {}", events_csv.as_ref(), e);
+            return;
+        }
+    };
+
+    let mut csv = String::from(
+        "load_level,actuator,segment,kind,start_s,duration_s,from,to,rise_time_s,overshoot_pct,settling_time_s,steady_state_error,iae,ise,itae,samples\n",
+    );
+    for (name, result) in &analysed {
+        for (i, s) in result.segments.iter().enumerate() {
+            csv.push_str(&step_row(cpu_load_threads, name, i, s));
+        }
+    }
+
+    let path = export_dir.join(format!("control_steps_load_{}.csv", cpu_load_threads));
+    match write(&path, csv) {
+        Ok(_) => info!("Control quality per setpoint segment exported to: {:?}", path),
+        Err(e) => error!("Failed to export control steps: {}", e),
+    }
+}
+
+/// One `control_steps_load_X.csv` row.
+fn step_row(load_level: usize, actuator: &str, segment: usize, s: &StepMetrics) -> String {
+    format!(
+        "{},{},{},{},{:.6},{:.6},{:.4},{:.4},{},{},{},{:.6},{:.6},{:.6},{:.6},{}\n",
+        load_level, actuator, segment, s.kind.name(), s.start_s, s.duration_s, s.from, s.to,
+        opt(s.rise_time_s, 6), opt(s.overshoot_pct, 3), opt(s.settling_time_s, 6),
+        s.steady_state_error, s.iae, s.ise, s.itae, s.samples
+    )
+}
+
+/// Writes the identified gains as `[control.<controller>]` tables, ready for config.toml.
+fn export_autotune_gains(path: PathBuf, load_level: usize, results: &BTreeMap<&'static str, AutotuneResult>) {
+    let mut toml = format!("# Relay auto-tune gains identified at cpu_load_threads={}\n", load_level);
+    for (name, r) in results.iter().filter(|(_, r)| r.converged) {
+        let [kp, ki, kd] = r.gains;
+        toml.push_str(&format!(
+            "\n[control.{}]\n# {}: Ku = {:.4}, Pu = {:.4} s\nkp = {:.6}\nki = {:.6}\nkd = {:.6}\n",
+            name.to_lowercase(), r.rule, r.ultimate_gain, r.ultimate_period_s, kp, ki, kd
+        ));
+    }
+
+    match write(&path, toml) {
+        Ok(_) => info!("Auto-tune gains exported to: {:?}", path),
+        Err(e) => error!("Failed to export auto-tune gains: {}", e),
+    }
+}
+
+/// Appends fault detection scores to the sweep-wide CSV (one row per scenario and load level).
+fn append_fault_detection_csv(path: PathBuf, load_level: usize, scores: &[FaultScore]) {
+    let file_exists = path.exists();
+
+    let mut file = match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("Failed to open fault detection CSV: {}", e);
+            return;
+        }
+    };
+
+    if !file_exists {
+        let header = "load_level,scenario,actuator,injected,start_s,end_s,detected,detected_as,latency_ms,false_positives,healthy_checks,false_positive_rate";
+        if let Err(e) = writeln!(file, "{}", header) {
+            error!("Failed to write fault detection CSV header: {}", e);
+            return;
+        }
+    }
+
+    for s in scores {
+        if let Err(e) = writeln!(
+            file,
+            "{},{},{},{},{:.3},{:.3},{},{},{},{},{},{:.6}",
+            load_level, s.scenario, s.actuator, s.injected.map_or("", ActuatorFault::name), s.start_s, s.end_s,
+            s.detected_at_s.is_some(), s.detected_as.map_or("", ActuatorFault::name), opt(s.latency_s().map(|l| l * 1e3), 1),
+            s.false_positives, s.healthy_checks, s.false_positive_rate()
+        ) {
+            error!("Failed to write fault detection CSV row: {}", e);
+            return;
+        }
+    }
+
+    info!("Appended {} fault detection rows to fault_detection_all.csv", scores.len());
+}
+
 /// Calls all export functions: metrics summary + sweep-wide CSVs + deadline miss rate CSV.
 pub fn run_exports(metrics: SharedMetrics, cpu_load_threads: usize) {
     export_metrics_to_csv(metrics.clone(), cpu_load_threads);
@@ -216,50 +581,91 @@ pub fn run_exports(metrics: SharedMetrics, cpu_load_threads: usize) {
 }
 
 /// Spawns background thread logging feedback loop events (state, errors, acks).
-/// Appends rows to `data/logs/feedback_events.csv`: timestamp_us,actuator,feedback_kind,value.
-/// Runs until feedback channel closes (simulator shutdown).
+/// Writes `data/logs/feedback_events.csv`: timestamp_us,actuator,feedback_kind,value,
+/// through one `LogWriter` kept open for the whole run (rotated per `policy`).
+/// Runs until the feedback topic closes (simulator shutdown).
 pub fn spawn_feedback_handler(
-    feedback_rx: crossbeam::channel::Receiver<Feedback>,
+    feedback_rx: Subscription<Feedback>,
+    policy: LogPolicy,
 ) -> thread::JoinHandle<()> {
     thread::spawn(move || {
         let log_path = "data/logs/feedback_events.csv";
-        let _ = create_dir_all("data/logs");
-
-        // Write header on first run
-        let file_exists = Path::new(log_path).exists();
-        if !file_exists {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .open(log_path)
-            {
-                let _ = file.write_all(b"timestamp_us,actuator,feedback_kind,value\n");
+        let mut file = match LogWriter::create(log_path, "timestamp_us,actuator,feedback_kind,value\n", policy) {
+            Ok(f) => f,
+            Err(e) => {
+                error!("Failed to create feedback log {}: {}", log_path, e);
+                // Keep draining so the subscription's queue never fills
+                while feedback_rx.recv().is_ok() {}
+                return;
             }
-        }
+        };
 
         // Drain feedback channel until closed (simulator shutdown)
         while let Ok(msg) = feedback_rx.recv() {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(log_path)
-            {
-                let timestamp_us = msg.timestamp.elapsed().as_micros();
-                
-                let csv_row = match msg.kind {
-                    FeedbackKind::ActuatorState(value) => {
-                        format!("{},{},ActuatorState,{:.2}\n", timestamp_us, msg.actuator, value)
-                    }
-                    FeedbackKind::Error(e) => {
-                        format!("{},{},Error,{}\n", timestamp_us, msg.actuator, e)
-                    }
-                    FeedbackKind::Ack => {
-                        format!("{},{},Ack,0\n", timestamp_us, msg.actuator)
-                    }
-                };
-
-                let _ = file.write_all(csv_row.as_bytes());
+            let timestamp_us = msg.timestamp.elapsed().as_micros();
+
+            let csv_row = match msg.kind {
+                FeedbackKind::ActuatorState(value) => {
+                    format!("{},{},ActuatorState,{:.2}\n", timestamp_us, msg.actuator, value)
+                }
+                FeedbackKind::Error(e) => {
+                    format!("{},{},Error,{}\n", timestamp_us, msg.actuator, e)
+                }
+                FeedbackKind::Ack => {
+                    format!("{},{},Ack,0\n", timestamp_us, msg.actuator)
+                }
+                FeedbackKind::Saturated(requested) => {
+                    format!("{},{},Saturated,{:.2}\n", timestamp_us, msg.actuator, requested)
+                }
+                FeedbackKind::RateLimited(requested) => {
+                    format!("{},{},RateLimited,{:.2}\n", timestamp_us, msg.actuator, requested)
+                }
+                FeedbackKind::CommandedState(value) => {
+                    format!("{},{},CommandedState,{:.2}\n", timestamp_us, msg.actuator, value)
+                }
+                FeedbackKind::ActuatorFault(fault) => {
+                    format!("{},{},ActuatorFault,{}\n", timestamp_us, msg.actuator, fault.name())
+                }
+            };
+
+            let _ = file.write_all(csv_row.as_bytes());
+        }
+    })
+}
+
+/// Spawns background thread logging processor anomalies from the bus `anomalies` topic.
+/// Writes `data/logs/anomalies_load_X.csv`: timestamp_us,sensor,seq,reading,filtered,threshold
+/// (rotated per `policy`). Runs until the anomalies topic closes (simulator shutdown).
+pub fn spawn_anomaly_logger(
+    anomaly_rx: Subscription<Anomaly>,
+    cpu_load_threads: usize,
+    policy: LogPolicy,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let log_path = format!("data/logs/anomalies_load_{}.csv", cpu_load_threads);
+        let header = "timestamp_us,sensor,seq,reading,filtered,threshold\n";
+
+        let mut file = match LogWriter::create(&log_path, header, policy) {
+            Ok(f) => f,
+            Err(e) => {
+                error!("Failed to create anomaly log {}: {}", log_path, e);
+                // Keep draining so the subscription's queue never fills
+                while anomaly_rx.recv().is_ok() {}
+                return;
             }
+        };
+
+        while let Ok(a) = anomaly_rx.recv() {
+            let csv_row = format!(
+                "{},{},{},{:.4},{:.4},{:.3}\n",
+                a.timestamp.elapsed().as_micros(),
+                a.sensor_type.name(),
+                a.seq,
+                a.reading,
+                a.filtered,
+                a.threshold
+            );
+            let _ = file.write_all(csv_row.as_bytes());
         }
     })
-}
\ No newline at end of file
+}
@@ -0,0 +1,452 @@
+//! live_metrics.rs
+//! Lock-free side of the dashboard metrics, written by the RT threads.
+//!
+//! - `SampleRing`: fixed ring of the last `MAX_POINTS` values (readings, actuator outputs,
+//!   latency, jitter). Writers claim a slot with one `fetch_add`; readers copy without locking.
+//! - `SeqLock`: small `Copy` snapshots (per-sensor sequence stats); readers retry instead of
+//!   blocking the writer.
+//! - `NamedCounters`: per-actuator counters (stale rejections, saturation and rate-limit
+//!   onsets) registered on first use, no allocation.
+//! - `NamedTimings`: per-law compute-time histograms, registered the same way.
+//! - `NamedLatest`: latest `Copy` value per name behind a `SeqLock` (auto-tune results,
+//!   control quality).
+//! - Plain atomics for the deadline-miss and cycle counters.
+//!
+//! Readers (dashboard, exporters, MQTT) go through `SharedMetrics::snapshot()`; a snapshot taken
+//! while writers are active may mix values from consecutive updates, but never blocks them.
+
+use std::{
+    cell::UnsafeCell,
+    collections::{BTreeMap, VecDeque},
+    hint,
+    marker::PhantomData,
+    sync::{
+        OnceLock,
+        atomic::{AtomicU64, Ordering, fence},
+    },
+};
+
+use crate::component_a::sensor::{SensorType, SENSOR_COUNT};
+use crate::component_b::{autotune::AutotuneResult, failsafe::ModeStatus, routing::ActuatorRole};
+use crate::utils::control_quality::ControlQuality;
+use crate::utils::metrics::{
+    ComputeTiming, DeadlineComponent, MAX_POINTS, Metrics, SequenceStats, TIMING_BUCKETS,
+};
+
+const MAX_NAMED_COUNTERS: usize = 16;
+
+/// Value stored in a `SampleRing` slot (as raw `u64` bits).
+pub trait RingValue: Copy {
+    fn to_bits(self) -> u64;
+    fn from_bits(bits: u64) -> Self;
+}
+
+impl RingValue for f64 {
+    fn to_bits(self) -> u64 {
+        f64::to_bits(self)
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+}
+
+impl RingValue for u64 {
+    fn to_bits(self) -> u64 {
+        self
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        bits
+    }
+}
+
+/// Last `MAX_POINTS` values of one series; any number of writers, wait-free.
+pub struct SampleRing<T> {
+    head: AtomicU64,            // Values ever pushed; next slot is head % MAX_POINTS
+    slots: Box<[AtomicU64]>,
+    _value: PhantomData<T>,
+}
+
+impl<T: RingValue> Default for SampleRing<T> {
+    fn default() -> Self {
+        Self {
+            head: AtomicU64::new(0),
+            slots: (0..MAX_POINTS).map(|_| AtomicU64::new(0)).collect(),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: RingValue> SampleRing<T> {
+    #[inline]
+    pub fn push(&self, value: T) {
+        let i = self.head.fetch_add(1, Ordering::AcqRel);
+        self.slots[(i % MAX_POINTS as u64) as usize].store(value.to_bits(), Ordering::Release);
+    }
+
+    /// Copies the retained values, oldest first.
+    pub fn to_deque(&self) -> VecDeque<T> {
+        let head = self.head.load(Ordering::Acquire);
+        (head.saturating_sub(MAX_POINTS as u64)..head).map(|i| self.at(i)).collect()
+    }
+
+    /// Empties the ring; only between runs (no writers).
+    pub fn clear(&self) {
+        self.head.store(0, Ordering::Release);
+    }
+
+    fn at(&self, i: u64) -> T {
+        T::from_bits(self.slots[(i % MAX_POINTS as u64) as usize].load(Ordering::Acquire))
+    }
+}
+
+/// Sequence lock over a small `Copy` value: writers never wait for readers.
+/// Concurrent writers serialise on the sequence word (one writer per value in practice).
+pub struct SeqLock<T> {
+    seq: AtomicU64,             // Odd while a write is in progress
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy + Default> Default for SeqLock<T> {
+    fn default() -> Self {
+        Self { seq: AtomicU64::new(0), value: UnsafeCell::new(T::default()) }
+    }
+}
+
+impl<T: Copy> SeqLock<T> {
+    pub fn write(&self, value: T) {
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq & 1 == 0 {
+                match self.seq.compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed) {
+                    Ok(_) => break,
+                    Err(current) => seq = current,
+                }
+            } else {
+                hint::spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+            }
+        }
+        fence(Ordering::Release);
+        // SAFETY: the odd sequence excludes other writers; readers discard torn copies
+        unsafe { self.value.get().write_volatile(value) };
+        self.seq.store(seq + 2, Ordering::Release);
+    }
+
+    pub fn read(&self) -> T {
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            // SAFETY: a copy racing a write is detected by the sequence check and retried
+            let value = unsafe { self.value.get().read_volatile() };
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == before {
+                return value;
+            }
+        }
+    }
+}
+
+/// Counters keyed by a `'static` name, registered on first use (at most `MAX_NAMED_COUNTERS`).
+pub struct NamedCounters {
+    slots: [(OnceLock<&'static str>, AtomicU64); MAX_NAMED_COUNTERS],
+}
+
+impl Default for NamedCounters {
+    fn default() -> Self {
+        Self { slots: std::array::from_fn(|_| (OnceLock::new(), AtomicU64::new(0))) }
+    }
+}
+
+impl NamedCounters {
+    pub fn add(&self, name: &'static str, n: u64) {
+        for (slot_name, count) in &self.slots {
+            if *slot_name.get_or_init(|| name) == name {
+                count.fetch_add(n, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<&'static str, u64> {
+        self.slots
+            .iter()
+            .map_while(|(name, count)| Some((*name.get()?, count.load(Ordering::Relaxed))))
+            .collect()
+    }
+
+    /// Zeroes every counter (names stay registered).
+    pub fn clear(&self) {
+        for (_, count) in &self.slots {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Compute-time histograms keyed by a `'static` name (at most `MAX_NAMED_COUNTERS`).
+pub struct NamedTimings {
+    slots: [TimingSlot; MAX_NAMED_COUNTERS],
+}
+
+#[derive(Default)]
+struct TimingSlot {
+    name: OnceLock<&'static str>,
+    count: AtomicU64,
+    sum_ns: AtomicU64,
+    max_ns: AtomicU64,
+    over_deadline: AtomicU64,
+    buckets: [AtomicU64; TIMING_BUCKETS],
+}
+
+impl Default for NamedTimings {
+    fn default() -> Self {
+        Self { slots: std::array::from_fn(|_| TimingSlot::default()) }
+    }
+}
+
+impl NamedTimings {
+    pub fn record(&self, name: &'static str, ns: u64, over_deadline: bool) {
+        let Some(slot) = self.slots.iter().find(|slot| *slot.name.get_or_init(|| name) == name) else {
+            return;
+        };
+        let bucket = ((u64::BITS - ns.leading_zeros()) as usize).min(TIMING_BUCKETS - 1);
+        slot.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        slot.count.fetch_add(1, Ordering::Relaxed);
+        slot.sum_ns.fetch_add(ns, Ordering::Relaxed);
+        slot.max_ns.fetch_max(ns, Ordering::Relaxed);
+        if over_deadline {
+            slot.over_deadline.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<&'static str, ComputeTiming> {
+        self.slots
+            .iter()
+            .map_while(|slot| {
+                let timing = ComputeTiming {
+                    count: slot.count.load(Ordering::Relaxed),
+                    sum_ns: slot.sum_ns.load(Ordering::Relaxed),
+                    max_ns: slot.max_ns.load(Ordering::Relaxed),
+                    over_deadline: slot.over_deadline.load(Ordering::Relaxed),
+                    buckets: std::array::from_fn(|i| slot.buckets[i].load(Ordering::Relaxed)),
+                };
+                Some((*slot.name.get()?, timing))
+            })
+            .collect()
+    }
+
+    /// Zeroes every histogram (names stay registered).
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            for counter in [&slot.count, &slot.sum_ns, &slot.max_ns, &slot.over_deadline].into_iter().chain(&slot.buckets) {
+                counter.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Latest value keyed by a `'static` name (at most `MAX_NAMED_COUNTERS`).
+pub struct NamedLatest<T> {
+    slots: [(OnceLock<&'static str>, SeqLock<Option<T>>); MAX_NAMED_COUNTERS],
+}
+
+impl<T: Copy + Default> Default for NamedLatest<T> {
+    fn default() -> Self {
+        Self { slots: std::array::from_fn(|_| (OnceLock::new(), SeqLock::default())) }
+    }
+}
+
+impl<T: Copy> NamedLatest<T> {
+    pub fn set(&self, name: &'static str, value: T) {
+        if let Some((_, latest)) = self.slots.iter().find(|(slot_name, _)| *slot_name.get_or_init(|| name) == name) {
+            latest.write(Some(value));
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<&'static str, T> {
+        self.slots
+            .iter()
+            .map_while(|(name, latest)| Some((*name.get()?, latest.read())))
+            .filter_map(|(name, latest)| Some((name, latest?)))
+            .collect()
+    }
+
+    /// Forgets every value (names stay registered).
+    pub fn clear(&self) {
+        for (_, latest) in &self.slots {
+            latest.write(None);
+        }
+    }
+}
+
+/// Everything the RT threads publish for the dashboard; see `SharedMetrics::live`.
+#[derive(Default)]
+pub struct LiveMetrics {
+    pub force: SampleRing<f64>,
+    pub position: SampleRing<f64>,
+    pub temperature: SampleRing<f64>,
+    pub gripper: SampleRing<f64>,
+    pub motor: SampleRing<f64>,
+    pub stabiliser: SampleRing<f64>,
+    pub latency_us: SampleRing<u64>,
+    pub jitter_us: SampleRing<u64>,
+
+    miss_sensor: AtomicU64,
+    miss_processor: AtomicU64,
+    miss_actuator: AtomicU64,
+    total_cycles: AtomicU64,
+    stale_rejections: NamedCounters,
+    saturations: NamedCounters,                           // Onsets per actuator
+    rate_limits: NamedCounters,
+    control_laws: NamedTimings,
+    autotune: NamedLatest<AutotuneResult>,
+    control_quality: NamedLatest<ControlQuality>,
+    seq_stats: [SeqLock<SequenceStats>; SENSOR_COUNT],    // By `SensorType::index`
+    mode: SeqLock<ModeStatus>,                            // Fail-safe supervisor
+}
+
+impl LiveMetrics {
+    /// Reading series of one sensor.
+    #[inline]
+    pub fn reading(&self, sensor: SensorType) -> &SampleRing<f64> {
+        match sensor {
+            SensorType::Force => &self.force,
+            SensorType::Position => &self.position,
+            SensorType::Temperature => &self.temperature,
+        }
+    }
+
+    /// Output series of an actuator role (written by the first routed actuator with it).
+    #[inline]
+    pub fn actuator_series(&self, role: ActuatorRole) -> &SampleRing<f64> {
+        match role {
+            ActuatorRole::Gripper => &self.gripper,
+            ActuatorRole::Motor => &self.motor,
+            ActuatorRole::Stabiliser => &self.stabiliser,
+        }
+    }
+
+    #[inline]
+    pub fn record_deadline_miss(&self, component: DeadlineComponent) {
+        let counter = match component {
+            DeadlineComponent::Sensor => &self.miss_sensor,
+            DeadlineComponent::Processor => &self.miss_processor,
+            DeadlineComponent::Actuator => &self.miss_actuator,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn record_cycle(&self) {
+        self.total_cycles.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a stale-command rejection for the named actuator.
+    #[inline]
+    pub fn record_stale(&self, actuator: &'static str) {
+        self.stale_rejections.add(actuator, 1);
+    }
+
+    /// Registers the actuator's stale counter at 0 so it shows up before the first rejection.
+    pub fn register_actuator(&self, actuator: &'static str) {
+        self.stale_rejections.add(actuator, 0);
+    }
+
+    /// Records the onset of saturation at the named actuator (actuator model).
+    #[inline]
+    pub fn record_saturation(&self, actuator: &'static str) {
+        self.saturations.add(actuator, 1);
+    }
+
+    /// Records the onset of rate limiting at the named actuator (actuator model).
+    #[inline]
+    pub fn record_rate_limit(&self, actuator: &'static str) {
+        self.rate_limits.add(actuator, 1);
+    }
+
+    /// Records one control-law computation and whether it overran the actuator deadline.
+    #[inline]
+    pub fn record_law(&self, law: &'static str, compute_ns: u64, over_deadline: bool) {
+        self.control_laws.record(law, compute_ns, over_deadline);
+    }
+
+    /// Publishes the latest auto-tune result of the named controller.
+    pub fn record_autotune(&self, controller: &'static str, result: AutotuneResult) {
+        self.autotune.set(controller, result);
+    }
+
+    /// Publishes the running control quality of the named actuator.
+    #[inline]
+    pub fn record_control_quality(&self, actuator: &'static str, quality: ControlQuality) {
+        self.control_quality.set(actuator, quality);
+    }
+
+    /// Publishes the fail-safe mode and its history (supervisor thread).
+    pub fn record_mode(&self, status: ModeStatus) {
+        self.mode.write(status);
+    }
+
+    #[inline]
+    pub fn set_seq_stats(&self, sensor: SensorType, stats: SequenceStats) {
+        self.seq_stats[sensor.index()].write(stats);
+    }
+
+    /// Copies the live series and counters into `m` (the run-level fields are left alone).
+    pub fn fill(&self, m: &mut Metrics) {
+        m.force = self.force.to_deque();
+        m.position = self.position.to_deque();
+        m.temperature = self.temperature.to_deque();
+        m.gripper = self.gripper.to_deque();
+        m.motor = self.motor.to_deque();
+        m.stabiliser = self.stabiliser.to_deque();
+        m.latency_us = self.latency_us.to_deque();
+        m.jitter_us = self.jitter_us.to_deque();
+
+        m.miss_sensor = self.miss_sensor.load(Ordering::Relaxed);
+        m.miss_processor = self.miss_processor.load(Ordering::Relaxed);
+        m.miss_actuator = self.miss_actuator.load(Ordering::Relaxed);
+        m.deadline_miss = m.miss_sensor + m.miss_processor + m.miss_actuator;
+        m.total_cycles = self.total_cycles.load(Ordering::Relaxed);
+        m.stale_rejections = self.stale_rejections.snapshot();
+        m.saturations = self.saturations.snapshot();
+        m.rate_limits = self.rate_limits.snapshot();
+        m.control_laws = self.control_laws.snapshot();
+        m.autotune = self.autotune.snapshot();
+        m.control_quality = self.control_quality.snapshot();
+        m.mode = self.mode.read();
+
+        m.seq_stats = SensorType::ALL
+            .into_iter()
+            .map(|s| (s.name(), self.seq_stats[s.index()].read()))
+            .filter(|(_, stats)| stats.expected > 0)
+            .collect();
+    }
+
+    /// Resets every series and counter; only between runs (no writers).
+    pub fn clear(&self) {
+        for ring in [&self.force, &self.position, &self.temperature, &self.gripper, &self.motor, &self.stabiliser] {
+            ring.clear();
+        }
+        self.latency_us.clear();
+        self.jitter_us.clear();
+        for counter in [&self.miss_sensor, &self.miss_processor, &self.miss_actuator, &self.total_cycles] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.stale_rejections.clear();
+        self.saturations.clear();
+        self.rate_limits.clear();
+        self.control_laws.clear();
+        self.autotune.clear();
+        self.control_quality.clear();
+        self.mode.write(ModeStatus::default());
+        for stats in &self.seq_stats {
+            stats.write(SequenceStats::default());
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! logwriter.rs
+//! Shared writer for the streaming CSV logs (event exporter, sync log, feedback, anomalies).
+//!
+//! - Size and time rotation: the active file keeps its name; full segments are renamed to
+//!   `<stem>.<NNNN>.<ext>` and optionally gzipped in the background (`.gz`).
+//! - Retention: only the newest `keep_segments` segments are kept per log.
+//! - A file left by a previous run is rotated aside on open instead of being overwritten.
+//! - Rows are never split across segments; every segment starts with the log's header.
+//! - Periodic flush + fsync (`fsync_interval`), plus a final flush on drop, panic and
+//!   SIGINT/SIGTERM (`install_crash_flush`).
+//!
+//! Configured from `[logging]` in `config/config.toml` (see `LoggingConfig`).
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    panic,
+    path::{Path, PathBuf},
+    sync::{Arc, Once, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+use flate2::{Compression, write::GzEncoder};
+use log::{error, info};
+use parking_lot::Mutex;
+
+// Crash-path lock timeout: a writer mid-write on the panicking thread must not hang the hook
+const CRASH_LOCK_TIMEOUT_MS: u64 = 50;
+
+// Every open writer, so the panic hook and signal thread can flush them
+static OPEN_LOGS: Mutex<Vec<Weak<Mutex<Segment>>>> = Mutex::new(Vec::new());
+static CRASH_FLUSH: Once = Once::new();
+
+/// Rotation, retention and durability settings for one log.
+#[derive(Debug, Clone)]
+pub struct LogPolicy {
+    pub max_bytes: Option<u64>,             // Rotate once the active segment reaches this size
+    pub max_age: Option<Duration>,          // Rotate once the active segment is this old
+    pub keep_segments: Option<usize>,       // Rotated segments kept (oldest deleted); None = all
+    pub compress: bool,                     // Gzip rotated segments
+    pub fsync_interval: Option<Duration>,   // Flush + fsync at most this often; None = on rotation/close only
+}
+
+impl Default for LogPolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: Some(64 * 1024 * 1024),
+            max_age: None,
+            keep_segments: Some(8),
+            compress: true,
+            fsync_interval: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// Active segment of one log.
+struct Segment {
+    path: PathBuf,
+    header: Vec<u8>,            // Written at the top of every segment
+    policy: LogPolicy,
+    out: BufWriter<File>,
+    bytes: u64,                 // Size of the active segment, header included
+    opened: Instant,
+    last_sync: Instant,
+    at_line_start: bool,        // Last byte written was '\n' (safe rotation point)
+}
+
+impl Segment {
+    fn rotation_due(&self) -> bool {
+        self.bytes > self.header.len() as u64
+            && (self.policy.max_bytes.is_some_and(|max| self.bytes >= max)
+                || self.policy.max_age.is_some_and(|age| self.opened.elapsed() >= age))
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.out.flush()?;
+        self.out.get_ref().sync_data()?;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.sync()?;
+        archive(&self.path, &self.policy)?;
+        self.out = BufWriter::new(File::create(&self.path)?);
+        self.out.write_all(&self.header)?;
+        self.bytes = self.header.len() as u64;
+        self.opened = Instant::now();
+        self.at_line_start = true;
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.rotation_due() {
+            if self.at_line_start {
+                self.rotate()?;
+            } else {
+                // Finish the current row first; rotate on the next call
+                let end = buf.iter().position(|&b| b == b'\n').map_or(buf.len(), |i| i + 1);
+                return self.write_counted(&buf[..end]);
+            }
+        }
+        self.write_counted(buf)
+    }
+
+    fn write_counted(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.out.write(buf)?;
+        if n > 0 {
+            self.bytes += n as u64;
+            self.at_line_start = buf[n - 1] == b'\n';
+        }
+        if self.policy.fsync_interval.is_some_and(|every| self.last_sync.elapsed() >= every) {
+            self.sync()?;
+        }
+        Ok(n)
+    }
+}
+
+/// Rotating, crash-flushed log file. Writes go through a `BufWriter`; the handle is
+/// cheap to lock (one writer thread per log).
+pub struct LogWriter {
+    segment: Arc<Mutex<Segment>>,
+}
+
+impl LogWriter {
+    /// Opens `path` (rotating aside any file left by a previous run) and writes `header`.
+    pub fn create(path: impl AsRef<Path>, header: impl Into<Vec<u8>>, policy: LogPolicy) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        if fs::metadata(&path).is_ok_and(|m| m.len() > 0) {
+            archive(&path, &policy)?;
+        }
+
+        let header = header.into();
+        let mut out = BufWriter::new(File::create(&path)?);
+        out.write_all(&header)?;
+        let now = Instant::now();
+        let segment = Arc::new(Mutex::new(Segment {
+            path,
+            bytes: header.len() as u64,
+            header,
+            policy,
+            out,
+            opened: now,
+            last_sync: now,
+            at_line_start: true,
+        }));
+
+        let mut open = OPEN_LOGS.lock();
+        open.retain(|w| w.strong_count() > 0);
+        open.push(Arc::downgrade(&segment));
+        Ok(Self { segment })
+    }
+
+    /// Flushes buffered rows and fsyncs the active segment.
+    pub fn sync(&self) -> io::Result<()> {
+        self.segment.lock().sync()
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.segment.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.segment.lock().out.flush()
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.sync() {
+            error!("Failed to flush log {:?}: {}", self.segment.lock().path, e);
+        }
+    }
+}
+
+// ============================================================================
+// SEGMENTS: naming, compression, retention
+// ============================================================================
+
+/// `data/logs/events_load_0.csv` → (`data/logs`, `events_load_0`, `csv`).
+fn split_path(path: &Path) -> (PathBuf, String, String) {
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = path.extension().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    (dir, stem, ext)
+}
+
+/// Rotated segments of `path` as (index, file), oldest first (`.gz` or not).
+fn segments(path: &Path) -> Vec<(u32, PathBuf)> {
+    let (dir, stem, ext) = split_path(path);
+    let dir_path = if dir.as_os_str().is_empty() { PathBuf::from(".") } else { dir };
+    let prefix = format!("{}.", stem);
+    let suffix = format!(".{}", ext);
+
+    let mut found: Vec<(u32, PathBuf)> = fs::read_dir(&dir_path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let index = name
+                .strip_prefix(&prefix)?
+                .trim_end_matches(".gz")
+                .strip_suffix(&suffix)?
+                .parse()
+                .ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Renames the active file to the next segment, compresses it and prunes old segments.
+fn archive(path: &Path, policy: &LogPolicy) -> io::Result<()> {
+    let existing = segments(path);
+    let next = existing.last().map_or(1, |(i, _)| i + 1);
+    let (dir, stem, ext) = split_path(path);
+    let target = dir.join(format!("{}.{:04}.{}", stem, next, ext));
+    fs::rename(path, &target)?;
+    info!("Rotated log {:?} -> {:?}", path, target);
+
+    if policy.compress {
+        thread::spawn(move || {
+            if let Err(e) = gzip(&target) {
+                error!("Failed to compress log segment {:?}: {}", target, e);
+            }
+        });
+    }
+
+    if let Some(keep) = policy.keep_segments {
+        // A segment being compressed exists twice (plain + .gz) for a moment: count indices
+        let mut indices: Vec<u32> = existing.iter().map(|(i, _)| *i).collect();
+        indices.dedup();
+        let excess = (indices.len() + 1).saturating_sub(keep);
+        for (_, old) in existing.iter().filter(|(i, _)| indices[..excess.min(indices.len())].contains(i)) {
+            if let Err(e) = fs::remove_file(old) {
+                error!("Failed to remove old log segment {:?}: {}", old, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `seg.csv` → `seg.csv.gz` (written beside, then renamed, then the original removed).
+fn gzip(segment: &Path) -> io::Result<()> {
+    let gz = PathBuf::from(format!("{}.gz", segment.display()));
+    let partial = PathBuf::from(format!("{}.gz.part", segment.display()));
+
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(&partial)?), Compression::default());
+    io::copy(&mut File::open(segment)?, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    fs::rename(&partial, &gz)?;
+    fs::remove_file(segment)
+}
+
+// ============================================================================
+// CRASH FLUSH: panic hook + SIGINT/SIGTERM
+// ============================================================================
+
+/// Flushes and fsyncs every open log (best effort: writers busy on a stuck thread are skipped).
+pub fn flush_all() {
+    let timeout = Duration::from_millis(CRASH_LOCK_TIMEOUT_MS);
+    let Some(open) = OPEN_LOGS.try_lock_for(timeout) else {
+        return;
+    };
+    for segment in open.iter().filter_map(Weak::upgrade) {
+        if let Some(mut s) = segment.try_lock_for(timeout) {
+            let _ = s.sync();
+        }
+    }
+}
+
+/// Installs (once) a panic hook and a SIGINT/SIGTERM handler that flush every open log.
+/// On a signal the process exits with 128 + signal number after flushing.
+pub fn install_crash_flush() {
+    CRASH_FLUSH.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            flush_all();
+            previous(info);
+        }));
+
+        use signal_hook::{consts::{SIGINT, SIGTERM}, iterator::Signals};
+        match Signals::new([SIGINT, SIGTERM]) {
+            Ok(mut signals) => {
+                let spawned = thread::Builder::new().name("log_signal".into()).spawn(move || {
+                    if let Some(sig) = signals.forever().next() {
+                        flush_all();
+                        std::process::exit(128 + sig);
+                    }
+                });
+                if let Err(e) = spawned {
+                    error!("Failed to start log signal handler: {}", e);
+                }
+            }
+            Err(e) => error!("Failed to register log signal handler: {}", e),
+        }
+    });
+}
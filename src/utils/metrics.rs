@@ -2,20 +2,39 @@
 //! Metrics collection and event recording for real-time system monitoring.
 //!
 //! Two independent paths:
-//! - **EventRecorder:** Lock-free queue (16K capacity) → background CSV export (nanosecond precision).
-//! - **Metrics:** Shared mutex buffer for live dashboard (bounded to 1000 points per metric).
+//! - **EventRecorder:** Per-thread lock-free buffers (8K each) → background CSV export merged by
+//!   timestamp (nanosecond precision), with per-producer drop counts.
+//! - **Metrics:** Live dashboard series (bounded to 1000 points per metric). RT threads write
+//!   lock-free rings and counters (`live_metrics.rs`); readers take non-blocking snapshots.
 //!
 //! Event tracing captures: sensor release → processing → transmission → actuator receipt → feedback.
 
 use std::{
-    sync::{Arc, Mutex},
-    collections::VecDeque,
-    fs::File,
-    io::{BufWriter, Write},
+    cell::RefCell,
+    cmp::Reverse,
+    sync::{Arc, Mutex, MutexGuard, atomic::{AtomicBool, AtomicU64, Ordering}},
+    collections::{BTreeMap, BinaryHeap, VecDeque},
+    io::Write,
     thread,
     time::{Instant, Duration},
 };
 use crossbeam_queue::ArrayQueue;
+use crate::utils::{
+    bus::SubscriberStats,
+    control_quality::{ControlQuality, SegmentKind},
+    live_metrics::LiveMetrics,
+    logwriter::{LogPolicy, LogWriter},
+};
+use crate::component_a::{
+    contention::ContentionStats,
+    sync_manager::{Diagnostics, SyncMode},
+};
+use crate::component_b::{
+    actuator_fault::FaultScore,
+    autotune::AutotuneResult,
+    failsafe::{ModeStatus, SystemMode},
+    routing::RoutingStats,
+};
 use log::error;
 
 /// Event lifecycle: sensor release through feedback completion.
@@ -26,7 +45,7 @@ pub enum Event {
     SensorRelease {
         seq: u64,
         ts_ns: u64,
-        sensor_type: String,
+        sensor_type: &'static str,        // Interned name (`SensorType::name`); no allocation per sample
     },
     /// Sensor data anomaly-checked and filtered.
     SensorProcessed {
@@ -53,12 +72,54 @@ pub enum Event {
         ts_ns: u64,
         control_output: f64,
         exec_us: u64,
+        setpoint: f64,                    // Target the output was computed for
     },
     /// Actuator sent feedback to processor (loop-close signal).
     FeedbackSent {
         seq: u64,
         ts_ns: u64,
     },
+    /// Actuator rejected a packet older than its freshness limit (sensor release → actuation).
+    StaleRejected {
+        seq: u64,
+        ts_ns: u64,
+        actuator: &'static str,
+        age_us: u64,
+    },
+    /// Controller parameter changed at runtime; `seq` is the first packet computed with it.
+    ParamChange {
+        seq: u64,
+        ts_ns: u64,
+        controller: &'static str,
+        param: &'static str,
+        old: f64,
+        new: f64,
+    },
+    /// Actuator setpoint jumped (or its controller started): a control-quality segment begins.
+    SegmentStart {
+        seq: u64,
+        ts_ns: u64,
+        controller: &'static str,
+        kind: SegmentKind,
+        setpoint: f64,
+    },
+    /// Actuator control instant: setpoint and the measurement the law acted on.
+    ControlSample {
+        seq: u64,
+        ts_ns: u64,
+        controller: &'static str,
+        setpoint: f64,
+        measurement: f64,
+    },
+    /// Fail-safe supervisor switched the system mode (`reason` from the watchdog at `source`).
+    ModeChange {
+        seq: u64,
+        ts_ns: u64,
+        from: SystemMode,
+        to: SystemMode,
+        reason: &'static str,
+        source: &'static str,
+    },
     /// Processor received feedback (may adjust thresholds).
     #[allow(dead_code)]
     FeedbackReceived {
@@ -83,12 +144,27 @@ impl Event {
             Event::ActuatorReceive { seq, ts_ns } => {
                 format!("{},threaded,actuator,ActuatorReceive,{},,,", seq, ts_ns)
             }
-            Event::ControllerComplete { seq, ts_ns, control_output, exec_us } => {
-                format!("{},threaded,actuator,ControllerComplete,{},control_out={},{},", seq, ts_ns, control_output, exec_us)
+            Event::ControllerComplete { seq, ts_ns, control_output, exec_us, setpoint } => {
+                format!("{},threaded,actuator,ControllerComplete,{},control_out={},{},setpoint={}", seq, ts_ns, control_output, exec_us, setpoint)
             }
             Event::FeedbackSent { seq, ts_ns } => {
                 format!("{},threaded,actuator,FeedbackSent,{},,,", seq, ts_ns)
             }
+            Event::StaleRejected { seq, ts_ns, actuator, age_us } => {
+                format!("{},threaded,actuator,StaleRejected,{},{},{},", seq, ts_ns, actuator, age_us)
+            }
+            Event::ParamChange { seq, ts_ns, controller, param, old, new } => {
+                format!("{},threaded,actuator,ParamChange,{},{}.{},{},{}", seq, ts_ns, controller, param, old, new)
+            }
+            Event::SegmentStart { seq, ts_ns, controller, kind, setpoint } => {
+                format!("{},threaded,actuator,SegmentStart,{},{},{},{}", seq, ts_ns, controller, kind.name(), setpoint)
+            }
+            Event::ControlSample { seq, ts_ns, controller, setpoint, measurement } => {
+                format!("{},threaded,actuator,ControlSample,{},{},{},{}", seq, ts_ns, controller, setpoint, measurement)
+            }
+            Event::ModeChange { seq, ts_ns, from, to, reason, source } => {
+                format!("{},threaded,supervisor,ModeChange,{},{},{},{}:{}", seq, ts_ns, from.name(), to.name(), reason, source)
+            }
             Event::FeedbackReceived { seq, ts_ns } => {
                 format!("{},threaded,sensor,FeedbackReceived,{},,,", seq, ts_ns)
             }
@@ -96,33 +172,155 @@ impl Event {
     }
 }
 
-const EVENT_QUEUE_CAPACITY: usize = 16_384;
+impl Event {
+    /// Nanosecond timestamp (recorder clock); the exporter merges producers on it.
+    pub fn ts_ns(&self) -> u64 {
+        match self {
+            Event::SensorRelease { ts_ns, .. }
+            | Event::SensorProcessed { ts_ns, .. }
+            | Event::SensorSent { ts_ns, .. }
+            | Event::ActuatorReceive { ts_ns, .. }
+            | Event::ControllerComplete { ts_ns, .. }
+            | Event::FeedbackSent { ts_ns, .. }
+            | Event::StaleRejected { ts_ns, .. }
+            | Event::ParamChange { ts_ns, .. }
+            | Event::SegmentStart { ts_ns, .. }
+            | Event::ControlSample { ts_ns, .. }
+            | Event::ModeChange { ts_ns, .. }
+            | Event::FeedbackReceived { ts_ns, .. } => *ts_ns,
+        }
+    }
+}
+
+const PRODUCER_BUFFER_CAPACITY: usize = 8_192;  // Per producer thread (single producer, single consumer)
+const MERGE_WINDOW_NS: u64 = 20_000_000;         // Exporter holds events this long to merge producers in ts order
+const EXPORTER_POLL_MS: u64 = 5;
+
+static NEXT_RECORDER_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // (recorder id, this thread's buffer for that recorder)
+    static LOCAL_BUFFERS: RefCell<Vec<(u64, Arc<ProducerBuffer>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// One producer thread's buffer: only that thread pushes, only the exporter pops.
+struct ProducerBuffer {
+    thread: String,
+    queue: ArrayQueue<Event>,
+    recorded: AtomicU64,
+    dropped: AtomicU64,         // Buffer full, or recorded after shutdown
+}
+
+/// State shared by every clone of one recorder and its exporter.
+struct RecorderShared {
+    id: u64,
+    producers: Mutex<Vec<Arc<ProducerBuffer>>>,
+    closed: AtomicBool,         // Set by `shutdown`; the exporter drains everything and exits
+    exported: AtomicU64,
+}
+
+/// Per-producer event accounting (snapshot).
+#[derive(Debug, Clone, Default)]
+pub struct ProducerStats {
+    pub thread: String,
+    pub recorded: u64,
+    pub dropped: u64,
+}
 
 /// Non-blocking event recorder with background CSV export.
 ///
 ///Timestamps via now_ns() (elapsed nanos from recorder creation).
-///record()` appends to lock-free queue; returns immediately (no blocking).
-///start_exporter() spawns thread that drains queue → CSV file (one event/line).
+///record()` pushes to the calling thread's own buffer (registered on first use); no queue is
+///shared between producers, so recording never contends with another sensor/actuator thread.
+///start_exporter() spawns thread that drains every buffer, merges them by timestamp
+///(MERGE_WINDOW_NS reorder window) → CSV file (one event/line), rotated and flushed per
+///`log_policy` (logwriter.rs). shutdown() is the handshake: the exporter drains all buffers,
+///flushes and exits, so joining its handle guarantees the file is complete.
 ///
-/// Capacity: 16K events; drops silently if queue full (prevents event thread blocking).
+/// Capacity: 8K events per producer; a full buffer drops the event and counts it for that producer.
 pub struct EventRecorder {
-    queue: Arc<ArrayQueue<Event>>,
+    shared: Arc<RecorderShared>,
     run_start: Instant,
+    log_policy: LogPolicy,
 }
 
 impl EventRecorder {
     /// Creates new recorder with internal clock reference.
     pub fn new() -> Self {
         Self {
-            queue: Arc::new(ArrayQueue::new(EVENT_QUEUE_CAPACITY)),
+            shared: Arc::new(RecorderShared {
+                id: NEXT_RECORDER_ID.fetch_add(1, Ordering::Relaxed),
+                producers: Mutex::new(Vec::new()),
+                closed: AtomicBool::new(false),
+                exported: AtomicU64::new(0),
+            }),
             run_start: Instant::now(),
+            log_policy: LogPolicy::default(),
         }
     }
 
-    /// Appends event to queue (lock-free). Silently drops if queue full.
+    /// Rotation / fsync policy of the exported CSV (`[logging]` in config.toml).
+    pub fn with_log_policy(mut self, policy: LogPolicy) -> Self {
+        self.log_policy = policy;
+        self
+    }
+
+    /// Pushes to this thread's buffer (lock-free). Counts a drop if the buffer is full.
     #[inline]
     pub fn record(&self, event: Event) {
-        let _ = self.queue.push(event);
+        LOCAL_BUFFERS.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let buffer = self.local_buffer(&mut cache);
+
+            if self.shared.closed.load(Ordering::Relaxed) || buffer.queue.push(event).is_err() {
+                buffer.dropped.fetch_add(1, Ordering::Relaxed);
+            } else {
+                buffer.recorded.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+
+    /// Registers the calling thread's buffer ahead of its first `record`; RT threads call this
+    /// before `rt_alloc::enter` so recording never allocates.
+    pub fn attach_thread(&self) {
+        LOCAL_BUFFERS.with(|cache| {
+            self.local_buffer(&mut cache.borrow_mut());
+        });
+    }
+
+    /// This thread's buffer for this recorder (registered on first use).
+    fn local_buffer<'a>(&self, cache: &'a mut Vec<(u64, Arc<ProducerBuffer>)>) -> &'a Arc<ProducerBuffer> {
+        match cache.iter().position(|(id, _)| *id == self.shared.id) {
+            Some(i) => &cache[i].1,
+            None => {
+                let b = self.register();
+                // Forget buffers of recorders that no longer exist (cache holds the last reference)
+                cache.retain(|(_, old)| Arc::strong_count(old) > 1);
+                cache.push((self.shared.id, b));
+                &cache[cache.len() - 1].1
+            }
+        }
+    }
+
+    /// First record from this thread: allocate and register its buffer.
+    #[cold]
+    fn register(&self) -> Arc<ProducerBuffer> {
+        let current = thread::current();
+        let buffer = Arc::new(ProducerBuffer {
+            thread: current
+                .name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{:?}", current.id())),
+            queue: ArrayQueue::new(PRODUCER_BUFFER_CAPACITY),
+            recorded: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        });
+        self.shared
+            .producers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(buffer.clone());
+        buffer
     }
 
     /// Nanosecond timestamp since recorder creation.
@@ -131,58 +329,150 @@ impl EventRecorder {
         self.run_start.elapsed().as_nanos() as u64
     }
 
-    /// Spawns background thread draining queue → CSV file.
-    /// Writes header with CPU load config; exits when queue empty + no producers.
+    /// Spawns background thread draining every producer buffer → CSV file, in timestamp order.
+    /// Writes header with CPU load config; runs until `shutdown`.
     pub fn start_exporter(
         &self,
         output_csv: String,
         cpu_load_threads: usize,
     ) -> thread::JoinHandle<()> {
-        let queue = self.queue.clone();
+        let shared = self.shared.clone();
+        let run_start = self.run_start;
+        let policy = self.log_policy.clone();
 
         thread::spawn(move || {
-            match File::create(&output_csv) {
-                Ok(file) => {
-                    let mut writer = BufWriter::new(file);
-                    let _ = writeln!(writer, "# cpu_load_threads={}", cpu_load_threads);
-                    let _ = writeln!(writer, "seq,pipeline,component,event,ts_ns,field1,field2,field3");
-
-                    loop {
-                        match queue.pop() {
-                            Some(event) => {
-                                let _ = writeln!(writer, "{}", event.to_csv_row());
-                            }
-                            None => {
-                                // Exit: queue empty + all producers dropped
-                                thread::sleep(Duration::from_millis(10));
-                                if queue.is_empty() {
-                                    break;
-                                }
-                            }
-                        }
+            // Header repeated at the top of every rotated segment
+            let header = format!(
+                "# cpu_load_threads={}\nseq,pipeline,component,event,ts_ns,field1,field2,field3\n",
+                cpu_load_threads
+            );
+            let mut writer = match LogWriter::create(&output_csv, header, policy) {
+                Ok(writer) => Some(writer),
+                Err(e) => {
+                    error!("Failed to create event CSV: {}", e);
+                    None    // Keep draining so producers see no difference
+                }
+            };
+
+            // Min-heap on (ts, arrival): events wait MERGE_WINDOW_NS for slower producers
+            let mut pending: BinaryHeap<Reverse<PendingEvent>> = BinaryHeap::new();
+            let mut arrival = 0u64;
+
+            loop {
+                // Read before draining: anything recorded before shutdown is drained in this pass
+                let closing = shared.closed.load(Ordering::Acquire);
+
+                let producers = shared.producers.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                let mut drained = 0usize;
+                for p in &producers {
+                    while let Some(event) = p.queue.pop() {
+                        pending.push(Reverse(PendingEvent { ts_ns: event.ts_ns(), arrival, event }));
+                        arrival += 1;
+                        drained += 1;
                     }
+                }
 
-                    let _ = writer.flush();
+                let horizon = if closing {
+                    u64::MAX
+                } else {
+                    (run_start.elapsed().as_nanos() as u64).saturating_sub(MERGE_WINDOW_NS)
+                };
+                while pending.peek().is_some_and(|Reverse(p)| p.ts_ns <= horizon) {
+                    let Some(Reverse(p)) = pending.pop() else { break };
+                    if let Some(w) = writer.as_mut() {
+                        let _ = writeln!(w, "{}", p.event.to_csv_row());
+                    }
+                    shared.exported.fetch_add(1, Ordering::Relaxed);
                 }
-                Err(e) => {
-                    error!("Failed to create event CSV: {}", e);
+
+                if closing {
+                    break;
                 }
+                if drained == 0 {
+                    thread::sleep(Duration::from_millis(EXPORTER_POLL_MS));
+                }
+            }
+
+            if let Some(mut w) = writer {
+                let _ = w.flush();
             }
         })
     }
+
+    /// Shutdown handshake: the exporter drains all buffers, flushes and exits
+    /// (join the handle from `start_exporter` to wait for it). Later records count as drops.
+    pub fn shutdown(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+    }
+
+    /// Recorded / dropped events per producer thread, in registration order.
+    pub fn producer_stats(&self) -> Vec<ProducerStats> {
+        self.shared
+            .producers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|p| ProducerStats {
+                thread: p.thread.clone(),
+                recorded: p.recorded.load(Ordering::Relaxed),
+                dropped: p.dropped.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Events written by the exporter so far.
+    pub fn exported_count(&self) -> u64 {
+        self.shared.exported.load(Ordering::Relaxed)
+    }
+}
+
+/// Exporter merge entry, ordered by (timestamp, arrival).
+struct PendingEvent {
+    ts_ns: u64,
+    arrival: u64,
+    event: Event,
+}
+
+impl PartialEq for PendingEvent {
+    fn eq(&self, other: &Self) -> bool {
+        (self.ts_ns, self.arrival) == (other.ts_ns, other.arrival)
+    }
+}
+
+impl Eq for PendingEvent {}
+
+impl PartialOrd for PendingEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingEvent {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.ts_ns, self.arrival).cmp(&(other.ts_ns, other.arrival))
+    }
+}
+
+impl Default for EventRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Clone for EventRecorder {
     fn clone(&self) -> Self {
         Self {
-            queue: self.queue.clone(),
+            shared: self.shared.clone(),
             run_start: self.run_start,
+            log_policy: self.log_policy.clone(),
         }
     }
 }
 
-/// Live dashboard metrics: sensor readings, actuator outputs, latency histograms, deadline misses.
-/// Updated in real-time by subsystems; bounded to 1000 most recent points per metric.
+/// Dashboard metrics snapshot: sensor readings, actuator outputs, latency histograms, deadline misses.
+/// The series and counters come from `LiveMetrics` (written lock-free by the RT threads); the
+/// run-level fields (load, bus, sync, event accounting) are written by the run loop.
+/// Bounded to 1000 most recent points per metric.
 #[derive(Default, Clone)]
 pub struct Metrics {
     /// Sensor readings (last 1000 samples)
@@ -207,8 +497,107 @@ pub struct Metrics {
     /// Total deadline misses across all components
     pub deadline_miss: u64,
 
+    /// Stale-command rejections per actuator (freshness limit exceeded; not deadline misses)
+    pub stale_rejections: BTreeMap<&'static str, u64>,
+
+    /// Onsets of saturation and of rate limiting per actuator (actuator model)
+    pub saturations: BTreeMap<&'static str, u64>,
+    pub rate_limits: BTreeMap<&'static str, u64>,
+
     pub total_cycles: u64,
     pub cpu_load_threads: usize,
+
+    /// Receiver-side sequence accounting per sensor (keyed by sensor name)
+    pub seq_stats: BTreeMap<&'static str, SequenceStats>,
+
+    /// Pub/sub bus delivery per subscriber (refreshed periodically by the run loop)
+    pub bus_stats: Vec<SubscriberStats>,
+
+    /// SyncManager strategy and its diagnostics (refreshed by the run loop, final after shutdown)
+    pub sync_mode: Option<SyncMode>,
+    pub sync_diagnostics: Diagnostics,
+
+    /// Per-thread lock contention (only when `[sync] instrument = true`)
+    pub sync_contention: Option<ContentionStats>,
+
+    /// Control-law compute time per law (keyed by `ControlLaw::name`), against the actuator deadline
+    pub control_laws: BTreeMap<&'static str, ComputeTiming>,
+
+    /// Latest relay auto-tune result per controller (keyed by controller name)
+    pub autotune: BTreeMap<&'static str, AutotuneResult>,
+
+    /// Control quality per actuator (step response, error integrals), running segment included
+    pub control_quality: BTreeMap<&'static str, ControlQuality>,
+
+    /// Fail-safe system mode, time per mode and the last transition
+    pub mode: ModeStatus,
+
+    /// Actuator fault detection per injected scenario, baseline first (detector thread)
+    pub fault_detection: Vec<FaultScore>,
+
+    /// Sensor → actuator deliveries per route, fan-out per sensor, fan-in per actuator (run loop)
+    pub routing: RoutingStats,
+
+    /// EventRecorder accounting per producer thread, and events the exporter wrote (end of run)
+    pub event_producers: Vec<ProducerStats>,
+    pub events_exported: u64,
+}
+
+/// Receiver-side sequence accounting for one sensor stream.
+/// `lost` counts sequence numbers never received; late arrivals inside the history window are recovered.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SequenceStats {
+    pub expected: u64,
+    pub received: u64,
+    pub lost: u64,
+    pub gaps: u64,
+    pub duplicates: u64,
+    pub out_of_order: u64,
+    pub late_discarded: u64,
+    pub max_burst_loss: u64,
+}
+
+impl SequenceStats {
+    /// Fraction of expected packets never received (0.0 before the first packet).
+    pub fn loss_rate(&self) -> f64 {
+        if self.expected == 0 {
+            0.0
+        } else {
+            self.lost as f64 / self.expected as f64
+        }
+    }
+}
+
+/// Log2-bucketed nanosecond histogram of one computation (sub-µs resolution for control laws).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ComputeTiming {
+    pub count: u64,
+    pub sum_ns: u64,
+    pub max_ns: u64,
+    pub over_deadline: u64,                 // Calls longer than the actuator deadline
+    pub buckets: [u64; TIMING_BUCKETS],     // buckets[i]: values in [2^(i-1), 2^i) ns
+}
+
+pub const TIMING_BUCKETS: usize = 32;      // Top bucket: ≥ 2^30 ns (~1 s)
+
+impl ComputeTiming {
+    pub fn mean_ns(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum_ns as f64 / self.count as f64 }
+    }
+
+    /// Upper bound of the bucket holding the `p` quantile (0..=1), capped at the exact max.
+    pub fn percentile_ns(&self, p: f64) -> u64 {
+        let rank = ((p.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                let upper = if i == 0 { 0 } else { (1u64 << i) - 1 };
+                return upper.min(self.max_ns);
+            }
+        }
+        self.max_ns
+    }
 }
 
 /// Component identifier for deadline miss attribution.
@@ -219,39 +608,48 @@ pub enum DeadlineComponent {
 }
 
 impl Metrics {
-    /// Records deadline miss for specified component; updates total count.
-    pub fn record_deadline_miss(&mut self, component: DeadlineComponent) {
-        match component {
-            DeadlineComponent::Sensor => self.miss_sensor += 1,
-            DeadlineComponent::Processor => self.miss_processor += 1,
-            DeadlineComponent::Actuator => self.miss_actuator += 1,
-        }
-        self.deadline_miss += 1;
+    /// Stale-command rejections across all actuators.
+    pub fn stale_total(&self) -> u64 {
+        self.stale_rejections.values().sum()
     }
 }
 
-pub type SharedMetrics = Arc<Mutex<Metrics>>;
+/// Metrics shared by the pipeline: lock-free live series for the RT threads, run-level fields
+/// behind a mutex that only non-RT threads (run loop, exporters) take.
+#[derive(Clone, Default)]
+pub struct SharedMetrics {
+    live: Arc<LiveMetrics>,
+    run: Arc<Mutex<Metrics>>,       // Series / counter fields unused here: they live in `live`
+}
 
-pub const MAX_POINTS: usize = 1_000;
+impl SharedMetrics {
+    /// Lock-free writer side for sensor, processor, controller and actuator threads.
+    #[inline]
+    pub fn live(&self) -> &LiveMetrics {
+        &self.live
+    }
 
-/// Appends value to metrics buffer; removes oldest if at capacity (FIFO).
-#[inline]
-pub fn push_capped(buf: &mut VecDeque<f64>, val: f64) {
-    if buf.len() >= MAX_POINTS {
-        buf.pop_front();
+    /// Run-level fields (load, bus, sync, event accounting); never from an RT thread.
+    pub fn lock_run(&self) -> MutexGuard<'_, Metrics> {
+        self.run.lock().unwrap_or_else(|e| e.into_inner())
     }
-    buf.push_back(val);
-}
 
-/// Appends u64 value to metrics buffer; removes oldest if at capacity.
-#[inline]
-pub fn push_capped_u64(buf: &mut VecDeque<u64>, val: u64) {
-    if buf.len() >= MAX_POINTS {
-        buf.pop_front();
+    /// Complete copy for readers; RT writers are never blocked.
+    pub fn snapshot(&self) -> Metrics {
+        let mut m = self.lock_run().clone();
+        self.live.fill(&mut m);
+        m
+    }
+
+    /// Clears everything between runs of a sweep (no writers running).
+    pub fn reset(&self) {
+        *self.lock_run() = Metrics::default();
+        self.live.clear();
     }
-    buf.push_back(val);
 }
 
+pub const MAX_POINTS: usize = 1_000;
+
 /// Statistics summary for a dataset.
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -17,7 +17,7 @@ use std::{
 /// Computes: deadline miss rate (%), max jitter, average latency from shared metrics.
 ///
 /// # Arguments
-/// * `metrics` — Shared metrics buffer (locked to read final state).
+/// * `metrics` — Shared metrics (snapshot of the final state).
 /// * `cpu_load_threads` — Number of background threads for this experiment (row identifier).
 pub fn export_summary_csv(metrics: &SharedMetrics, cpu_load_threads: usize) {
     let _ = create_dir_all("data");
@@ -25,11 +25,8 @@ pub fn export_summary_csv(metrics: &SharedMetrics, cpu_load_threads: usize) {
     let csv_path = "data/logs/cpu_load_results.csv";
     let file_exists = Path::new(csv_path).exists();
 
-    // Lock metrics; recover from poisoned state (shouldn't happen but safe)
-    let m = match metrics.lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
+    // Non-blocking snapshot of the final state
+    let m = metrics.snapshot();
 
     // Calculate statistics from bounded metrics buffers
     let max_jitter = m.jitter_us.iter().copied().max().unwrap_or(0);
@@ -68,11 +65,11 @@ pub fn export_summary_csv(metrics: &SharedMetrics, cpu_load_threads: usize) {
         .open(csv_path)
     {
         Ok(mut file) => {
-            if !file_exists {
-                if let Err(e) = file.write_all(header.as_bytes()) {
-                    eprintln!("[ERROR] Failed to write CSV header: {}", e);
-                    return;
-                }
+            if !file_exists
+                && let Err(e) = file.write_all(header.as_bytes())
+            {
+                eprintln!("[ERROR] Failed to write CSV header: {}", e);
+                return;
             }
 
             match file.write_all(row.as_bytes()) {
@@ -1,6 +1,12 @@
 
 //Shared utility modules for config, logging, and timing
 pub mod metrics;
+pub mod live_metrics;
 pub mod metrics_export;
 pub mod export;
+pub mod bus;
+pub mod config;
+pub mod logwriter;
+pub mod rt_alloc;
+pub mod control_quality;
 
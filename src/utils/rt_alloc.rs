@@ -0,0 +1,109 @@
+//! rt_alloc.rs
+//! Allocation guard for the real-time path (sensor loop, processor cycle, controller, actuators).
+//!
+//! - `RtAllocator` wraps the system allocator; the simulation binary installs it in debug
+//!   builds only (`#[cfg(debug_assertions)] #[global_allocator]`), release builds pay nothing.
+//! - An RT thread calls `enter("processor")` once its startup work (buffers, registrations)
+//!   is done; every allocation or reallocation made by that thread afterwards is a violation.
+//! - The first violation per section is printed with its size and a backtrace; later ones are
+//!   only counted. The section total is logged when the guard drops; `violations()` is the
+//!   process-wide total (always 0 when the allocator is not installed).
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    backtrace::Backtrace,
+    cell::Cell,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use log::warn;
+
+static VIOLATIONS: AtomicU64 = AtomicU64::new(0);
+
+// Const-initialised, no destructor: safe to touch from inside the allocator
+thread_local! {
+    static SECTION: Cell<Option<&'static str>> = const { Cell::new(None) };   // RT section this thread is in
+    static COUNT: Cell<u64> = const { Cell::new(0) };                          // Violations in that section
+    static REPORTING: Cell<bool> = const { Cell::new(false) };                 // The report's own allocations are not counted
+}
+
+/// System allocator that flags allocations made on a thread inside an RT section.
+pub struct RtAllocator;
+
+unsafe impl GlobalAlloc for RtAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        check(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        check(layout.size());
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        check(new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+fn check(size: usize) {
+    let _ = SECTION.try_with(|section| {
+        let Some(name) = section.get() else { return };
+        if REPORTING.get() {
+            return;
+        }
+
+        VIOLATIONS.fetch_add(1, Ordering::Relaxed);
+        let count = COUNT.get() + 1;
+        COUNT.set(count);
+
+        if count == 1 {
+            // stderr, not the logger: its lock is not reentrant if the logger itself allocated
+            REPORTING.set(true);
+            eprintln!(
+                "[rt_alloc] {}: {} byte allocation on RT thread\n{}",
+                name,
+                size,
+                Backtrace::force_capture()
+            );
+            REPORTING.set(false);
+        }
+    });
+}
+
+/// Marks the current thread as real-time until dropped.
+#[must_use = "the RT section ends when the guard is dropped"]
+pub struct RtSection {
+    name: &'static str,
+    previous: Option<&'static str>,
+    previous_count: u64,
+}
+
+/// Enters an RT section on the current thread; call after the thread's startup allocations.
+pub fn enter(name: &'static str) -> RtSection {
+    RtSection {
+        name,
+        previous: SECTION.replace(Some(name)),
+        previous_count: COUNT.replace(0),
+    }
+}
+
+impl Drop for RtSection {
+    fn drop(&mut self) {
+        let count = COUNT.replace(self.previous_count);
+        SECTION.set(self.previous);
+        if count > 0 {
+            warn!("[rt_alloc] {}: {} allocations on RT thread", self.name, count);
+        }
+    }
+}
+
+/// Allocations made inside RT sections so far, all threads.
+pub fn violations() -> u64 {
+    VIOLATIONS.load(Ordering::Relaxed)
+}